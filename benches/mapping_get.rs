@@ -0,0 +1,74 @@
+//! Benchmark looking up keys in a wide mapping through [`Mapping::get`]
+//! compared to [`Mapping::get_str`].
+//!
+//! Run with `cargo bench`. When built as a test, every benchmark only runs
+//! once to check that it works.
+//!
+//! [`Mapping::get`]: nondestructive::yaml::Mapping::get
+//! [`Mapping::get_str`]: nondestructive::yaml::Mapping::get_str
+
+use std::env;
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use nondestructive::yaml;
+
+const KEYS: usize = 1000;
+const ITERATIONS: u32 = 100;
+
+fn main() -> Result<()> {
+    let iterations = if env::args().any(|arg| arg == "--bench") {
+        ITERATIONS
+    } else {
+        1
+    };
+
+    let mut input = String::new();
+
+    for n in 0..KEYS {
+        writeln!(input, "key{n}: value{n}")?;
+    }
+
+    let doc = yaml::from_slice(&input)?;
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    let keys = (0..KEYS).map(|n| format!("key{n}")).collect::<Vec<_>>();
+
+    let get = bench(iterations, || {
+        for key in &keys {
+            black_box(root.get(key).and_then(|v| v.as_bstr()));
+        }
+    });
+
+    let get_str = bench(iterations, || {
+        for key in &keys {
+            black_box(root.get_str(key));
+        }
+    });
+
+    report("get", get, iterations);
+    report("get_str", get_str, iterations);
+    Ok(())
+}
+
+/// Run the given function the given number of times, returning the total time
+/// it took.
+fn bench(iterations: u32, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+        f();
+    }
+
+    start.elapsed()
+}
+
+fn report(name: &str, elapsed: Duration, iterations: u32) {
+    let lookups = u32::try_from(KEYS)
+        .unwrap_or(u32::MAX)
+        .saturating_mul(iterations);
+    let per_lookup = elapsed / lookups.max(1);
+    println!("{name}: {per_lookup:?} per lookup ({lookups} lookups in {elapsed:?})");
+}
@@ -3,13 +3,15 @@ use std::fmt;
 use std::hash::Hash;
 use std::mem;
 use std::num::NonZeroUsize;
+use std::ops::Range;
 
-use bstr::BStr;
+use bstr::{BStr, ByteSlice};
 #[cfg(feature = "serde-edits")]
 use serde::{Deserialize, Serialize};
 use twox_hash::xxh3::{Hash128, HasherExt};
 
 use crate::yaml::raw;
+use crate::yaml::IndentConfig;
 
 /// The unique hash of a string.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -87,6 +89,13 @@ impl fmt::Display for Id {
 pub(crate) struct Entry {
     raw: raw::Raw,
     layout: raw::Layout,
+    /// The span of the value in the input it was parsed from.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    span: Option<Range<usize>>,
+    /// If the value is as it was parsed. Unlike the span this is kept when the
+    /// value is copied, and is also set for values parsed from fragments.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    parsed: bool,
 }
 
 /// Strings cache.
@@ -95,9 +104,118 @@ pub(crate) struct Entry {
 pub(crate) struct Data {
     strings: HashMap<StringId, Box<[u8]>>,
     slab: slab::Slab<Entry>,
+    /// Whitespace and comments following the root value of the document.
+    suffix: Option<StringId>,
+    /// Indentation used for freshly created collections.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    indent: IndentConfig,
+    /// If the document was parsed with the YAML 1.1 spellings of booleans,
+    /// in which case strings using them have to be quoted.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    yaml_1_1_bools: bool,
+    /// The offsets at which the lines spanned by the document start in the
+    /// input it was parsed from.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    lines: Vec<usize>,
+    /// The zero-based number of the first line in `lines`, since a document
+    /// in a stream doesn't necessarily start on the first line of the input.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    first_line: usize,
 }
 
 impl Data {
+    /// Get the whitespace and comments following the root value of the
+    /// document.
+    #[inline]
+    pub(crate) fn suffix(&self) -> &BStr {
+        match self.suffix {
+            Some(suffix) => self.str(suffix),
+            None => BStr::new(b""),
+        }
+    }
+
+    /// Set the whitespace and comments following the root value of the
+    /// document.
+    #[inline]
+    pub(crate) fn set_suffix(&mut self, suffix: StringId) {
+        self.suffix = Some(suffix);
+    }
+
+    /// Get the indentation used for freshly created collections.
+    #[inline]
+    pub(crate) fn indent(&self) -> IndentConfig {
+        self.indent
+    }
+
+    /// Set the indentation used for freshly created collections.
+    #[inline]
+    pub(crate) fn set_indent(&mut self, indent: IndentConfig) {
+        self.indent = indent;
+    }
+
+    /// Test if the YAML 1.1 spellings of booleans are recognized in the
+    /// document.
+    #[inline]
+    pub(crate) fn yaml_1_1_bools(&self) -> bool {
+        self.yaml_1_1_bools
+    }
+
+    /// Set if the YAML 1.1 spellings of booleans are recognized in the
+    /// document.
+    #[inline]
+    pub(crate) fn set_yaml_1_1_bools(&mut self, yaml_1_1_bools: bool) {
+        self.yaml_1_1_bools = yaml_1_1_bools;
+    }
+
+    /// Set the offsets at which the lines spanned by the document start in the
+    /// input it was parsed from, where the first of them is the zero-based
+    /// line `first_line`.
+    #[inline]
+    pub(crate) fn set_lines(&mut self, lines: Vec<usize>, first_line: usize) {
+        self.lines = lines;
+        self.first_line = first_line;
+    }
+
+    /// Get the 1-based line and column of the given offset in the input the
+    /// document was parsed from.
+    pub(crate) fn location(&self, offset: usize) -> Option<(usize, usize)> {
+        let line = self.lines.partition_point(|start| *start <= offset);
+        let start = *self.lines.get(line.checked_sub(1)?)?;
+        let line = line.wrapping_add(self.first_line);
+        Some((line, offset.wrapping_sub(start).wrapping_add(1)))
+    }
+
+    /// Get the span of the value with the given identifier in the input it was
+    /// parsed from.
+    #[inline]
+    pub(crate) fn span(&self, id: Id) -> Option<Range<usize>> {
+        self.slab.get(id.get())?.span.clone()
+    }
+
+    /// Set the span of the value with the given identifier.
+    #[inline]
+    pub(crate) fn set_span(&mut self, id: Id, span: Range<usize>) {
+        if let Some(value) = self.slab.get_mut(id.get()) {
+            value.span = Some(span);
+        }
+    }
+
+    /// Test if the value with the given identifier is as it was parsed, either
+    /// from the input of the document or from a fragment of YAML, as opposed to
+    /// having been set or inserted through an edit.
+    #[inline]
+    pub(crate) fn is_parsed(&self, id: Id) -> bool {
+        self.slab.get(id.get()).is_some_and(|value| value.parsed)
+    }
+
+    /// Mark the value with the given identifier as parsed.
+    #[inline]
+    pub(crate) fn set_parsed(&mut self, id: Id) {
+        if let Some(value) = self.slab.get_mut(id.get()) {
+            value.parsed = true;
+        }
+    }
+
     /// Get a string.
     #[inline]
     #[must_use]
@@ -141,6 +259,62 @@ impl Data {
         self.str(self.layout(id).prefix)
     }
 
+    /// Get the collection containing the value with the given identifier,
+    /// skipping over the mapping or sequence item it belongs to.
+    #[inline]
+    pub(crate) fn parent(&self, id: Id) -> Option<Id> {
+        let item = self.layout(id).parent?;
+        self.layout(item).parent
+    }
+
+    /// Set the prefix of a value.
+    #[inline]
+    pub(crate) fn set_prefix(&mut self, id: Id, prefix: StringId) {
+        if let Some(value) = self.slab.get_mut(id.get()) {
+            value.layout.prefix = prefix;
+        }
+    }
+
+    /// Mark a value as detached from the document.
+    #[inline]
+    pub(crate) fn set_detached(&mut self, id: Id) {
+        if let Some(value) = self.slab.get_mut(id.get()) {
+            value.layout.detached = true;
+        }
+    }
+
+    /// Get the tag associated with a value, including the whitespace which
+    /// preceeds it.
+    #[inline]
+    pub(crate) fn tag(&self, id: Id) -> Option<&BStr> {
+        Some(self.str(self.layout(id).tag?))
+    }
+
+    /// Get the name of the anchor associated with a value, without the
+    /// leading `&`.
+    #[inline]
+    pub(crate) fn anchor(&self, id: Id) -> Option<&BStr> {
+        let anchor = self.str(self.layout(id).anchor?.string);
+        let anchor = anchor.trim_start().strip_prefix(b"&")?;
+        Some(anchor.as_bstr())
+    }
+
+    /// Set the anchor associated with a value.
+    #[inline]
+    pub(crate) fn set_anchor(&mut self, id: Id, anchor: Option<raw::Anchor>) {
+        if let Some(value) = self.slab.get_mut(id.get()) {
+            value.layout.anchor = anchor;
+        }
+    }
+
+    /// Set the tag associated with a value.
+    #[inline]
+    pub(crate) fn set_tag(&mut self, id: Id, tag: Option<StringId>) {
+        if let Some(value) = self.slab.get_mut(id.get()) {
+            value.layout.tag = tag;
+        }
+    }
+
     #[inline]
     pub(crate) fn pair(&self, id: Id) -> (&raw::Raw, &raw::Layout) {
         if let Some(raw) = self.slab.get(id.get()) {
@@ -246,17 +420,359 @@ impl Data {
         panic!("expected mapping at {id}")
     }
 
+    /// Walk over the value with the given identifier and all of the values it
+    /// contains in depth-first order, skipping over the mapping and sequence
+    /// items which associates values with collections.
+    pub(crate) fn walk(&self, id: Id) -> Walk<'_> {
+        Walk {
+            data: self,
+            stack: vec![id],
+        }
+    }
+
+    /// Add `extra` spaces of indentation to every line inside of the value
+    /// with the given identifier, not including its own prefix. If `extra` is
+    /// negative, indentation is removed instead.
+    pub(crate) fn indent_by(&mut self, id: Id, extra: isize) {
+        let mut stack = vec![(id, false)];
+
+        while let Some((id, prefix)) = stack.pop() {
+            if prefix {
+                self.indent_prefix(id, extra);
+            }
+
+            let (items, suffix) = match self.raw(id) {
+                raw::Raw::Mapping(raw) => (
+                    raw.items.clone(),
+                    match raw.kind {
+                        raw::MappingKind::Inline { suffix, .. } => Some(suffix),
+                        raw::MappingKind::Mapping => None,
+                    },
+                ),
+                raw::Raw::Sequence(raw) => (
+                    raw.items.clone(),
+                    match raw.kind {
+                        raw::SequenceKind::Inline { suffix, .. } => Some(suffix),
+                        raw::SequenceKind::Mapping => None,
+                    },
+                ),
+                raw::Raw::String(raw) => {
+                    let original = self.strings_indent(raw.original, extra);
+
+                    if let raw::Raw::String(raw) = self.raw_mut(id) {
+                        raw.original = original;
+                    }
+
+                    continue;
+                }
+                _ => continue,
+            };
+
+            let new_suffix = suffix.map(|suffix| self.strings_indent(suffix, extra));
+
+            match self.raw_mut(id) {
+                raw::Raw::Mapping(raw) => {
+                    raw.indent = raw.indent.saturating_add_signed(extra);
+
+                    if let (raw::MappingKind::Inline { suffix, .. }, Some(new)) =
+                        (&mut raw.kind, new_suffix)
+                    {
+                        *suffix = new;
+                    }
+                }
+                raw::Raw::Sequence(raw) => {
+                    raw.indent = raw.indent.saturating_add_signed(extra);
+
+                    if let (raw::SequenceKind::Inline { suffix, .. }, Some(new)) =
+                        (&mut raw.kind, new_suffix)
+                    {
+                        *suffix = new;
+                    }
+                }
+                _ => {}
+            }
+
+            for item in items.into_iter().rev() {
+                self.indent_prefix(item, extra);
+
+                let (value, suffix) = match self.raw(item) {
+                    raw::Raw::MappingItem(raw) => (raw.value, raw.suffix),
+                    raw::Raw::SequenceItem(raw) => (raw.value, raw.suffix),
+                    _ => continue,
+                };
+
+                if let Some(suffix) = suffix {
+                    let suffix = self.strings_indent(suffix, extra);
+
+                    if let Some(entry) = self.slab.get_mut(item.get()) {
+                        match &mut entry.raw {
+                            raw::Raw::MappingItem(raw) => raw.suffix = Some(suffix),
+                            raw::Raw::SequenceItem(raw) => raw.suffix = Some(suffix),
+                            _ => {}
+                        }
+                    }
+                }
+
+                stack.push((value, true));
+            }
+        }
+    }
+
+    /// Add indentation to the prefix and the tag of the given value.
+    pub(crate) fn indent_prefix(&mut self, id: Id, extra: isize) {
+        let Some(layout) = self.slab.get(id.get()).map(|entry| entry.layout) else {
+            return;
+        };
+
+        let prefix = self.strings_indent(layout.prefix, extra);
+        let tag = layout.tag.map(|tag| self.strings_indent(tag, extra));
+        let anchor = layout.anchor.map(|anchor| raw::Anchor {
+            string: self.strings_indent(anchor.string, extra),
+            ..anchor
+        });
+
+        if let Some(entry) = self.slab.get_mut(id.get()) {
+            entry.layout.prefix = prefix;
+            entry.layout.tag = tag;
+            entry.layout.anchor = anchor;
+        }
+    }
+
+    /// Add indentation to every line in the given string after the first.
+    fn strings_indent(&mut self, string: StringId, extra: isize) -> StringId {
+        match raw::indent_lines(self.str(string), extra) {
+            Some(string) => self.insert_str(string),
+            None => string,
+        }
+    }
+
+    /// Move the value `from` into the place of the value `to`, replacing it
+    /// with the given prefix.
+    ///
+    /// The tag and span of the moved value are retained, and any items it
+    /// contains are updated to belong to the new value.
+    pub(crate) fn relocate(&mut self, from: Id, to: Id, prefix: StringId) {
+        let Some(entry) = self.slab.try_remove(from.get()) else {
+            return;
+        };
+
+        let items = match &entry.raw {
+            raw::Raw::Mapping(raw) => &raw.items[..],
+            raw::Raw::Sequence(raw) => &raw.items[..],
+            _ => &[],
+        };
+
+        for item in items {
+            if let Some(item) = self.slab.get_mut(item.get()) {
+                item.layout.parent = Some(to);
+            }
+        }
+
+        let Some(value) = self.slab.get_mut(to.get()) else {
+            self.drop_kind(entry.raw);
+            return;
+        };
+
+        value.layout.prefix = prefix;
+        value.layout.tag = entry.layout.tag;
+        value.layout.anchor = entry.layout.anchor;
+        value.span = entry.span;
+        value.parsed = entry.parsed;
+        let removed = mem::replace(&mut value.raw, entry.raw);
+        self.drop_kind(removed);
+    }
+
+    /// Strip trailing spaces and tabs from every line in the whitespace which
+    /// is stored as part of the document, such as prefixes and separators.
+    ///
+    /// The contents of strings are not modified.
+    pub(crate) fn trim_trailing_whitespace(&mut self) {
+        let indexes = self.slab.iter().map(|(index, _)| index).collect::<Vec<_>>();
+
+        for index in indexes {
+            let Some(entry) = self.slab.get_mut(index) else {
+                continue;
+            };
+
+            let layout = entry.layout;
+            let suffix = suffix_mut(&mut entry.raw).copied();
+
+            let prefix = self.trim_lines(layout.prefix);
+            let tag = layout.tag.map(|tag| self.trim_lines(tag));
+            let suffix = suffix.map(|suffix| self.trim_lines(suffix));
+            let anchor = layout.anchor.map(|anchor| raw::Anchor {
+                string: self.trim_lines(anchor.string),
+                ..anchor
+            });
+
+            let Some(entry) = self.slab.get_mut(index) else {
+                continue;
+            };
+
+            entry.layout.prefix = prefix;
+            entry.layout.tag = tag;
+            entry.layout.anchor = anchor;
+
+            if let (Some(to), Some(from)) = (suffix_mut(&mut entry.raw), suffix) {
+                *to = from;
+            }
+        }
+    }
+
+    /// Strip trailing spaces and tabs from every line in the given string.
+    fn trim_lines(&mut self, string: StringId) -> StringId {
+        match raw::trim_lines(self.str(string)) {
+            Some(string) => self.insert_str(string),
+            None => string,
+        }
+    }
+
     /// Insert a raw value and return its identifier.
     #[inline]
     pub(crate) fn insert(&mut self, raw: raw::Raw, prefix: StringId, parent: Option<Id>) -> Id {
         let index = self.slab.insert(Entry {
             raw,
-            layout: raw::Layout { prefix, parent },
+            layout: raw::Layout {
+                prefix,
+                parent,
+                tag: None,
+                anchor: None,
+                detached: false,
+            },
+            span: None,
+            parsed: false,
         });
         let index = NonZeroUsize::new(index.wrapping_add(1)).expect("ran out of ids");
         Id(index)
     }
 
+    /// Recursively copy the value with the given identifier, returning the
+    /// identifier of the copy which belongs to the given parent.
+    ///
+    /// Strings are immutable once interned, so they are shared with the
+    /// original. Anchors are not copied, so that the copy doesn't redefine
+    /// them.
+    pub(crate) fn clone_tree(&mut self, id: Id, parent: Option<Id>) -> Id {
+        self.copy_tree(None, id, parent)
+    }
+
+    /// Recursively copy the value with the given identifier from another
+    /// document, returning the identifier of the copy which belongs to the
+    /// given parent.
+    ///
+    /// Anchors are copied along with the value, so that any aliases inside of
+    /// it still refer to them.
+    pub(crate) fn import_tree(&mut self, other: &Data, id: Id, parent: Option<Id>) -> Id {
+        self.copy_tree(Some(other), id, parent)
+    }
+
+    /// Copy the strings referenced by a raw value and its layout from another
+    /// document.
+    ///
+    /// Strings are identified by their contents, so strings which are already
+    /// present in this document are shared.
+    fn import_strings(&mut self, other: &Data, raw: &raw::Raw, layout: &raw::Layout) {
+        let mut strings = vec![layout.prefix];
+        strings.extend(layout.tag);
+        strings.extend(layout.anchor.map(|anchor| anchor.string));
+
+        match raw {
+            raw::Raw::Null(raw::Null::Keyword(string)) => strings.push(*string),
+            raw::Raw::Null(..) => {}
+            raw::Raw::Boolean(raw) => strings.push(raw.string),
+            raw::Raw::Number(raw) => strings.push(raw.string),
+            raw::Raw::String(raw) => strings.extend([raw.id, raw.original]),
+            raw::Raw::Alias(raw) => strings.push(raw.name),
+            raw::Raw::Mapping(raw) => {
+                if let raw::MappingKind::Inline { suffix, .. } = raw.kind {
+                    strings.push(suffix);
+                }
+            }
+            raw::Raw::MappingItem(raw) => {
+                strings.extend([raw.key.id, raw.key.original]);
+                strings.extend(raw.suffix);
+            }
+            raw::Raw::Sequence(raw) => {
+                if let raw::SequenceKind::Inline { suffix, .. } = raw.kind {
+                    strings.push(suffix);
+                }
+            }
+            raw::Raw::SequenceItem(raw) => strings.extend(raw.suffix),
+        }
+
+        for id in strings {
+            if let (hash_map::Entry::Vacant(e), Some(string)) =
+                (self.strings.entry(id), other.strings.get(&id))
+            {
+                e.insert(string.clone());
+            }
+        }
+    }
+
+    /// Recursively copy a value either from the given document, or from this
+    /// one if `other` is `None`.
+    fn copy_tree(&mut self, other: Option<&Data>, id: Id, parent: Option<Id>) -> Id {
+        let (raw, layout) = other.unwrap_or(self).pair(id);
+        let (raw, layout) = (raw.clone(), *layout);
+
+        if let Some(other) = other {
+            self.import_strings(other, &raw, &layout);
+        }
+
+        let new = self.insert(raw.clone(), layout.prefix, parent);
+        self.set_tag(new, layout.tag);
+
+        if other.unwrap_or(self).is_parsed(id) {
+            self.set_parsed(new);
+        }
+
+        if other.is_some() {
+            self.set_anchor(new, layout.anchor);
+        }
+
+        match raw {
+            raw::Raw::Mapping(raw) => {
+                let items = raw
+                    .items
+                    .iter()
+                    .map(|item| self.copy_tree(other, *item, Some(new)))
+                    .collect();
+
+                if let raw::Raw::Mapping(raw) = self.raw_mut(new) {
+                    raw.items = items;
+                }
+            }
+            raw::Raw::Sequence(raw) => {
+                let items = raw
+                    .items
+                    .iter()
+                    .map(|item| self.copy_tree(other, *item, Some(new)))
+                    .collect();
+
+                if let raw::Raw::Sequence(raw) = self.raw_mut(new) {
+                    raw.items = items;
+                }
+            }
+            raw::Raw::MappingItem(raw) => {
+                let value = self.copy_tree(other, raw.value, Some(new));
+
+                if let raw::Raw::MappingItem(raw) = self.raw_mut(new) {
+                    raw.value = value;
+                }
+            }
+            raw::Raw::SequenceItem(raw) => {
+                let value = self.copy_tree(other, raw.value, Some(new));
+
+                if let raw::Raw::SequenceItem(raw) = self.raw_mut(new) {
+                    raw.value = value;
+                }
+            }
+            _ => {}
+        }
+
+        new
+    }
+
     /// Drop a value recursively.
     #[inline]
     pub(crate) fn drop(&mut self, id: Id) {
@@ -294,26 +810,135 @@ impl Data {
     }
 
     /// Replace a raw value.
+    ///
+    /// Since the tag and span of a value describe its content, they are
+    /// cleared and the value is no longer considered parsed. An empty
+    /// null which immediately follows the `:` of a mapping item or the `-` of a
+    /// block sequence item is separated from it by a space once it's replaced
+    /// with something else.
     pub(crate) fn replace<T>(&mut self, id: Id, raw: T)
     where
         T: Into<raw::Raw>,
     {
+        let raw = raw.into();
+
+        let Some(existing) = self.slab.get(id.get()) else {
+            return;
+        };
+
+        let separate = matches!(existing.raw, raw::Raw::Null(raw::Null::Empty))
+            && !matches!(raw, raw::Raw::Null(raw::Null::Empty))
+            && self.prefix(id).is_empty()
+            && self.needs_separator(id);
+
+        let space = separate.then(|| self.insert_str(" "));
+
         let Some(value) = self.slab.get_mut(id.get()) else {
             return;
         };
 
-        let removed = mem::replace(&mut value.raw, raw.into());
+        if let Some(space) = space {
+            value.layout.prefix = space;
+        }
+
+        value.layout.tag = None;
+        value.span = None;
+        value.parsed = false;
+        let removed = mem::replace(&mut value.raw, raw);
         self.drop_kind(removed);
     }
 
+    /// Test if the value with the given identifier has to be separated by
+    /// whitespace from the indicator of the item it belongs to.
+    fn needs_separator(&self, id: Id) -> bool {
+        let Some(item) = self.layout(id).parent else {
+            return false;
+        };
+
+        match self.raw(item) {
+            raw::Raw::MappingItem(..) => true,
+            raw::Raw::SequenceItem(..) => self.layout(item).parent.is_some_and(|parent| {
+                matches!(
+                    self.raw(parent),
+                    raw::Raw::Sequence(raw::Sequence {
+                        kind: raw::SequenceKind::Mapping,
+                        ..
+                    })
+                )
+            }),
+            _ => false,
+        }
+    }
+
     /// Replace with indentation.
+    ///
+    /// Since the tag and span of a value describe its content, they are
+    /// cleared and the value is no longer considered parsed.
     pub(crate) fn replace_with(&mut self, id: Id, prefix: StringId, raw: raw::Raw) {
         let Some(value) = self.slab.get_mut(id.get()) else {
             return;
         };
 
         value.layout.prefix = prefix;
+        value.layout.tag = None;
+        value.span = None;
+        value.parsed = false;
         let removed = mem::replace(&mut value.raw, raw);
         self.drop_kind(removed);
     }
 }
+
+/// Access the whitespace stored after the given raw value, which is the
+/// whitespace before the closing delimiter of an inline collection, or before
+/// the `,` separator of an item in an inline collection.
+fn suffix_mut(raw: &mut raw::Raw) -> Option<&mut StringId> {
+    match raw {
+        raw::Raw::Mapping(raw::Mapping {
+            kind: raw::MappingKind::Inline { suffix, .. },
+            ..
+        })
+        | raw::Raw::Sequence(raw::Sequence {
+            kind: raw::SequenceKind::Inline { suffix, .. },
+            ..
+        })
+        | raw::Raw::MappingItem(raw::MappingItem {
+            suffix: Some(suffix),
+            ..
+        })
+        | raw::Raw::SequenceItem(raw::SequenceItem {
+            suffix: Some(suffix),
+            ..
+        }) => Some(suffix),
+        _ => None,
+    }
+}
+
+/// Iterator over values produced by [`Data::walk`].
+pub(crate) struct Walk<'a> {
+    data: &'a Data,
+    stack: Vec<Id>,
+}
+
+impl Iterator for Walk<'_> {
+    type Item = Id;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.stack.pop()?;
+
+        match self.data.raw(id) {
+            raw::Raw::Mapping(raw) => {
+                for item in raw.items.iter().rev() {
+                    self.stack.push(self.data.mapping_item(*item).value);
+                }
+            }
+            raw::Raw::Sequence(raw) => {
+                for item in raw.items.iter().rev() {
+                    self.stack.push(self.data.sequence_item(*item).value);
+                }
+            }
+            _ => {}
+        }
+
+        Some(id)
+    }
+}
@@ -1,14 +1,39 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::io;
+use std::str;
+
+use bstr::ByteSlice;
 
 #[cfg(feature = "serde-edits")]
 use serde::{Deserialize, Serialize};
 
 use crate::yaml::data::{Data, Id, StringId};
-use crate::yaml::{Value, ValueMut};
+use crate::yaml::eq;
+use crate::yaml::options;
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::{Error, ErrorKind};
+use crate::yaml::{IndentConfig, PrettyOptions, SerializeOptions, StringKind, Value, ValueMut};
+use crate::yaml::{MappingMut, Separator, SequenceMut};
 
 /// A whitespace preserving YAML document.
 ///
+/// A document which has been parsed and not modified formats back into exactly
+/// the bytes it was parsed from. This includes comments, whitespace, line
+/// endings, directives, document markers, tags, and the original
+/// representation of quoted and block strings. Input which cannot be reproduced
+/// this way is rejected when parsing rather than being normalized. This
+/// includes a stream containing multiple documents, which can instead be
+/// parsed into a [`Stream`] through [`from_slice_stream`].
+///
+/// Besides formatting it through [`Display`], a document can be written
+/// directly to any [`io::Write`] implementation through
+/// [`Document::write_to`], which doesn't build the whole output in memory.
+///
+/// [`Stream`]: crate::yaml::Stream
+/// [`from_slice_stream`]: crate::yaml::from_slice_stream
+/// [`Display`]: fmt::Display
+///
 /// # Examples
 ///
 /// ```
@@ -30,15 +55,23 @@ use crate::yaml::{Value, ValueMut};
 #[derive(Clone)]
 #[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
 pub struct Document {
-    suffix: StringId,
     pub(crate) root: Id,
     pub(crate) data: Data,
+    /// If the document was decoded from latin-1, and should be encoded back
+    /// into it when written.
+    pub(crate) latin1: bool,
 }
 
 impl Document {
     /// Construct a new document.
-    pub(crate) fn new(suffix: StringId, root: Id, data: Data) -> Self {
-        Self { suffix, root, data }
+    pub(crate) fn new(suffix: StringId, root: Id, mut data: Data) -> Self {
+        data.set_suffix(suffix);
+
+        Self {
+            root,
+            data,
+            latin1: false,
+        }
     }
 
     /// Get the document as a [`Value`].
@@ -78,6 +111,59 @@ impl Document {
         ValueMut::new(&mut self.data, self.root)
     }
 
+    /// Get the indentation used for collections which are freshly created
+    /// through edits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    ///
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("a: 1")?;
+    /// let step = NonZeroUsize::new(2).context("zero step")?;
+    /// assert_eq!(doc.indent(), yaml::IndentConfig::new().step(step));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn indent(&self) -> IndentConfig {
+        self.data.indent()
+    }
+
+    /// Set the indentation used for collections which are freshly created
+    /// through edits.
+    ///
+    /// The indentation of existing content is left as it is. See
+    /// [`IndentConfig`] for more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    ///
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("- one")?;
+    /// let step = NonZeroUsize::new(3).context("zero step")?;
+    /// doc.set_indent(yaml::IndentConfig::new().step(step));
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// let mut nested = root.push(yaml::Separator::Auto).make_mapping();
+    /// let mut inner = nested.insert("two", yaml::Separator::Auto).make_mapping();
+    /// inner.insert_u32("three", 3);
+    ///
+    /// assert_eq!(doc.to_string(), "- one\n- two:\n     three: 3");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[inline]
+    pub fn set_indent(&mut self, indent: IndentConfig) {
+        self.data.set_indent(indent);
+    }
+
     /// Get the given value.
     ///
     /// If [`Id`]'s are shared between documents, this might also result in
@@ -206,12 +292,13 @@ impl Document {
         ValueMut::new(&mut self.data, id)
     }
 
-    /// Write the bytes of the document to the given `output`.
-    ///
-    /// # Errors
+    /// Make a deep copy of the given value inside of the document, returning
+    /// the identifier of the copy.
     ///
-    /// Raises an I/O error if the underlying resource being written to raises
-    /// it.
+    /// The copy is detached, so it isn't part of the document until it is
+    /// moved into place with [`ValueMut::copy_from`], after which its
+    /// identifier is no longer valid. Editing the copy through
+    /// [`Document::value_mut`] doesn't affect the original.
     ///
     /// # Examples
     ///
@@ -221,116 +308,1695 @@ impl Document {
     ///
     /// let mut doc = yaml::from_slice(
     ///     r"
-    ///     string
+    ///     - name: web
+    ///       port: 80
     ///     "
     /// )?;
     ///
-    /// let mut mapping = doc.as_mut().make_mapping();
-    /// mapping.insert_u32("first", 1);
-    /// mapping.insert_u32("second", 2);
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// let web = root.get(0).context("missing web")?.id();
     ///
-    /// let mut out = Vec::new();
-    /// doc.write_to(&mut out)?;
+    /// let copy = doc.clone_value(web);
+    /// let mut mapping = doc.value_mut(copy).into_mapping_mut().context("missing mapping")?;
+    /// mapping.get_mut("name").context("missing name")?.set_string("admin");
+    /// mapping.get_mut("port").context("missing port")?.set_u32(8080);
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.push(yaml::Separator::Auto).copy_from(copy);
     ///
     /// assert_eq!(
-    ///     &out[..],
-    ///     br"
-    ///     first: 1
-    ///     second: 2
+    ///     doc.to_string(),
+    ///     r"
+    ///     - name: web
+    ///       port: 80
+    ///     - name: admin
+    ///       port: 8080
     ///     "
     /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn clone_value(&mut self, id: Id) -> Id {
+        let from = raw::indent_of(&self.data, id);
+        let copy = self.data.clone_tree(id, None);
+        self.data.set_detached(copy);
+
+        // The indentation of a detached value is determined by its prefix
+        // alone, so its contents are re-indented to match.
+        let from = isize::try_from(from).unwrap_or(isize::MAX);
+        let to = isize::try_from(raw::indent_of(&self.data, copy)).unwrap_or(isize::MAX);
+        self.data.indent_by(copy, to.saturating_sub(from));
+        copy
+    }
+
+    /// Deep-merge another document on top of this one, as is done when
+    /// layering configuration.
     ///
-    /// let mut doc = yaml::from_slice(
+    /// This is the same as [`Document::merge_with`] with
+    /// [`MergeStrategy::Replace`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut base = yaml::from_slice(
     ///     r"
-    ///     first: second
+    ///     ## Base configuration.
+    ///     name: web
+    ///     server:
+    ///       port: 80 # default port
+    ///       hosts: [a, b]
     ///     "
     /// )?;
     ///
-    /// let mut mapping = doc.as_mut().into_mapping_mut().and_then(|m| Some(m.get_into_mut("first")?.make_mapping())).context("missing first")?;
-    /// mapping.insert_u32("second", 2);
-    /// mapping.insert_u32("third", 3);
+    /// let production = yaml::from_slice(
+    ///     r"
+    ///     server:
+    ///       port: 443
+    ///       hosts: [c]
+    ///       tls:
+    ///         cert: /etc/cert.pem
+    ///     replicas: 3
+    ///     "
+    /// )?;
     ///
-    /// let mut out = Vec::new();
-    /// doc.write_to(&mut out)?;
+    /// base.merge(&production);
     ///
     /// assert_eq!(
-    ///     &out[..],
-    ///     br"
-    ///     first:
-    ///       second: 2
-    ///       third: 3
+    ///     base.to_string(),
+    ///     r"
+    ///     ## Base configuration.
+    ///     name: web
+    ///     server:
+    ///       port: 443 # default port
+    ///       hosts: [c]
+    ///       tls:
+    ///         cert: /etc/cert.pem
+    ///     replicas: 3
     ///     "
     /// );
     /// # Ok::<_, anyhow::Error>(())
     /// ```
-    pub fn write_to<O>(&self, mut output: O) -> io::Result<()>
-    where
-        O: io::Write,
-    {
-        output.write_all(self.data.prefix(self.root))?;
-        self.data.raw(self.root).write_to(&self.data, &mut output)?;
-        output.write_all(self.data.str(self.suffix))?;
-        Ok(())
+    pub fn merge(&mut self, other: &Document) {
+        self.merge_with(other, MergeStrategy::Replace);
     }
 
-    // Display helper for document.
-    fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use std::fmt::Display;
+    /// Deep-merge another document on top of this one using the given
+    /// strategy for sequences.
+    ///
+    /// Mappings are merged recursively: keys which are present in both take
+    /// the value from `other`, merging them in turn if they are both mappings,
+    /// and keys which are only present in `other` are appended. Anything else
+    /// is replaced by the value from `other`, except for sequences which are
+    /// concatenated with [`MergeStrategy::Concat`].
+    ///
+    /// Values copied from `other` are re-indented to fit into this document,
+    /// and keep their own comments and formatting, including the relative
+    /// indentation of their nested collections. Keys copied from `other` keep
+    /// their quoting. Comments and formatting surrounding values which are
+    /// replaced are kept, and so is their anchor so that aliases to them
+    /// refer to the new value. Anchors copied from `other` which are already
+    /// defined elsewhere in this document are renamed, so that existing aliases
+    /// keep referring to the values they did. Block values which are copied
+    /// into inline collections are converted into inline values, dropping any
+    /// comments inside of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut base = yaml::from_slice(
+    ///     r"
+    ///     args:
+    ///       - --verbose
+    ///     env: {RUST_LOG: info}
+    ///     "
+    /// )?;
+    ///
+    /// let other = yaml::from_slice(
+    ///     r"
+    ///     args:
+    ///     - --port
+    ///     - 80
+    ///     env: {RUST_BACKTRACE: 1}
+    ///     "
+    /// )?;
+    ///
+    /// base.merge_with(&other, yaml::MergeStrategy::Concat);
+    ///
+    /// assert_eq!(
+    ///     base.to_string(),
+    ///     r"
+    ///     args:
+    ///       - --verbose
+    ///       - --port
+    ///       - 80
+    ///     env: {RUST_LOG: info, RUST_BACKTRACE: 1}
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn merge_with(&mut self, other: &Document, strategy: MergeStrategy) {
+        merge_value(&mut self.data, self.root, &other.data, other.root, strategy);
+    }
 
-        self.data.prefix(self.root).fmt(f)?;
-        self.data.raw(self.root).display(&self.data, f, None)?;
-        self.data.str(self.suffix).fmt(f)?;
-        Ok(())
+    /// Replace every string value in the document which is equal to `find`
+    /// with `replace`, returning the number of values which were replaced.
+    ///
+    /// Strings are matched on their decoded content, so quoted and bare
+    /// strings are treated the same. Replacements preserve the quoting style
+    /// of the string they replace where possible, and introduce quotes where
+    /// necessary. Mapping keys are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r#"
+    ///     web:
+    ///       image: nginx
+    ///       sidecars: [nginx, "nginx", envoy]
+    ///     proxy:
+    ///       image: 'nginx'
+    ///       nginx: kept
+    ///     "#
+    /// )?;
+    ///
+    /// assert_eq!(doc.replace_scalar("nginx", "nginx:1.25"), 4);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     web:
+    ///       image: nginx:1.25
+    ///       sidecars: ['nginx:1.25', "nginx:1.25", envoy]
+    ///     proxy:
+    ///       image: 'nginx:1.25'
+    ///       nginx: kept
+    ///     "#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn replace_scalar(&mut self, find: &str, replace: &str) -> usize {
+        let ids = self.data.walk(self.root).collect::<Vec<_>>();
+        let mut count = 0usize;
+
+        for id in ids {
+            let Raw::String(existing) = self.data.raw(id) else {
+                continue;
+            };
+
+            if self.data.str(existing.id) != find {
+                continue;
+            }
+
+            let raw = raw::new_string_like(&mut self.data, id, replace);
+            self.data.replace(id, raw);
+            count = count.wrapping_add(1);
+        }
+
+        count
     }
-}
 
-impl fmt::Display for Document {
-    #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // If we're running in debug mode, assert that the produced document
-        // matches whatever would've been produced through `Document::write_to`.
-        //
-        // This is only enabled with `--cfg nondestructive_write_to_eq`.
-        if cfg!(nondestructive_write_to_eq) {
-            use bstr::BStr;
-            use std::fmt::Write;
+    /// Rename every mapping key in the document using the given function.
+    ///
+    /// Keys for which the function returns `None` are left unchanged. Renamed
+    /// keys retain the quoting of the key they replace where possible, and
+    /// introduce quotes where necessary. Values, the order of keys, and all
+    /// other formatting are preserved.
+    ///
+    /// If a key would be renamed to the same name as another key in the same
+    /// mapping, it is left unchanged. The paths of such keys are returned in
+    /// the order in which they appear in the document, using the same format
+    /// as [`Document::leaves`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// fn snake_case(key: &str) -> Option<String> {
+    ///     if !key.contains(char::is_uppercase) {
+    ///         return None;
+    ///     }
+    ///
+    ///     let mut out = String::new();
+    ///
+    ///     for c in key.chars() {
+    ///         if c.is_uppercase() {
+    ///             out.push('_');
+    ///         }
+    ///
+    ///         out.extend(c.to_lowercase());
+    ///     }
+    ///
+    ///     Some(out)
+    /// }
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r#"
+    ///     apiVersion: v1
+    ///     metadata:
+    ///       ## Comments are preserved.
+    ///       labels: {appName: web}
+    ///       "resourceVersion": 42
+    ///     spec:
+    ///       restartPolicy: Always
+    ///       restart_policy: Never
+    ///     "#
+    /// )?;
+    ///
+    /// let conflicts = doc.rename_keys(snake_case);
+    /// assert_eq!(conflicts, ["spec.restartPolicy"]);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     api_version: v1
+    ///     metadata:
+    ///       ## Comments are preserved.
+    ///       labels: {app_name: web}
+    ///       "resource_version": 42
+    ///     spec:
+    ///       restartPolicy: Always
+    ///       restart_policy: Never
+    ///     "#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn rename_keys<F>(&mut self, f: F) -> Vec<String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut conflicts = Vec::new();
+        let mut stack = vec![(String::new(), self.root)];
 
-            #[repr(transparent)]
-            struct Inner<'a>(&'a Document);
+        while let Some((path, id)) = stack.pop() {
+            let items = match self.data.raw(id) {
+                Raw::Mapping(raw) => raw.items.clone(),
+                Raw::Sequence(raw) => {
+                    for (index, item) in raw.items.iter().enumerate().rev() {
+                        let item = self.data.sequence_item(*item);
+                        stack.push((join_path(&path, index), item.value));
+                    }
 
-            impl fmt::Display for Inner<'_> {
-                #[inline]
-                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                    self.0.display(f)
+                    continue;
+                }
+                _ => continue,
+            };
+
+            let keys = items
+                .iter()
+                .map(|item| self.data.str(self.data.mapping_item(*item).key.id).to_vec())
+                .collect::<Vec<_>>();
+
+            let mut renamed = keys
+                .iter()
+                .map(|key| {
+                    let new = f(key.to_str().ok()?)?;
+                    (new.as_bytes() != key).then_some(new)
+                })
+                .collect::<Vec<_>>();
+
+            let mut conflicted = vec![false; keys.len()];
+
+            // Reverting a rename might cause another conflict, so keep going
+            // until there are none left.
+            loop {
+                let names = keys
+                    .iter()
+                    .zip(&renamed)
+                    .map(|(key, new)| new.as_ref().map_or(&key[..], |new| new.as_bytes()))
+                    .collect::<Vec<_>>();
+
+                let colliding = (0..keys.len())
+                    .filter(|&n| renamed[n].is_some())
+                    .filter(|&n| {
+                        names
+                            .iter()
+                            .enumerate()
+                            .any(|(m, name)| m != n && *name == names[n])
+                    })
+                    .collect::<Vec<_>>();
+
+                if colliding.is_empty() {
+                    break;
+                }
+
+                for n in colliding {
+                    renamed[n] = None;
+                    conflicted[n] = true;
                 }
             }
 
-            let mut string = String::new();
-            write!(string, "{}", Inner(self))?;
-            let mut bytes = Vec::new();
+            let inline = raw::is_inline(&self.data, id);
+            let mut children = Vec::with_capacity(items.len());
 
-            self.write_to(&mut bytes)
-                .expect("Document::write_to should not panic");
+            for (n, item) in items.iter().enumerate() {
+                let key = &keys[n];
 
-            debug_assert_eq!(
-                BStr::new(string.as_bytes()),
-                BStr::new(&bytes),
-                "nondestructive_write_to_eq: ensure write_to produces the same output"
-            );
+                if conflicted[n] {
+                    conflicts.push(join_path(&path, escape_key(key)));
+                }
 
-            string.fmt(f)?;
-        } else {
-            self.display(f)?;
+                let name = if let Some(new) = renamed[n].take() {
+                    let quote = self.data.mapping_item(*item).key.quote(&self.data);
+                    let kind = raw::key_kind_like(&self.data, quote, &new, inline);
+                    let string = self.data.insert_str(&new);
+
+                    if let Raw::MappingItem(raw) = self.data.raw_mut(*item) {
+                        raw.key = raw::String::new(kind, string, string);
+                    }
+
+                    escape_key(new.as_bytes())
+                } else {
+                    escape_key(key)
+                };
+
+                children.push((join_path(&path, name), self.data.mapping_item(*item).value));
+            }
+
+            stack.extend(children.into_iter().rev());
         }
 
-        Ok(())
+        conflicts
+    }
+
+    /// Iterate over every scalar value in the document, together with the path
+    /// leading up to it.
+    ///
+    /// Paths are formed by joining mapping keys and sequence indexes with `.`,
+    /// so `a.0.b` refers to the key `b` in the first element of the sequence
+    /// stored under the key `a`. Any `.` or `\` inside of a mapping key is
+    /// escaped with a `\`. A scalar stored in the root of the document has an
+    /// empty path.
+    ///
+    /// Values are visited in the order in which they appear in the document,
+    /// and collections themselves are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     name: web
+    ///     ports: [80, 443]
+    ///     labels:
+    ///       app.kubernetes.io/name: web
+    ///     empty: {}
+    ///     "
+    /// )?;
+    ///
+    /// let leaves = doc
+    ///     .leaves()
+    ///     .map(|(path, value)| (path, value.to_string()))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     leaves,
+    ///     [
+    ///         (String::from("name"), String::from("web")),
+    ///         (String::from("ports.0"), String::from("80")),
+    ///         (String::from("ports.1"), String::from("443")),
+    ///         (String::from("labels.app\\.kubernetes\\.io/name"), String::from("web")),
+    ///     ]
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn leaves(&self) -> impl Iterator<Item = (String, Value<'_>)> {
+        Leaves {
+            data: &self.data,
+            stack: vec![(String::new(), self.root)],
+        }
+    }
+
+    /// Get the path of the value with the given identifier, in the same format
+    /// as used by [`Document::leaves`].
+    ///
+    /// The value is searched for starting from the root of the document, so
+    /// this returns `None` if the identifier doesn't refer to a value in this
+    /// document, such as one which has been removed. Unlike
+    /// [`Document::leaves`], collections have paths too.
+    ///
+    /// Like with [`Document::value`], the result is unspecified if the
+    /// identifier belongs to a different document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     services:
+    ///       web:
+    ///         ports: [80, 443]
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let services = root.get("services").and_then(|v| v.as_mapping()).context("missing services")?;
+    /// let web = services.get("web").context("missing web")?;
+    /// let ports = web.as_mapping().and_then(|m| m.get("ports")).and_then(|v| v.as_sequence()).context("missing ports")?;
+    /// let port = ports.get(1).context("missing port")?;
+    ///
+    /// assert_eq!(doc.path_of(port.id()).as_deref(), Some("services.web.ports.1"));
+    /// assert_eq!(doc.path_of(web.id()).as_deref(), Some("services.web"));
+    /// assert_eq!(doc.path_of(doc.as_ref().id()).as_deref(), Some(""));
+    ///
+    /// let mut doc = yaml::from_slice("[1, 2, 3]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing sequence")?;
+    /// let id = root.get(2).context("missing item")?.id();
+    ///
+    /// doc.as_mut().into_sequence_mut().context("missing sequence")?.remove(2);
+    /// assert_eq!(doc.path_of(id), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn path_of(&self, id: Id) -> Option<String> {
+        let mut stack = vec![(String::new(), self.root)];
+
+        while let Some((path, current)) = stack.pop() {
+            if current == id {
+                return Some(path);
+            }
+
+            match self.data.raw(current) {
+                Raw::Mapping(raw) => {
+                    for item in &raw.items {
+                        let item = self.data.mapping_item(*item);
+                        let key = escape_key(self.data.str(item.key.id));
+                        stack.push((join_path(&path, key), item.value));
+                    }
+                }
+                Raw::Sequence(raw) => {
+                    for (index, item) in raw.items.iter().enumerate() {
+                        let item = self.data.sequence_item(*item);
+                        stack.push((join_path(&path, index), item.value));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    /// Look up a value using a [JSON Pointer], like `/spec/ports/0`.
+    ///
+    /// Segments index into sequences when the value they're applied to is a
+    /// sequence, and key into mappings otherwise. In keys, `~1` stands for `/`
+    /// and `~0` for `~`. The empty pointer refers to the root of the document.
+    ///
+    /// Returns `None` if the pointer is malformed or doesn't refer to a value.
+    ///
+    /// [JSON Pointer]: https://www.rfc-editor.org/rfc/rfc6901
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     first: 32
+    ///     second: [1, 2, 3]
+    ///     a/b: slash
+    ///     m~n: tilde
+    ///     "
+    /// )?;
+    ///
+    /// assert_eq!(doc.pointer("/first").and_then(|v| v.as_u32()), Some(32));
+    /// assert_eq!(doc.pointer("/second/0").and_then(|v| v.as_u32()), Some(1));
+    /// assert_eq!(doc.pointer("/a~1b").and_then(|v| v.as_str()), Some("slash"));
+    /// assert_eq!(doc.pointer("/m~0n").and_then(|v| v.as_str()), Some("tilde"));
+    /// assert!(doc.pointer("").and_then(|v| v.as_mapping()).is_some());
+    ///
+    /// assert!(doc.pointer("/second/3").is_none());
+    /// assert!(doc.pointer("/second/01").is_none());
+    /// assert!(doc.pointer("first").is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<Value<'_>> {
+        let id = self.pointer_id(pointer)?;
+        Some(Value::new(&self.data, id))
+    }
+
+    /// Look up a value mutably using a [JSON Pointer], like `/spec/ports/0`.
+    ///
+    /// See [`Document::pointer`] for details.
+    ///
+    /// [JSON Pointer]: https://www.rfc-editor.org/rfc/rfc6901
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     spec:
+    ///       ports: [80, 443]
+    ///     "
+    /// )?;
+    ///
+    /// doc.pointer_mut("/spec/ports/1").context("missing port")?.set_u32(8443);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     spec:
+    ///       ports: [80, 8443]
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<ValueMut<'_>> {
+        let id = self.pointer_id(pointer)?;
+        Some(ValueMut::new(&mut self.data, id))
+    }
+
+    /// Resolve a JSON Pointer into the identifier of the value it refers to.
+    fn pointer_id(&self, pointer: &str) -> Option<Id> {
+        let mut current = self.root;
+
+        if pointer.is_empty() {
+            return Some(current);
+        }
+
+        let mut segments = pointer.split('/');
+
+        if segments.next() != Some("") {
+            return None;
+        }
+
+        for segment in segments {
+            current = match self.data.raw(current) {
+                Raw::Sequence(raw) => {
+                    if segment.len() > 1 && segment.starts_with('0')
+                        || !segment.bytes().all(|b| b.is_ascii_digit())
+                    {
+                        return None;
+                    }
+
+                    let index = segment.parse::<usize>().ok()?;
+                    self.data.sequence_item(*raw.items.get(index)?).value
+                }
+                Raw::Mapping(raw) => {
+                    let key = unescape_pointer(segment)?;
+
+                    raw.items
+                        .iter()
+                        .map(|item| self.data.mapping_item(*item))
+                        .find(|item| self.data.str(item.key.id) == key.as_str())?
+                        .value
+                }
+                _ => return None,
+            };
+        }
+
+        Some(current)
+    }
+
+    /// Format the document as a string, emitting the keys of every mapping in
+    /// the given `order` first followed by any remaining keys in the order in
+    /// which they appear in the document.
+    ///
+    /// The same `order` is applied to mappings at every level of the document.
+    /// Whitespace is assigned positionally, so the formatting of each slot in
+    /// a mapping is preserved even though the keys occupying them are moved.
+    /// Comments on the same line as a key move along with it.
+    ///
+    /// This does not modify the document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     spec:
+    ///       replicas: 3
+    ///       selector: {app: web}
+    ///     kind: Deployment
+    ///     metadata: {name: web, labels: {app: web}}
+    ///     apiVersion: apps/v1
+    ///     "
+    /// )?;
+    ///
+    /// let order = ["apiVersion", "kind", "metadata", "name", "spec"];
+    ///
+    /// assert_eq!(
+    ///     doc.to_string_with_order(&order),
+    ///     r"
+    ///     apiVersion: apps/v1
+    ///     kind: Deployment
+    ///     metadata: {name: web, labels: {app: web}}
+    ///     spec:
+    ///       replicas: 3
+    ///       selector: {app: web}
+    ///     "
+    /// );
+    ///
+    /// // The original document is unchanged.
+    /// assert!(doc.to_string().starts_with("\n    spec:"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_string_with_order(&self, order: &[&str]) -> String {
+        let mut doc = self.clone();
+        let mappings = doc
+            .data
+            .walk(doc.root)
+            .filter(|id| matches!(doc.data.raw(*id), Raw::Mapping(..)))
+            .collect::<Vec<_>>();
+
+        let position = |key: &[u8]| {
+            order
+                .iter()
+                .position(|o| o.as_bytes() == key)
+                .unwrap_or(order.len())
+        };
+
+        for id in mappings {
+            // NB: sort is stable, so keys not mentioned in the order retain
+            // their original relative position.
+            raw::sort_mapping_with_lines(&mut doc.data, id, |a, b| position(a).cmp(&position(b)));
+        }
+
+        doc.to_string()
+    }
+
+    /// Format the document into a string using the given
+    /// [`SerializeOptions`].
+    ///
+    /// This produces normalized output without modifying the document itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     name: web
+    ///     args: [--port, 80, --verbose]
+    ///     "
+    /// )?;
+    ///
+    /// let options = yaml::SerializeOptions::new().max_inline_items(Some(2));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string_with(&options),
+    ///     r"
+    ///     name: web
+    ///     args:
+    ///       - --port
+    ///       - 80
+    ///       - --verbose
+    ///     "
+    /// );
+    ///
+    /// // The original document is unchanged.
+    /// assert!(doc.to_string().contains("[--port, 80, --verbose]"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_string_with(&self, options: &SerializeOptions) -> String {
+        let mut string = String::new();
+        // NB: writing to a string can't fail.
+        _ = self.write_raw_with(&mut raw::Fmt(&mut string), options);
+        string
+    }
+
+    /// Format the document into a normalized string according to the given
+    /// [`PrettyOptions`].
+    ///
+    /// This brings together all the formatting options in one place, and
+    /// doesn't modify the document itself. See [`PrettyOptions`] for how the
+    /// options interact.
+    ///
+    /// The options are applied to a copy of the document, so every call clones
+    /// the whole document. When the same document is formatted repeatedly, it
+    /// is cheaper to keep the formatted string around.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    ///
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     ## Generated, do not edit.
+    ///     services:
+    ///         web: {image: 'nginx', ports: [80, 443, 8080, 8443]}
+    ///         db: {image: 'postgres'}
+    ///     "
+    /// )?;
+    ///
+    /// let options = yaml::PrettyOptions::new()
+    ///     .indent(NonZeroUsize::new(2).context("zero indent")?)
+    ///     .sort_keys(true)
+    ///     .max_width(40)
+    ///     .quote_style(yaml::StringKind::Bare);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string_pretty(&options),
+    ///     r"
+    ///     ## Generated, do not edit.
+    ///     services:
+    ///       db: {image: postgres}
+    ///       web:
+    ///         image: nginx
+    ///         ports: [80, 443, 8080, 8443]
+    ///     "
+    /// );
+    ///
+    /// // The original document is unchanged.
+    /// assert!(doc.to_string().contains("web: {image: 'nginx'"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_string_pretty(&self, options: &PrettyOptions) -> String {
+        let mut doc = self.clone();
+        options.apply(&mut doc.data, doc.root);
+        doc.to_string()
+    }
+
+    /// Format the document into a string which is suited for storing in
+    /// version control, where small changes to the document should result in
+    /// small diffs.
+    ///
+    /// This is an opinionated preset which doesn't modify the document itself:
+    /// * Inline collections with any items are expanded so that every item is
+    ///   on its own line, as with [`SerializeOptions::max_inline_items`].
+    /// * Block collections are indented by two spaces, as with
+    ///   [`PrettyOptions::indent`].
+    /// * Quotes are removed from strings which don't need them, as with
+    ///   [`PrettyOptions::quote_style`].
+    /// * Trailing whitespace is removed, as with
+    ///   [`Document::trim_trailing_whitespace`].
+    ///
+    /// Keys keep their order, since sorting them causes unrelated lines to move
+    /// around as keys are added. If sorted keys are desired, the output can be
+    /// parsed again and formatted with [`PrettyOptions::sort_keys`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     name: "web"
+    ///     ports: [80, 443]   
+    ///     env: {RUST_LOG: 'debug', EMPTY: []}
+    ///     "#
+    /// )?;
+    ///
+    /// assert_eq!(
+    ///     doc.to_string_vcs_friendly(),
+    ///     r"
+    ///     name: web
+    ///     ports:
+    ///       - 80
+    ///       - 443
+    ///     env:
+    ///       RUST_LOG: debug
+    ///       EMPTY: []
+    /// "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_string_vcs_friendly(&self) -> String {
+        let mut doc = self.clone();
+
+        SerializeOptions::new()
+            .max_inline_items(Some(0))
+            .apply(&mut doc.data, doc.root);
+
+        PrettyOptions::new()
+            .indent(IndentConfig::new().step)
+            .quote_style(StringKind::Bare)
+            .apply(&mut doc.data, doc.root);
+
+        doc.trim_trailing_whitespace();
+        doc.to_string()
+    }
+
+    /// Reformat the document into canonical block style.
+    ///
+    /// Unlike everything else in this crate, this is a destructive formatting
+    /// operation:
+    /// * Inline collections with any items are expanded into block
+    ///   collections. Empty collections stay as `[]` and `{}`.
+    /// * Block collections are indented by two spaces, and the same
+    ///   indentation is used for collections created afterwards.
+    /// * Values on the same line as their key or `-` are separated from it by
+    ///   a single space, unless they have a tag or an anchor.
+    ///
+    /// Scalars keep their values and quoting, and comments are preserved.
+    /// Comments inside of expanded inline collections are moved onto the lines
+    /// of their items. As with [`PrettyOptions::indent`], the contents of block
+    /// strings keep their indentation relative to their key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     name:    'web'
+    ///     env: {RUST_LOG: debug, EMPTY: []}
+    ///     ports:
+    ///         -   80
+    ///         - [443, 8443]
+    ///     "
+    /// )?;
+    ///
+    /// doc.normalize();
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     name: 'web'
+    ///     env:
+    ///       RUST_LOG: debug
+    ///       EMPTY: []
+    ///     ports:
+    ///       - 80
+    ///       - - 443
+    ///         - 8443
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn normalize(&mut self) {
+        self.data.set_indent(IndentConfig::new());
+
+        SerializeOptions::new()
+            .max_inline_items(Some(0))
+            .apply(&mut self.data, self.root);
+
+        PrettyOptions::new()
+            .indent(IndentConfig::new().step)
+            .apply(&mut self.data, self.root);
+
+        let single = self.data.insert_str(" ");
+        let values = self.data.walk(self.root).collect::<Vec<_>>();
+
+        for id in values {
+            let Some(parent) = self.data.layout(id).parent else {
+                continue;
+            };
+
+            let layout = self.data.layout(id);
+
+            if layout.tag.is_some() || layout.anchor.is_some() {
+                continue;
+            }
+
+            if !matches!(self.data.raw(parent), Raw::MappingItem(..)) {
+                continue;
+            }
+
+            let prefix = self.data.prefix(id);
+
+            if !prefix.is_empty() && prefix.iter().all(|b| matches!(b, b' ' | b'\t')) {
+                self.data.set_prefix(id, single);
+            }
+        }
+    }
+
+    /// Strip trailing whitespace from every line in the document.
+    ///
+    /// This only affects whitespace which is not significant, such as the
+    /// whitespace between values and after comments. The contents of strings,
+    /// such as trailing spaces in block strings, are left as they are, as is
+    /// the indentation of every line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("name: web  \n## comment\t\nports: [80,  \n  443]\nscript: |\n  run  \n")?;
+    ///
+    /// doc.trim_trailing_whitespace();
+    /// assert_eq!(doc.to_string(), "name: web\n## comment\nports: [80,\n  443]\nscript: |\n  run  \n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn trim_trailing_whitespace(&mut self) {
+        self.data.trim_trailing_whitespace();
+
+        let suffix = self.data.suffix();
+        let suffix = raw::trim_lines(suffix).unwrap_or_else(|| suffix.to_vec());
+        let suffix = suffix.trim_end_with(|c| matches!(c, ' ' | '\t'));
+        let suffix = self.data.insert_str(suffix);
+        self.data.set_suffix(suffix);
+    }
+
+    /// Convert every string value in the document to the given quoting style
+    /// where it can be represented with it. The contents of strings are
+    /// preserved, only the way they are quoted changes.
+    ///
+    /// The following fallbacks are used:
+    /// * With [`StringKind::Bare`], strings which would be ambiguous or invalid
+    ///   without quotes, like `'42'` or `'a: b'`, stay quoted as they are.
+    /// * With [`StringKind::Single`], strings which contain control characters
+    ///   such as line breaks are double-quoted, since they can't be escaped in
+    ///   single-quoted strings.
+    ///
+    /// Keys and block strings are never affected. This is the same as
+    /// [`PrettyOptions::quote_style`], except that the document is modified.
+    ///
+    /// [`StringKind::Bare`]: crate::yaml::StringKind::Bare
+    /// [`StringKind::Single`]: crate::yaml::StringKind::Single
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r#"
+    ///     name: "web"
+    ///     version: '42'
+    ///     command: "echo\ndone"
+    ///     args: ["--port", 'a, b']
+    ///     "#
+    /// )?;
+    ///
+    /// doc.normalize_quotes(yaml::StringKind::Bare);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     name: web
+    ///     version: '42'
+    ///     command: "echo\ndone"
+    ///     args: [--port, 'a, b']
+    ///     "#
+    /// );
+    ///
+    /// doc.normalize_quotes(yaml::StringKind::Single);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     name: 'web'
+    ///     version: '42'
+    ///     command: "echo\ndone"
+    ///     args: ['--port', 'a, b']
+    ///     "#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn normalize_quotes(&mut self, style: StringKind) {
+        options::quote_strings(&mut self.data, self.root, style);
+    }
+
+    /// Test if two documents are semantically equal, ignoring differences in
+    /// formatting and comments, and the order of keys in mappings.
+    ///
+    /// Two documents are semantically equal if:
+    /// * Their collections have the same structure, regardless of if they are
+    ///   written in block or inline style.
+    /// * Strings have the same decoded value, regardless of how they are quoted
+    ///   or escaped.
+    /// * Numbers are spelled the same, so `1.0` is not equal to `1` and `0x10`
+    ///   is not equal to `16`.
+    /// * Booleans have the same value, and nulls are all equal to each other
+    ///   regardless of if they are written as `null`, `~` or are empty.
+    /// * Values have the same custom tags. Standard tags like `!!str` are only
+    ///   considered through their effect on the value they are applied to.
+    ///
+    /// Use [`Document::semantic_eq_ordered`] to also require keys to be in the
+    /// same order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let a = yaml::from_slice(
+    ///     r#"
+    ///     ## The number of replicas.
+    ///     replicas: 3
+    ///     image: "nginx"
+    ///     ports: [80, 443]
+    ///     "#
+    /// )?;
+    ///
+    /// let b = yaml::from_slice(
+    ///     r"
+    ///     image: nginx
+    ///     ports:
+    ///       - 80
+    ///       - 443
+    ///     replicas: 3
+    ///     "
+    /// )?;
+    ///
+    /// let c = yaml::from_slice("{replicas: 3.0, image: nginx, ports: [80, 443]}")?;
+    ///
+    /// assert!(a.semantic_eq(&b));
+    /// assert!(!a.semantic_eq_ordered(&b));
+    /// assert!(!a.semantic_eq(&c));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn semantic_eq(&self, other: &Document) -> bool {
+        eq::semantic_eq(
+            &self.data,
+            self.root,
+            &other.data,
+            other.root,
+            eq::Mode::UNORDERED,
+        )
+    }
+
+    /// Test if two documents are semantically equal, with keys in mappings in
+    /// the same order.
+    ///
+    /// See [`Document::semantic_eq`] for what is considered equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let a = yaml::from_slice("{a: 1, b: 2}")?;
+    /// let b = yaml::from_slice("a: 1\nb: 2\n")?;
+    /// let c = yaml::from_slice("b: 2\na: 1\n")?;
+    ///
+    /// assert!(a.semantic_eq_ordered(&b));
+    /// assert!(!a.semantic_eq_ordered(&c));
+    /// assert!(a.semantic_eq(&c));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn semantic_eq_ordered(&self, other: &Document) -> bool {
+        eq::semantic_eq(
+            &self.data,
+            self.root,
+            &other.data,
+            other.root,
+            eq::Mode::ORDERED,
+        )
+    }
+
+    /// Check that the document can be serialized and parsed again without
+    /// changing its contents.
+    ///
+    /// This is a safety check for documents which have been edited, since
+    /// edits such as [`ValueMut::set_string_with`] with a style which can't
+    /// represent the string may otherwise produce output which can't be
+    /// parsed, or which is parsed into something else. Contents are compared
+    /// as with [`Document::semantic_eq_ordered`].
+    ///
+    /// [`ValueMut::set_string_with`]: crate::yaml::ValueMut::set_string_with
+    ///
+    /// # Errors
+    ///
+    /// Errors if the serialized document can't be parsed, in which case the
+    /// span of the error refers to the serialized document, or with
+    /// [`ErrorKind::RoundtripMismatch`] if it is parsed into something else.
+    ///
+    /// [`ErrorKind::RoundtripMismatch`]: crate::yaml::ErrorKind::RoundtripMismatch
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("name: web\nports: [80]")?;
+    /// doc.validate_roundtrip()?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// let mut ports = root.get_mut("ports").and_then(|v| v.into_sequence_mut()).context("missing ports")?;
+    /// ports.get_mut(0).context("missing port")?.set_string_with("80, 443", yaml::StringKind::Bare);
+    ///
+    /// assert!(doc.validate_roundtrip().is_err());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn validate_roundtrip(&self) -> Result<(), Error> {
+        let string = self.to_string();
+        let reparsed = crate::yaml::from_slice(&string)?;
+
+        if !self.semantic_eq_ordered(&reparsed) {
+            return Err(Error::new(
+                string.as_bytes(),
+                0..string.len(),
+                ErrorKind::RoundtripMismatch,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Write the bytes of the document to the given `output`.
+    ///
+    /// If the document was parsed through [`from_slice_latin1`], it is encoded
+    /// back into latin-1 as it is written.
+    ///
+    /// [`from_slice_latin1`]: crate::yaml::from_slice_latin1
+    ///
+    /// # Errors
+    ///
+    /// Raises an I/O error if the underlying resource being written to raises
+    /// it, or if the document is written as latin-1 and contains characters
+    /// which can't be represented in it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     string
+    ///     "
+    /// )?;
+    ///
+    /// let mut mapping = doc.as_mut().make_mapping();
+    /// mapping.insert_u32("first", 1);
+    /// mapping.insert_u32("second", 2);
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    ///
+    /// assert_eq!(
+    ///     &out[..],
+    ///     br"
+    ///     first: 1
+    ///     second: 2
+    ///     "
+    /// );
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     first: second
+    ///     "
+    /// )?;
+    ///
+    /// let mut mapping = doc.as_mut().into_mapping_mut().and_then(|m| Some(m.get_into_mut("first")?.make_mapping())).context("missing first")?;
+    /// mapping.insert_u32("second", 2);
+    /// mapping.insert_u32("third", 3);
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    ///
+    /// assert_eq!(
+    ///     &out[..],
+    ///     br"
+    ///     first:
+    ///       second: 2
+    ///       third: 3
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn write_to<O>(&self, mut output: O) -> io::Result<()>
+    where
+        O: io::Write,
+    {
+        if self.latin1 {
+            let mut bytes = Vec::new();
+            self.write_utf8_to(&mut bytes)?;
+            return output.write_all(&raw::encode_latin1(&bytes)?);
+        }
+
+        self.write_utf8_to(output)
+    }
+
+    /// Write the document to the given `output` using the given
+    /// [`SerializeOptions`].
+    ///
+    /// This is the [`Document::write_to`] counterpart of
+    /// [`Document::to_string_with`], and doesn't modify the document itself.
+    ///
+    /// # Errors
+    ///
+    /// Errors in case the output cannot be written to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("name: \"caf\u{e9}\"")?;
+    ///
+    /// let options = yaml::SerializeOptions::new().escape_unicode(true);
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to_with(&mut out, &options)?;
+    /// assert_eq!(&out[..], "name: \"caf\u{e9}\"".as_bytes());
+    ///
+    /// let mut doc = doc;
+    /// doc.normalize_quotes(yaml::StringKind::Double);
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to_with(&mut out, &options)?;
+    /// assert_eq!(&out[..], br#"name: "caf\xe9""#);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn write_to_with<O>(&self, mut output: O, options: &SerializeOptions) -> io::Result<()>
+    where
+        O: io::Write,
+    {
+        if self.latin1 {
+            let mut bytes = Vec::new();
+            self.write_raw_with(&mut raw::Io(&mut bytes), options)?;
+            return output.write_all(&raw::encode_latin1(&bytes)?);
+        }
+
+        self.write_raw_with(&mut raw::Io(&mut output), options)
+    }
+
+    /// Write the document to the given `output` without re-encoding it.
+    fn write_utf8_to<O>(&self, mut output: O) -> io::Result<()>
+    where
+        O: io::Write,
+    {
+        self.write_raw(&mut raw::Io(&mut output))
+    }
+
+    // Display helper for document.
+    fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_raw(&mut raw::Fmt(f))
+    }
+
+    /// Write the document to the given raw output using the given
+    /// [`SerializeOptions`].
+    ///
+    /// The document is only copied if the options modify it, since escaping is
+    /// handled by the output.
+    fn write_raw_with<O>(&self, o: &mut O, options: &SerializeOptions) -> Result<(), O::Error>
+    where
+        O: ?Sized + raw::Output,
+    {
+        let modified;
+
+        let doc = if options.modifies() {
+            let mut doc = self.clone();
+            options.apply(&mut doc.data, doc.root);
+            modified = doc;
+            &modified
+        } else {
+            self
+        };
+
+        if options.escapes_unicode() {
+            doc.write_raw(&mut raw::EscapeUnicode(o))
+        } else {
+            doc.write_raw(o)
+        }
+    }
+
+    /// Write the document to the given raw output, which is shared between
+    /// [`fmt::Display`] and [`Document::write_to`].
+    fn write_raw<O>(&self, o: &mut O) -> Result<(), O::Error>
+    where
+        O: ?Sized + raw::Output,
+    {
+        for property in raw::properties(&self.data, self.root).into_iter().flatten() {
+            o.write_bytes(property)?;
+        }
+
+        o.write_bytes(self.data.prefix(self.root))?;
+        self.data.raw(self.root).write_to(&self.data, o, None)?;
+        o.write_bytes(self.data.suffix())?;
+        Ok(())
+    }
+}
+
+impl str::FromStr for Document {
+    type Err = Error;
+
+    /// Parse a YAML document from a string, as with [`from_str`].
+    ///
+    /// [`from_str`]: crate::yaml::from_str
+    #[inline]
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        crate::yaml::from_slice(input)
+    }
+}
+
+impl fmt::Display for Document {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // If we're running in debug mode, assert that the produced document
+        // matches whatever would've been produced through `Document::write_to`.
+        //
+        // This is only enabled with `--cfg nondestructive_write_to_eq`.
+        if cfg!(nondestructive_write_to_eq) && !self.latin1 {
+            use bstr::BStr;
+            use std::fmt::Write;
+
+            #[repr(transparent)]
+            struct Inner<'a>(&'a Document);
+
+            impl fmt::Display for Inner<'_> {
+                #[inline]
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    self.0.display(f)
+                }
+            }
+
+            let mut string = String::new();
+            write!(string, "{}", Inner(self))?;
+            let mut bytes = Vec::new();
+
+            self.write_to(&mut bytes)
+                .expect("Document::write_to should not panic");
+
+            debug_assert_eq!(
+                BStr::new(string.as_bytes()),
+                BStr::new(&bytes),
+                "nondestructive_write_to_eq: ensure write_to produces the same output"
+            );
+
+            string.fmt(f)?;
+        } else {
+            self.display(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How sequences are treated when merging documents through
+/// [`Document::merge_with`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MergeStrategy {
+    /// Sequences are replaced wholesale, like any other value.
+    #[default]
+    Replace,
+    /// The items of the sequence being merged in are appended to the sequence
+    /// being merged into.
+    Concat,
+}
+
+/// Merge the value `other_id` in `other` into the value `id` in `data`.
+fn merge_value(data: &mut Data, id: Id, other: &Data, other_id: Id, strategy: MergeStrategy) {
+    match (data.raw(id), other.raw(other_id)) {
+        (Raw::Mapping(..), Raw::Mapping(raw)) => {
+            for item in &raw.items {
+                let item = other.mapping_item(*item);
+                let key = other.str(item.key.id);
+
+                let existing = data
+                    .mapping(id)
+                    .items
+                    .iter()
+                    .map(|item| data.mapping_item(*item))
+                    .find(|item| data.str(item.key.id) == key)
+                    .map(|item| item.value);
+
+                if let Some(existing) = existing {
+                    merge_value(data, existing, other, item.value, strategy);
+                    continue;
+                }
+
+                let mut mapping = MappingMut::new(data, id);
+                let inserted = mapping.insert(key, Separator::Auto);
+
+                // Bare keys might need quoting in an inline mapping, other keys
+                // keep the quoting they had in `other`.
+                let mut value = if matches!(item.key.kind, raw::RawStringKind::Bare) {
+                    inserted.with_key_kind(StringKind::Bare).into_value_mut()
+                } else {
+                    let value = inserted.into_value_mut();
+                    let original = value.data.insert_str(other.str(item.key.original));
+
+                    if let Some(parent) = value.data.layout(value.id).parent {
+                        if let Raw::MappingItem(new) = value.data.raw_mut(parent) {
+                            new.key.kind = item.key.kind;
+                            new.key.original = original;
+                        }
+                    }
+
+                    value
+                };
+
+                import_value(&mut value, other, item.value);
+            }
+        }
+        (Raw::Sequence(..), Raw::Sequence(raw)) if strategy == MergeStrategy::Concat => {
+            for item in &raw.items {
+                let item = other.sequence_item(*item);
+                let mut sequence = SequenceMut::new(data, id);
+                let mut value = sequence.push(Separator::Auto);
+                import_value(&mut value, other, item.value);
+            }
+        }
+        _ => {
+            // Aliases elsewhere in the document might refer to the replaced
+            // value, so it keeps its anchor unless the new value has one.
+            let anchor = data.layout(id).anchor;
+            import_value(&mut ValueMut::new(data, id), other, other_id);
+
+            if data.layout(id).anchor.is_none() {
+                data.set_anchor(id, anchor);
+            }
+        }
+    }
+}
+
+/// Replace the given value with a copy of the value `id` in `other`.
+fn import_value(value: &mut ValueMut<'_>, other: &Data, id: Id) {
+    let from = raw::indent_of(other, id);
+    let copy = value.data.import_tree(other, id, None);
+    value.place(copy, from);
+    rename_anchors(value.data, value.id);
+
+    // Block values can't be placed inside of inline collections.
+    if raw::is_inline_item(value.data, value.id) {
+        raw::force_flow(value.data, value.id);
+    }
+
+    if !value.data.raw(value.id).is_tabular() {
+        return;
+    }
+
+    // The rest of the last line of a block collection, such as a trailing
+    // comment, is stored after it so it has to be copied separately.
+    let (Some(source), Some(target)) = (
+        raw::Following::locate(other, id),
+        raw::Following::locate(value.data, value.id),
+    ) else {
+        return;
+    };
+
+    let line = source.get(other);
+    let line = &line[..line.find_byte(raw::NEWLINE).unwrap_or(line.len())];
+
+    let string = target.get(value.data);
+    let n = string.find_byte(raw::NEWLINE).unwrap_or(string.len());
+
+    let mut new = line.to_vec();
+    new.extend_from_slice(&string[n..]);
+    let new = value.data.insert_str(new);
+    target.set(value.data, new);
+}
+
+/// Rename anchors inside of the imported value with the given identifier which
+/// are also defined elsewhere in the document, along with the aliases inside
+/// of it which refer to them.
+///
+/// Otherwise an anchor in the imported value would take over aliases following
+/// it which refer to a value outside of it.
+fn rename_anchors(data: &mut Data, id: Id) {
+    let mut root = id;
+
+    while let Some(parent) = data.parent(root) {
+        root = parent;
+    }
+
+    let inside = data.walk(id).collect::<Vec<_>>();
+    let contained = inside.iter().copied().collect::<HashSet<_>>();
+
+    // NB: the names are collected once, so that each candidate name is only
+    // looked up in a set rather than compared against every anchor.
+    let mut taken = data
+        .walk(root)
+        .filter(|value| !contained.contains(value))
+        .filter_map(|value| Some(data.anchor(value)?.to_vec()))
+        .collect::<HashSet<_>>();
+
+    if taken.is_empty() {
+        return;
+    }
+
+    taken.extend(
+        inside
+            .iter()
+            .filter_map(|value| Some(data.anchor(*value)?.to_vec())),
+    );
+
+    let mut renamed = HashMap::new();
+
+    for value in &inside {
+        let Some(anchor) = data.layout(*value).anchor else {
+            continue;
+        };
+
+        let Some(name) = data.anchor(*value).map(|name| name.to_vec()) else {
+            continue;
+        };
+
+        let new = if let Some(new) = renamed.get(&name) {
+            Vec::clone(new)
+        } else if taken.contains(&name) {
+            let mut n = 2usize;
+
+            let new = loop {
+                let mut new = name.clone();
+                new.extend_from_slice(format!("_{n}").as_bytes());
+
+                if !taken.contains(&new) {
+                    break new;
+                }
+
+                n = n.wrapping_add(1);
+            };
+
+            taken.insert(new.clone());
+            renamed.insert(name.clone(), new.clone());
+            new
+        } else {
+            continue;
+        };
+
+        // Keep the whitespace preceeding the anchor.
+        let string = data.str(anchor.string);
+        let ws = string.len().saturating_sub(name.len()).saturating_sub(1);
+        let mut string = string[..ws].to_vec();
+        string.push(b'&');
+        string.extend_from_slice(&new);
+
+        let string = data.insert_str(string);
+
+        data.set_anchor(
+            *value,
+            Some(raw::Anchor {
+                string,
+                after_tag: anchor.after_tag,
+            }),
+        );
+    }
+
+    for value in inside {
+        let Raw::Alias(alias) = data.raw(value) else {
+            continue;
+        };
+
+        let Some(new) = renamed.get(data.str(alias.name).as_bytes()) else {
+            continue;
+        };
+
+        let name = data.insert_str(new);
+
+        if let Raw::Alias(alias) = data.raw_mut(value) {
+            alias.name = name;
+        }
+    }
+}
+
+/// Join a segment onto a path, as used by [`Document::leaves`] and
+/// [`Document::path_of`].
+fn join_path(path: &str, segment: impl fmt::Display) -> String {
+    if path.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{path}.{segment}")
+    }
+}
+
+/// Escape a mapping key so that it can be used as a segment in a path.
+fn escape_key(key: &[u8]) -> String {
+    key.to_str_lossy().replace('\\', "\\\\").replace('.', "\\.")
+}
+
+/// Unescape a segment of a JSON Pointer, returning `None` if it contains an
+/// invalid escape sequence.
+fn unescape_pointer(segment: &str) -> Option<String> {
+    let mut out = String::with_capacity(segment.len());
+    let mut chars = segment.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            '0' => out.push('~'),
+            '1' => out.push('/'),
+            _ => return None,
+        }
+    }
+
+    Some(out)
+}
+
+/// Iterator produced by [`Document::leaves`].
+struct Leaves<'a> {
+    data: &'a Data,
+    stack: Vec<(String, Id)>,
+}
+
+impl<'a> Iterator for Leaves<'a> {
+    type Item = (String, Value<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((path, id)) = self.stack.pop() {
+            match self.data.raw(id) {
+                Raw::Mapping(raw) => {
+                    for item in raw.items.iter().rev() {
+                        let item = self.data.mapping_item(*item);
+                        let key = escape_key(self.data.str(item.key.id));
+                        self.stack.push((join_path(&path, key), item.value));
+                    }
+                }
+                Raw::Sequence(raw) => {
+                    for (index, item) in raw.items.iter().enumerate().rev() {
+                        let item = self.data.sequence_item(*item);
+                        self.stack.push((join_path(&path, index), item.value));
+                    }
+                }
+                _ => {
+                    return Some((path, Value::new(self.data, id)));
+                }
+            }
+        }
+
+        None
     }
 }
 
 impl fmt::Debug for Document {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Document")
-            .field("suffix", &self.suffix)
+            .field("suffix", &self.data.suffix())
             .field("root", &self.root)
             .finish_non_exhaustive()
     }
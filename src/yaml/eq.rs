@@ -0,0 +1,164 @@
+use bstr::ByteSlice;
+
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::{self, Raw};
+
+/// How values are compared by [`semantic_eq`].
+#[derive(Clone, Copy)]
+pub(crate) struct Mode {
+    /// Keys in mappings must appear in the same order.
+    pub(crate) ordered: bool,
+    /// Numbers are compared by their value rather than their representation,
+    /// so that `1` equals `1.0`.
+    pub(crate) numeric: bool,
+}
+
+impl Mode {
+    /// As documented in [`Document::semantic_eq`].
+    ///
+    /// [`Document::semantic_eq`]: crate::yaml::Document::semantic_eq
+    pub(crate) const UNORDERED: Self = Self {
+        ordered: false,
+        numeric: false,
+    };
+
+    /// As documented in [`Document::semantic_eq_ordered`].
+    ///
+    /// [`Document::semantic_eq_ordered`]: crate::yaml::Document::semantic_eq_ordered
+    pub(crate) const ORDERED: Self = Self {
+        ordered: true,
+        numeric: false,
+    };
+
+    /// As documented in [`Value::eq_logical`].
+    ///
+    /// [`Value::eq_logical`]: crate::yaml::Value::eq_logical
+    pub(crate) const LOGICAL: Self = Self {
+        ordered: false,
+        numeric: true,
+    };
+}
+
+/// Test if two values are semantically equal according to the given `mode`.
+pub(crate) fn semantic_eq(a: &Data, a_id: Id, b: &Data, b_id: Id, mode: Mode) -> bool {
+    if custom_tag(a, a_id) != custom_tag(b, b_id) {
+        return false;
+    }
+
+    match (a.raw(a_id), b.raw(b_id)) {
+        (Raw::Null(..), Raw::Null(..)) => true,
+        (Raw::Boolean(lhs), Raw::Boolean(rhs)) => lhs.value == rhs.value,
+        (Raw::Number(lhs), Raw::Number(rhs)) if mode.numeric => {
+            number_eq(a.str(lhs.string), b.str(rhs.string))
+        }
+        (Raw::Number(lhs), Raw::Number(rhs)) => a.str(lhs.string) == b.str(rhs.string),
+        (Raw::String(lhs), Raw::String(rhs)) => a.str(lhs.id) == b.str(rhs.id),
+        (Raw::Alias(lhs), Raw::Alias(rhs)) => a.str(lhs.name) == b.str(rhs.name),
+        (Raw::Sequence(lhs), Raw::Sequence(rhs)) => {
+            lhs.items.len() == rhs.items.len()
+                && lhs.items.iter().zip(&rhs.items).all(|(lhs, rhs)| {
+                    let lhs = a.sequence_item(*lhs).value;
+                    let rhs = b.sequence_item(*rhs).value;
+                    semantic_eq(a, lhs, b, rhs, mode)
+                })
+        }
+        (Raw::Mapping(lhs), Raw::Mapping(rhs)) => {
+            if lhs.items.len() != rhs.items.len() {
+                return false;
+            }
+
+            let mut lhs = lhs
+                .items
+                .iter()
+                .map(|id| a.mapping_item(*id))
+                .collect::<Vec<_>>();
+
+            let mut rhs = rhs
+                .items
+                .iter()
+                .map(|id| b.mapping_item(*id))
+                .collect::<Vec<_>>();
+
+            if !mode.ordered {
+                // NB: sorting is stable, so values associated with duplicate
+                // keys are still compared in the order they appear.
+                lhs.sort_by_key(|item| a.str(item.key.id));
+                rhs.sort_by_key(|item| b.str(item.key.id));
+            }
+
+            lhs.iter().zip(&rhs).all(|(lhs, rhs)| {
+                a.str(lhs.key.id) == b.str(rhs.key.id)
+                    && semantic_eq(a, lhs.value, b, rhs.value, mode)
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Compare two numbers by their value.
+///
+/// Integers are compared exactly, including against floats, which are only
+/// equal to an integer if they have the exact same value. Unlike float
+/// comparison, `.nan` is considered equal to itself.
+fn number_eq(a: &[u8], b: &[u8]) -> bool {
+    let (Some(lhs), Some(rhs)) = (number(a), number(b)) else {
+        return a == b;
+    };
+
+    match (lhs, rhs) {
+        (Number::Integer(a), Number::Integer(b)) => a == b,
+        (Number::Integer(n), Number::Float(f)) | (Number::Float(f), Number::Integer(n)) => {
+            float_to_integer(f) == Some(n)
+        }
+        (Number::Float(a), Number::Float(b)) => a == b || a.is_nan() && b.is_nan(),
+    }
+}
+
+/// The value of a number.
+pub(crate) enum Number {
+    Integer(i128),
+    Float(f64),
+}
+
+/// Get the value of a number, preferring to read it as an integer.
+pub(crate) fn number(string: &[u8]) -> Option<Number> {
+    if let Some(n) = raw::parse_integer::<i128>(string) {
+        return Some(Number::Integer(n));
+    }
+
+    let float = match raw::SpecialFloat::detect(string) {
+        Some(raw::SpecialFloat::Infinity) => f64::INFINITY,
+        Some(raw::SpecialFloat::NegInfinity) => f64::NEG_INFINITY,
+        Some(raw::SpecialFloat::NaN) => f64::NAN,
+        None => lexical_core::parse(string).ok()?,
+    };
+
+    Some(Number::Float(float))
+}
+
+/// Convert a float to an integer if it has an integral value within the range
+/// of an `i128`.
+pub(crate) fn float_to_integer(f: f64) -> Option<i128> {
+    // NB: the bounds are powers of two, so they are exactly representable.
+    #[allow(clippy::cast_precision_loss)]
+    const MIN: f64 = i128::MIN as f64;
+
+    if f.fract() != 0.0 || !(MIN..-MIN).contains(&f) {
+        return None;
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    Some(f as i128)
+}
+
+/// Get a tag associated with the value which is not one of the core tags,
+/// since those have already been applied to the value itself.
+fn custom_tag(data: &Data, id: Id) -> Option<&[u8]> {
+    let tag = data.tag(id)?;
+
+    if raw::CoreTag::detect(tag).is_some() {
+        return None;
+    }
+
+    Some(tag.trim())
+}
@@ -3,18 +3,62 @@
 use core::fmt;
 use core::ops::Range;
 
+use bstr::ByteSlice;
+
+use crate::yaml::data::{Data, Id};
+
 /// An error raised by the YAML module.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// const INPUT: &str = "name: Aristotle\nbooks: [Organon, Physics\n";
+///
+/// let error = yaml::from_slice(INPUT).unwrap_err();
+/// assert_eq!(error.span(), 40..41);
+/// assert_eq!(error.line(), 2);
+/// assert_eq!(error.column(), 25);
+/// assert_eq!(error.to_string(), "error at line 2 column 25: bad sequence terminator");
+/// ```
 #[derive(Debug)]
 pub struct Error {
     span: Range<usize>,
+    line: usize,
+    column: usize,
     kind: ErrorKind,
 }
 
 impl Error {
-    /// Construct a new error.
-    #[inline]
-    pub(crate) const fn new(span: Range<usize>, kind: ErrorKind) -> Self {
-        Self { span, kind }
+    /// Construct a new error, locating the start of the span in the given
+    /// input.
+    pub(crate) fn new(input: &[u8], span: Range<usize>, kind: ErrorKind) -> Self {
+        let before = input.get(..span.start).unwrap_or(input);
+        let line_start = before.rfind_byte(b'\n').map_or(0, |n| n.saturating_add(1));
+        let line = before.find_iter("\n").count().saturating_add(1);
+        let column = before[line_start..].chars().count().saturating_add(1);
+
+        Self {
+            span,
+            line,
+            column,
+            kind,
+        }
+    }
+
+    /// Construct a new error about the value with the given identifier, which
+    /// is located where the value was parsed from if that is known.
+    pub(crate) fn value(data: &Data, id: Id, kind: ErrorKind) -> Self {
+        let span = data.span(id).unwrap_or_default();
+        let (line, column) = data.location(span.start).unwrap_or((1, 1));
+
+        Self {
+            span,
+            line,
+            column,
+            kind,
+        }
     }
 
     /// Get the range of the input span.
@@ -24,6 +68,20 @@ impl Error {
         self.span.clone()
     }
 
+    /// Get the line on which the error starts, counting from 1.
+    #[must_use]
+    #[inline]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Get the column at which the error starts, counting characters from 1.
+    #[must_use]
+    #[inline]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
     /// Get the kind of an error.
     #[must_use]
     #[inline]
@@ -37,8 +95,8 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{} (at {}-{})",
-            self.kind, self.span.start, self.span.end
+            "error at line {} column {}: {}",
+            self.line, self.column, self.kind
         )
     }
 }
@@ -46,7 +104,11 @@ impl fmt::Display for Error {
 impl std::error::Error for Error {}
 
 /// The kind of an [`Error`].
+///
+/// More kinds of errors might be added in the future, so this can't be matched
+/// exhaustively.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// Expect end of file.
     ///
@@ -164,6 +226,107 @@ pub enum ErrorKind {
     /// assert_eq!(&INPUT[error.span()], "\\ud800");
     /// ```
     BadUnicodeEscape,
+    /// A quoted string is missing its closing quote. The span covers the
+    /// string from its opening quote.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// const INPUT: &str = "name: 'Aristotle\n";
+    ///
+    /// let error = yaml::from_slice(INPUT).unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::UnterminatedString);
+    /// assert_eq!(&INPUT[error.span()], "'Aristotle\n");
+    /// ```
+    UnterminatedString,
+    /// A document doesn't have the same contents after being serialized and
+    /// parsed again, as detected by [`Document::validate_roundtrip`]. The span
+    /// covers the whole serialized document.
+    ///
+    /// [`Document::validate_roundtrip`]: crate::yaml::Document::validate_roundtrip
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("version: '42'")?;
+    /// doc.as_mut().into_mapping_mut().and_then(|m| m.get_into_mut("version")).unwrap().set_string_with("42", yaml::StringKind::Bare);
+    ///
+    /// let error = doc.validate_roundtrip().unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::RoundtripMismatch);
+    /// assert_eq!(error.span(), 0..11);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    RoundtripMismatch,
+    /// A value was expected to be a string, as by [`ValueMut::edit_string`].
+    /// The span and location are those of the value as returned by
+    /// [`Value::span`] and [`Value::location`], or empty if they are not known.
+    ///
+    /// [`ValueMut::edit_string`]: crate::yaml::ValueMut::edit_string
+    /// [`Value::span`]: crate::yaml::Value::span
+    /// [`Value::location`]: crate::yaml::Value::location
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("port: 80")?;
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut port = root.get_mut("port").context("missing port")?;
+    ///
+    /// let error = port.edit_string(|s| s.push('0')).unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::ExpectedString);
+    /// assert_eq!(error.span(), 6..8);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    ExpectedString,
+    /// A string was expected to be valid UTF-8, as by
+    /// [`ValueMut::edit_string`]. The span and location are those of the
+    /// value, as with [`ErrorKind::ExpectedString`].
+    ///
+    /// [`ValueMut::edit_string`]: crate::yaml::ValueMut::edit_string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(b"name: caf\xe9\n")?;
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut name = root.get_mut("name").context("missing name")?;
+    ///
+    /// let error = name.edit_string(|s| s.push('!')).unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::BadUtf8);
+    /// assert_eq!(error.span(), 6..10);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    BadUtf8,
+    /// An alias refers to an anchor on a value which contains it, so expanding
+    /// it would never terminate. The span and location are those of the alias,
+    /// as with [`ErrorKind::ExpectedString`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("a: &x [*x]")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let a = root.get("a").and_then(|v| v.as_sequence()).context("missing a")?;
+    ///
+    /// let error = a.get(0).context("missing alias")?.try_resolve_alias().unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::AnchorCycle);
+    /// assert_eq!(error.span(), 7..9);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    AnchorCycle,
 }
 
 impl fmt::Display for ErrorKind {
@@ -176,6 +339,11 @@ impl fmt::Display for ErrorKind {
             ErrorKind::BadEscape => write!(f, "bad escape"),
             ErrorKind::BadHexEscape => write!(f, "bad hex escape"),
             ErrorKind::BadUnicodeEscape => write!(f, "bad unicode escape"),
+            ErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            ErrorKind::RoundtripMismatch => write!(f, "document changed when parsed again"),
+            ErrorKind::ExpectedString => write!(f, "expected a string"),
+            ErrorKind::BadUtf8 => write!(f, "string is not valid UTF-8"),
+            ErrorKind::AnchorCycle => write!(f, "alias refers to a value which contains it"),
         }
     }
 }
@@ -0,0 +1,283 @@
+use core::ops::{Deref, DerefMut};
+
+use bstr::ByteSlice;
+
+use crate::yaml::data::Id;
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::{CollectionStyle, MappingMut, Separator, SequenceMut, StringKind, ValueMut};
+
+/// A value which has just been inserted into a mapping through
+/// [`MappingMut::insert`].
+///
+/// This dereferences to a [`ValueMut`] of the inserted value, and also allows
+/// for adjusting the formatting of the inserted item by chaining calls.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut doc = yaml::from_slice(
+///     r"
+///     name: web
+///     "
+/// )?;
+///
+/// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+///
+/// root.insert("port", yaml::Separator::Auto)
+///     .with_separator("  ")
+///     .with_comment("The port to listen on.")
+///     .with_key_kind(yaml::StringKind::Double)
+///     .set_u32(8080);
+///
+/// assert_eq!(
+///     doc.to_string(),
+///     r#"
+///     name: web
+///     ## The port to listen on.
+///     "port":  8080
+///     "#
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub struct InsertedMut<'a> {
+    value: ValueMut<'a>,
+    new: bool,
+}
+
+impl<'a> InsertedMut<'a> {
+    pub(crate) fn new(value: ValueMut<'a>, new: bool) -> Self {
+        Self { value, new }
+    }
+
+    /// Get the identifier of the inserted mapping item.
+    fn item(&self) -> Option<Id> {
+        self.value.data.layout(self.value.id).parent
+    }
+
+    /// Set the whitespace which separates the value from the `:` following
+    /// the key, like the spaces in `key:   value`.
+    ///
+    /// This has the same effect as inserting with [`Separator::Custom`], so
+    /// the separator must only contain spaces. In debug builds, this is
+    /// checked with an assertion.
+    ///
+    /// [`Separator::Custom`]: crate::yaml::Separator::Custom
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("one: 1")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.insert("two", yaml::Separator::Auto).with_separator("   ").set_u32(2);
+    ///
+    /// assert_eq!(doc.to_string(), "one: 1\ntwo:   2");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_separator(self, separator: &str) -> Self {
+        if let Some(separator) = Separator::Custom(separator).insert(self.value.data, 0) {
+            self.value.data.set_prefix(self.value.id, separator);
+        }
+
+        self
+    }
+
+    /// Add a comment on its own line before the key, indented to match it.
+    /// Comments spanning multiple lines are split up into multiple comment
+    /// lines.
+    ///
+    /// Comments are only added to newly inserted keys in block mappings, so
+    /// this does nothing if the key already existed or if the mapping is
+    /// inline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("retries: 1")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.insert("retries", yaml::Separator::Auto).with_comment("Ignored.").set_u32(2);
+    /// root.insert("timeout", yaml::Separator::Auto).with_comment("In seconds.\nSet to 0 to disable.").set_u32(30);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "retries: 2\n# In seconds.\n# Set to 0 to disable.\ntimeout: 30"
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_comment<C>(self, comment: C) -> Self
+    where
+        C: AsRef<str>,
+    {
+        if !self.new {
+            return self;
+        }
+
+        let Some(item) = self.item() else {
+            return self;
+        };
+
+        let data = &mut *self.value.data;
+
+        let Some(mapping) = data.layout(item).parent else {
+            return self;
+        };
+
+        let mapping = data.mapping(mapping);
+
+        if !matches!(mapping.kind, raw::MappingKind::Mapping) {
+            return self;
+        }
+
+        let indent = mapping.indent;
+        let mut prefix = data.prefix(item).to_vec();
+
+        for line in comment.as_ref().lines() {
+            prefix.push(b'#');
+
+            if !line.is_empty() {
+                prefix.push(raw::SPACE);
+                prefix.extend_from_slice(line.as_bytes());
+            }
+
+            prefix.push(raw::NEWLINE);
+            prefix.resize(prefix.len().saturating_add(indent), raw::SPACE);
+        }
+
+        let prefix = data.insert_str(prefix);
+        data.set_prefix(item, prefix);
+        self
+    }
+
+    /// Set how the key of the inserted item is quoted.
+    ///
+    /// Keys which can't be written without quotes, like `a: b` or `#c`, are
+    /// quoted even if [`StringKind::Bare`] is requested, since they would
+    /// otherwise produce invalid YAML or read back as something else.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("one: 1")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.insert("two", yaml::Separator::Auto).with_key_kind(yaml::StringKind::Single).set_u32(2);
+    ///
+    /// assert_eq!(doc.to_string(), "one: 1\n'two': 2");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_key_kind(self, kind: StringKind) -> Self {
+        let Some(item) = self.item() else {
+            return self;
+        };
+
+        let kind = match kind {
+            StringKind::Bare => {
+                let Raw::MappingItem(raw) = self.value.data.raw(item) else {
+                    return self;
+                };
+
+                let key = self.value.data.str(raw.key.id).to_str_lossy();
+                let inline = raw::is_inline_item(self.value.data, self.value.id);
+                raw::key_kind_like(self.value.data, None, &key, inline)
+            }
+            StringKind::Single => raw::RawStringKind::Single,
+            StringKind::Double => raw::RawStringKind::Double,
+        };
+
+        if let Raw::MappingItem(item) = self.value.data.raw_mut(item) {
+            item.key.kind = kind;
+            item.key.original = item.key.id;
+        }
+
+        self
+    }
+
+    /// Convert into a [`ValueMut`] of the inserted value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("one: 1")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut value: yaml::ValueMut<'_> = root.insert("two", yaml::Separator::Auto).into_value_mut();
+    /// value.set_u32(2);
+    ///
+    /// assert_eq!(doc.to_string(), "one: 1\ntwo: 2");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn into_value_mut(self) -> ValueMut<'a> {
+        self.value
+    }
+
+    /// Make the inserted value into a mapping, as with
+    /// [`ValueMut::make_mapping`].
+    #[must_use]
+    pub fn make_mapping(self) -> MappingMut<'a> {
+        self.value.make_mapping()
+    }
+
+    /// Make the inserted value into a sequence, as with
+    /// [`ValueMut::make_sequence`].
+    #[must_use]
+    pub fn make_sequence(self) -> SequenceMut<'a> {
+        self.value.make_sequence()
+    }
+
+    /// Make the inserted value into a mapping with the given style, as with
+    /// [`ValueMut::make_mapping_with`].
+    #[must_use]
+    pub fn make_mapping_with(self, style: CollectionStyle) -> MappingMut<'a> {
+        self.value.make_mapping_with(style)
+    }
+
+    /// Make the inserted value into a sequence with the given style, as with
+    /// [`ValueMut::make_sequence_with`].
+    #[must_use]
+    pub fn make_sequence_with(self, style: CollectionStyle) -> SequenceMut<'a> {
+        self.value.make_sequence_with(style)
+    }
+
+    /// Make the inserted value into a sequence which isn't indented under its
+    /// key, as with [`ValueMut::make_indentless_sequence`].
+    #[must_use]
+    pub fn make_indentless_sequence(self) -> SequenceMut<'a> {
+        self.value.make_indentless_sequence()
+    }
+}
+
+impl<'a> Deref for InsertedMut<'a> {
+    type Target = ValueMut<'a>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl DerefMut for InsertedMut<'_> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
@@ -59,7 +59,7 @@ impl DoubleEndedIterator for Iter<'_> {
 
     #[inline]
     fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
-        let item = self.data.mapping_item(*self.iter.nth(n)?);
+        let item = self.data.mapping_item(*self.iter.nth_back(n)?);
         let key = self.data.str(item.key.id);
         let value = Value::new(self.data, item.value);
         Some((key, value))
@@ -67,3 +67,113 @@ impl DoubleEndedIterator for Iter<'_> {
 }
 
 impl ExactSizeIterator for Iter<'_> {}
+
+/// An immutable iterator over the keys of a [`Mapping`][crate::yaml::Mapping].
+///
+/// See [`Mapping::keys`][crate::yaml::Mapping::keys].
+pub struct Keys<'a> {
+    data: &'a Data,
+    iter: slice::Iter<'a, Id>,
+}
+
+impl<'a> Keys<'a> {
+    #[inline]
+    pub(crate) fn new(data: &'a Data, slice: &'a [Id]) -> Self {
+        Self {
+            data,
+            iter: slice.iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for Keys<'a> {
+    type Item = &'a BStr;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.data.mapping_item(*self.iter.next()?);
+        Some(self.data.str(item.key.id))
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let item = self.data.mapping_item(*self.iter.nth(n)?);
+        Some(self.data.str(item.key.id))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Keys<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.data.mapping_item(*self.iter.next_back()?);
+        Some(self.data.str(item.key.id))
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let item = self.data.mapping_item(*self.iter.nth_back(n)?);
+        Some(self.data.str(item.key.id))
+    }
+}
+
+impl ExactSizeIterator for Keys<'_> {}
+
+/// An immutable iterator over the values of a [`Mapping`][crate::yaml::Mapping].
+///
+/// See [`Mapping::values`][crate::yaml::Mapping::values].
+pub struct Values<'a> {
+    data: &'a Data,
+    iter: slice::Iter<'a, Id>,
+}
+
+impl<'a> Values<'a> {
+    #[inline]
+    pub(crate) fn new(data: &'a Data, slice: &'a [Id]) -> Self {
+        Self {
+            data,
+            iter: slice.iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for Values<'a> {
+    type Item = Value<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.data.mapping_item(*self.iter.next()?);
+        Some(Value::new(self.data, item.value))
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let item = self.data.mapping_item(*self.iter.nth(n)?);
+        Some(Value::new(self.data, item.value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Values<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.data.mapping_item(*self.iter.next_back()?);
+        Some(Value::new(self.data, item.value))
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let item = self.data.mapping_item(*self.iter.nth_back(n)?);
+        Some(Value::new(self.data, item.value))
+    }
+}
+
+impl ExactSizeIterator for Values<'_> {}
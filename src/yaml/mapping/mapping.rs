@@ -1,11 +1,46 @@
 use core::fmt;
+use std::collections::HashSet;
 
 use bstr::BStr;
+#[cfg(feature = "indexmap")]
+use bstr::ByteSlice;
+#[cfg(feature = "indexmap")]
+use indexmap::IndexMap;
 
 use crate::yaml::data::{Data, Id};
-use crate::yaml::mapping::Iter;
+use crate::yaml::mapping::{Iter, Keys, Values};
+use crate::yaml::raw::{self, Raw};
 use crate::yaml::Value;
 
+macro_rules! get_or {
+    ($name:ident, $as:ident, $ty:ty, $string:literal, $value:expr, $default:expr) => {
+        #[doc = concat!("Get a value from the mapping by its key as a [`", stringify!($ty), "`], or")]
+        /// the given default if the key is missing or if its value has a
+        /// different type.
+        ///
+        #[doc = concat!("This is a shorthand for calling [`Mapping::get`] followed by [`Value::", stringify!($as), "`].")]
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use anyhow::Context;
+        /// use nondestructive::yaml;
+        ///
+        #[doc = concat!("let doc = yaml::from_slice(\"value: ", $string, "\\nstring: text\")?;")]
+        /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+        ///
+        #[doc = concat!("assert_eq!(root.", stringify!($name), "(\"value\", ", stringify!($default), "), ", stringify!($value), ");")]
+        #[doc = concat!("assert_eq!(root.", stringify!($name), "(\"string\", ", stringify!($default), "), ", stringify!($default), ");")]
+        #[doc = concat!("assert_eq!(root.", stringify!($name), "(\"missing\", ", stringify!($default), "), ", stringify!($default), ");")]
+        /// # Ok::<_, anyhow::Error>(())
+        /// ```
+        #[must_use]
+        pub fn $name(&self, key: &str, default: $ty) -> $ty {
+            self.get(key).and_then(|v| v.$as()).unwrap_or(default)
+        }
+    };
+}
+
 /// Accessor for a mapping.
 ///
 /// # Examples
@@ -162,6 +197,34 @@ impl<'a> Mapping<'a> {
         self.data.mapping(self.id).items.is_empty()
     }
 
+    /// Test if the mapping contains the given key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     one: 1
+    ///     "two": 2
+    ///     three:
+    ///     "#,
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert!(root.contains_key("one"));
+    /// assert!(root.contains_key("two"));
+    /// assert!(root.contains_key("three"));
+    /// assert!(!root.contains_key("four"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.find(key).is_some()
+    }
+
     /// Get a value from the mapping by its key.
     ///
     /// # Examples
@@ -193,10 +256,95 @@ impl<'a> Mapping<'a> {
     /// ```
     #[must_use]
     pub fn get(&self, key: &str) -> Option<Value<'a>> {
+        let id = self.find(key)?;
+        Some(Value::new(self.data, id))
+    }
+
+    /// Get a value from the mapping by its key, falling back to the mappings
+    /// merged into it through `<<` merge keys if the key is not present.
+    ///
+    /// The value of a merge key is either a single mapping or alias to one,
+    /// like `<<: *defaults`, or a sequence of them, like `<<: [*a, *b]`. When
+    /// several mappings are merged, the first one which contains the key wins.
+    /// Merge keys in merged mappings are followed as well.
+    ///
+    /// Unlike [`Mapping::get`], this doesn't reflect how the document is
+    /// written, but its meaning under the merge key convention.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     base: &base
+    ///       image: nginx
+    ///       replicas: 1
+    ///     extra: &extra
+    ///       replicas: 2
+    ///       port: 80
+    ///     web:
+    ///       <<: [*base, *extra]
+    ///       replicas: 3
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let web = root.get("web").and_then(|v| v.as_mapping()).context("missing web")?;
+    ///
+    /// assert!(web.get("image").is_none());
+    /// assert_eq!(web.get_merged("image").and_then(|v| v.as_str()), Some("nginx"));
+    /// assert_eq!(web.get_merged("replicas").and_then(|v| v.as_u32()), Some(3));
+    /// assert_eq!(web.get_merged("port").and_then(|v| v.as_u32()), Some(80));
+    /// assert!(web.get_merged("missing").is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_merged(&self, key: &str) -> Option<Value<'a>> {
+        let id = find_merged(self.data, self.id, key, &mut Vec::new())?;
+        Some(Value::new(self.data, id))
+    }
+
+    /// Get a value from the mapping by its key, ignoring the ASCII case of
+    /// both the key and the keys in the mapping.
+    ///
+    /// If several keys only differ in case, the value of the first one in the
+    /// order of the document is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     Host: localhost
+    ///     PORT: 8080
+    ///     port: 80
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// assert_eq!(root.get_case_insensitive("host").and_then(|v| v.as_str()), Some("localhost"));
+    /// assert_eq!(root.get_case_insensitive("Port").and_then(|v| v.as_u32()), Some(8080));
+    /// assert_eq!(root.get("port").and_then(|v| v.as_u32()), Some(80));
+    /// assert!(root.get_case_insensitive("user").is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_case_insensitive(&self, key: &str) -> Option<Value<'a>> {
         for item in &self.data.mapping(self.id).items {
             let item = self.data.mapping_item(*item);
 
-            if self.data.str(item.key.id) == key {
+            if self
+                .data
+                .str(item.key.id)
+                .eq_ignore_ascii_case(key.as_bytes())
+            {
                 return Some(Value::new(self.data, item.value));
             }
         }
@@ -204,6 +352,329 @@ impl<'a> Mapping<'a> {
         None
     }
 
+    /// Get a string value from the mapping by its key.
+    ///
+    /// This is a cheaper alternative to calling [`Mapping::get`] followed by
+    /// [`Value::as_bstr`], and returns `None` if the key is missing or if its
+    /// value is not a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     name: nondestructive
+    ///     quoted: "I am a quoted string!"
+    ///     number: 42
+    ///     "#
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// assert_eq!(root.get_str("name"), Some("nondestructive".into()));
+    /// assert_eq!(root.get_str("quoted"), Some("I am a quoted string!".into()));
+    /// assert_eq!(root.get_str("number"), None);
+    /// assert_eq!(root.get_str("missing"), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_str(&self, key: &str) -> Option<&'a BStr> {
+        let id = self.find(key)?;
+
+        match self.data.raw(id) {
+            Raw::String(raw) => Some(self.data.str(raw.id)),
+            _ => None,
+        }
+    }
+
+    /// Get every value associated with the given key, in the order in which
+    /// they appear in the mapping.
+    ///
+    /// Mappings may contain the same key more than once, in which case
+    /// [`Mapping::get`] only finds the first value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     name: first
+    ///     port: 80
+    ///     name: second
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// let names = root.get_all("name").flat_map(|v| v.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(names, ["first", "second"]);
+    /// assert_eq!(root.get_all("missing").count(), 0);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn get_all<'k>(&self, key: &'k str) -> impl Iterator<Item = Value<'a>> + 'k
+    where
+        'a: 'k,
+    {
+        let data = self.data;
+
+        data.mapping(self.id)
+            .items
+            .iter()
+            .map(move |item| data.mapping_item(*item))
+            .filter(move |item| data.str(item.key.id) == key)
+            .map(move |item| Value::new(data, item.value))
+    }
+
+    /// Get a value from the mapping by its key, or the given default if the
+    /// key is missing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     replicas: 3
+    ///     "
+    /// )?;
+    ///
+    /// let defaults = yaml::from_slice("1")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// assert_eq!(root.get_or("replicas", defaults.as_ref()).as_u32(), Some(3));
+    /// assert_eq!(root.get_or("missing", defaults.as_ref()).as_u32(), Some(1));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_or(&self, key: &str, default: Value<'a>) -> Value<'a> {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Get a string value from the mapping by its key, or the given default
+    /// if the key is missing or if its value is not a string.
+    ///
+    /// This is a shorthand for calling [`Mapping::get`] followed by
+    /// [`Value::as_str`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     name: web
+    ///     port: 80
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// assert_eq!(root.get_str_or("name", "default"), "web");
+    /// assert_eq!(root.get_str_or("port", "default"), "default");
+    /// assert_eq!(root.get_str_or("missing", "default"), "default");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_str_or<'b>(&self, key: &str, default: &'b str) -> &'b str
+    where
+        'a: 'b,
+    {
+        self.get(key).and_then(|v| v.as_str()).unwrap_or(default)
+    }
+
+    get_or!(get_i64_or, as_i64, i64, "-42", -42, 0);
+    get_or!(get_f64_or, as_f64, f64, "10.42", 10.42, 0.0);
+    get_or!(get_bool_or, as_bool, bool, "true", true, false);
+
+    /// Test if renaming the key `from` into `to` would collide with an
+    /// existing key in the mapping.
+    ///
+    /// This is the case if `to` is already present in the mapping and differs
+    /// from `from`. Renaming a key to itself never collides.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     name: web
+    ///     image: nginx
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// assert!(root.would_collide("name", "image"));
+    /// assert!(!root.would_collide("name", "title"));
+    /// assert!(!root.would_collide("name", "name"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn would_collide(&self, from: &str, to: &str) -> bool {
+        from != to && self.find(to).is_some()
+    }
+
+    /// Get the keys out of `required` which are not present in the mapping, in
+    /// the order in which they were given.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     name: web
+    ///     image: nginx
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// assert_eq!(root.missing_keys(&["replicas", "name", "ports"]), ["replicas", "ports"]);
+    /// assert!(root.missing_keys(&["image", "name"]).is_empty());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn missing_keys<'k>(&self, required: &[&'k str]) -> Vec<&'k str> {
+        let keys = self
+            .data
+            .mapping(self.id)
+            .items
+            .iter()
+            .map(|item| self.data.str(self.data.mapping_item(*item).key.id))
+            .collect::<HashSet<_>>();
+
+        required
+            .iter()
+            .copied()
+            .filter(|key| !keys.contains(BStr::new(key)))
+            .collect()
+    }
+
+    /// Get the first key in the mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     apiVersion: v1
+    ///     kind: ConfigMap
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.first_key(), Some("apiVersion".into()));
+    ///
+    /// let doc = yaml::from_slice("{}")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.first_key(), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn first_key(&self) -> Option<&'a BStr> {
+        let item = self.data.mapping(self.id).items.first()?;
+        Some(self.data.str(self.data.mapping_item(*item).key.id))
+    }
+
+    /// Get the first key and value in the mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     apiVersion: v1
+    ///     kind: ConfigMap
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let (key, value) = root.first().context("missing first")?;
+    /// assert_eq!(key, "apiVersion");
+    /// assert_eq!(value.as_str(), Some("v1"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn first(&self) -> Option<(&'a BStr, Value<'a>)> {
+        let item = self.data.mapping(self.id).items.first()?;
+        let item = self.data.mapping_item(*item);
+        Some((
+            self.data.str(item.key.id),
+            Value::new(self.data, item.value),
+        ))
+    }
+
+    /// Get the last key and value in the mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     apiVersion: v1
+    ///     kind: ConfigMap
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let (key, value) = root.last().context("missing last")?;
+    /// assert_eq!(key, "kind");
+    /// assert_eq!(value.as_str(), Some("ConfigMap"));
+    ///
+    /// let doc = yaml::from_slice("{}")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert!(root.last().is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn last(&self) -> Option<(&'a BStr, Value<'a>)> {
+        let item = self.data.mapping(self.id).items.last()?;
+        let item = self.data.mapping_item(*item);
+        Some((
+            self.data.str(item.key.id),
+            Value::new(self.data, item.value),
+        ))
+    }
+
+    /// Find the identifier of the value associated with the given key.
+    pub(crate) fn find(&self, key: &str) -> Option<Id> {
+        for item in &self.data.mapping(self.id).items {
+            let item = self.data.mapping_item(*item);
+
+            if self.data.str(item.key.id) == key {
+                return Some(item.value);
+            }
+        }
+
+        None
+    }
+
     /// Returns an iterator over the [Mapping].
     ///
     /// # Examples
@@ -229,12 +700,244 @@ impl<'a> Mapping<'a> {
     pub fn iter(&self) -> Iter<'a> {
         Iter::new(self.data, &self.data.mapping(self.id).items)
     }
+
+    /// Returns an iterator over the keys of the [Mapping].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     one: 1
+    ///     two: 2
+    ///     three: 3
+    ///     "#,
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert!(root.keys().eq(["one", "two", "three"]));
+    /// assert!(root.keys().rev().eq(["three", "two", "one"]));
+    /// assert_eq!(root.keys().len(), 3);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn keys(&self) -> Keys<'a> {
+        Keys::new(self.data, &self.data.mapping(self.id).items)
+    }
+
+    /// Returns an iterator over the values of the [Mapping].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     one: 1
+    ///     two: 2
+    ///     three: 3
+    ///     "#,
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert!(root.values().flat_map(|value| value.as_u32()).eq([1, 2, 3]));
+    /// assert_eq!(root.values().next_back().and_then(|value| value.as_u32()), Some(3));
+    /// assert_eq!(root.values().len(), 3);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn values(&self) -> Values<'a> {
+        Values::new(self.data, &self.data.mapping(self.id).items)
+    }
+
+    /// Collect the mapping into an [`IndexMap`], which retains the order of
+    /// the keys in the document.
+    ///
+    /// Keys which are not valid UTF-8 are converted lossily. If a key is
+    /// repeated, the first value associated with it is used, just like with
+    /// [`Mapping::get`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     zebra: 1
+    ///     apple: 2
+    ///     mango: 3
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let map = root.to_index_map();
+    ///
+    /// assert!(map.keys().eq(["zebra", "apple", "mango"]));
+    /// assert_eq!(map.get("apple").and_then(|v| v.as_u32()), Some(2));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "indexmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "indexmap")))]
+    #[must_use]
+    pub fn to_index_map(&self) -> IndexMap<String, Value<'a>> {
+        let mut map = IndexMap::with_capacity(self.len());
+
+        for (key, value) in self {
+            map.entry(key.to_str_lossy().into_owned()).or_insert(value);
+        }
+
+        map
+    }
+
+    /// Get the whitespace padding just inside of the delimiters of an inline
+    /// mapping, like the spaces in `{ one: 1, two: 2 }`.
+    ///
+    /// The leading padding is the whitespace following `{` and the
+    /// trailing padding is the whitespace preceeding `}`. An empty
+    /// mapping only has trailing padding.
+    ///
+    /// Returns `None` if this is not an inline mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("{ one: 1, two: 2 }")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.padding(), Some((" ".into(), " ".into())));
+    ///
+    /// let doc = yaml::from_slice("{one: 1, two: 2}")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.padding(), Some(("".into(), "".into())));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn padding(&self) -> Option<(&'a BStr, &'a BStr)> {
+        let raw = self.data.mapping(self.id);
+
+        let raw::MappingKind::Inline { suffix, .. } = &raw.kind else {
+            return None;
+        };
+
+        let leading = match raw.items.first() {
+            Some(item) => self.data.prefix(*item),
+            None => BStr::new(""),
+        };
+
+        Some((leading, self.data.str(*suffix)))
+    }
+
+    /// Apply a conversion to every value in the mapping, collecting the ones
+    /// which converted successfully together with their keys.
+    ///
+    /// Values for which the conversion returns `None` are skipped.
+    ///
+    /// Keys are borrowed as [`BStr`] like with [`Mapping::iter`], since they
+    /// aren't necessarily valid UTF-8 and this avoids allocating a string for
+    /// every key. The conversion is [`FnMut`], which any [`Fn`] also is, so
+    /// that it can keep state between values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     one: 1
+    ///     two: 2
+    ///     three: three
+    ///     four: 4
+    ///     ",
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let numbers = root.map_values(|v| v.as_u32());
+    ///
+    /// assert_eq!(numbers, [("one".into(), 1), ("two".into(), 2), ("four".into(), 4)]);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn map_values<T, F>(&self, mut f: F) -> Vec<(&'a BStr, T)>
+    where
+        F: FnMut(Value<'a>) -> Option<T>,
+    {
+        self.iter()
+            .filter_map(|(key, value)| Some((key, f(value)?)))
+            .collect()
+    }
+}
+
+/// Find a key in the mapping with the given identifier, or in the mappings
+/// merged into it. The `visited` mappings are skipped in case they are merged
+/// into each other.
+fn find_merged(data: &Data, id: Id, key: &str, visited: &mut Vec<Id>) -> Option<Id> {
+    if visited.contains(&id) {
+        return None;
+    }
+
+    visited.push(id);
+
+    let Raw::Mapping(raw) = data.raw(id) else {
+        return None;
+    };
+
+    let mut merges = Vec::new();
+
+    for item in &raw.items {
+        let item = data.mapping_item(*item);
+        let k = data.str(item.key.id);
+
+        if k == key {
+            return Some(item.value);
+        }
+
+        if k == "<<" {
+            merges.push(item.value);
+        }
+    }
+
+    for merge in merges {
+        let merge = raw::resolve_alias(data, merge).unwrap_or(merge);
+
+        let sources = match data.raw(merge) {
+            Raw::Sequence(raw) => raw
+                .items
+                .iter()
+                .map(|item| data.sequence_item(*item).value)
+                .collect(),
+            _ => vec![merge],
+        };
+
+        for source in sources {
+            let source = raw::resolve_alias(data, source).unwrap_or(source);
+
+            if let Some(value) = find_merged(data, source, key, visited) {
+                return Some(value);
+            }
+        }
+    }
+
+    None
 }
 
 impl fmt::Display for Mapping<'_> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.data.mapping(self.id).display(self.data, f, None)
+        self.data
+            .mapping(self.id)
+            .write_to(self.data, &mut raw::Fmt(f))
     }
 }
 
@@ -1,8 +1,12 @@
+use core::cmp::Ordering;
 use core::mem;
+use std::collections::HashSet;
+
+use bstr::{BStr, ByteSlice};
 
 use crate::yaml::data::{Data, Id, StringId};
 use crate::yaml::raw::{self, new_bool, new_string, Raw};
-use crate::yaml::{Block, Mapping, Separator, ValueMut};
+use crate::yaml::{Block, InsertedMut, Mapping, Null, Scalar, Separator, ValueMut};
 
 /// Mutator for a mapping.
 ///
@@ -83,7 +87,7 @@ macro_rules! insert_float {
             K: AsRef<[u8]>,
         {
             let mut buffer = ryu::Buffer::new();
-            let number = self.data.insert_str(buffer.format(value));
+            let number = self.data.insert_str(raw::format_float(&mut buffer, value));
             let value = Raw::Number(raw::Number::new(number, crate::yaml::serde_hint::$hint));
             self._insert(key.as_ref(), Separator::Auto, value);
         }
@@ -166,18 +170,34 @@ impl<'a> MappingMut<'a> {
 
         let key = raw::String::new(raw::RawStringKind::Bare, key, key);
 
-        let item_prefix = if self.data.mapping(self.id).items.last().is_some() {
-            self.make_prefix()
-        } else {
-            self.data.insert_str("")
+        let inline = matches!(
+            self.data.mapping(self.id).kind,
+            raw::MappingKind::Inline { .. }
+        );
+
+        let item_prefix = match &self.data.mapping(self.id).items[..] {
+            [] => self.data.insert_str(""),
+            [.., _, last] if inline => self.data.layout(*last).prefix,
+            [..] if inline => self.data.insert_str(" "),
+            [..] => {
+                // Anything which trails the last item on the same line, like
+                // a comment, stays in front of the new item.
+                let mut prefix = raw::take_rest_of_line(self.data, self.id).unwrap_or_default();
+                let rest = self.make_prefix();
+                prefix.extend_from_slice(self.data.str(rest));
+                self.data.insert_str(prefix)
+            }
         };
 
         let item_id = self
             .data
             .insert(Raw::Null(raw::Null::Empty), item_prefix, Some(self.id));
 
-        let value_prefix = match separator {
-            Separator::Auto => {
+        let width = self.data.str(key.id).chars().count().saturating_add(1);
+
+        let value_prefix = match separator.insert(self.data, width) {
+            Some(value_prefix) => value_prefix,
+            None => {
                 if value.is_tabular() {
                     let mapping = self.data.mapping(self.id);
 
@@ -193,13 +213,18 @@ impl<'a> MappingMut<'a> {
                     self.data.insert_str(" ")
                 }
             }
-            Separator::Custom(separator) => self.data.insert_str(separator),
         };
 
         let value = self.data.insert(value, value_prefix, Some(item_id));
 
-        self.data
-            .replace(item_id, Raw::MappingItem(raw::MappingItem { key, value }));
+        self.data.replace(
+            item_id,
+            Raw::MappingItem(raw::MappingItem {
+                key,
+                value,
+                suffix: None,
+            }),
+        );
 
         self.data.mapping_mut(self.id).items.push(item_id);
         value
@@ -314,15 +339,8 @@ impl<'a> MappingMut<'a> {
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     pub fn get_mut(&mut self, key: &str) -> Option<ValueMut<'_>> {
-        for item in &self.data.mapping(self.id).items {
-            let item = self.data.mapping_item(*item);
-
-            if self.data.str(item.key.id) == key {
-                return Some(ValueMut::new(self.data, item.value));
-            }
-        }
-
-        None
+        let id = self.as_ref().find(key)?;
+        Some(ValueMut::new(self.data, id))
     }
 
     /// Get a value mutably from the mutable mapping with the lifetime of the
@@ -362,15 +380,83 @@ impl<'a> MappingMut<'a> {
     /// ```
     #[must_use]
     pub fn get_into_mut(self, key: &str) -> Option<ValueMut<'a>> {
-        for item in &self.data.mapping(self.id).items {
-            let item = self.data.mapping_item(*item);
+        let id = self.as_ref().find(key)?;
+        Some(ValueMut::new(self.data, id))
+    }
 
-            if self.data.str(item.key.id) == key {
-                return Some(ValueMut::new(self.data, item.value));
-            }
-        }
+    /// Get a value mutably from the mapping, or insert the value produced by
+    /// `f` under the given key if it is missing.
+    ///
+    /// This is similar to [`HashMap::entry`] followed by
+    /// [`Entry::or_insert_with`]. Since [`ValueMut::make_mapping`] and
+    /// [`ValueMut::make_sequence`] leave existing collections as they are,
+    /// they can be chained onto this to get or create nested collections.
+    ///
+    /// [`HashMap::entry`]: std::collections::HashMap::entry
+    /// [`Entry::or_insert_with`]: std::collections::hash_map::Entry::or_insert_with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     name: web
+    ///     env:
+    ///       RUST_LOG: debug
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    ///
+    /// let replicas = root.get_or_insert_with("replicas", || 1u32);
+    /// assert_eq!(replicas.as_ref().as_u32(), Some(1));
+    /// let name = root.get_or_insert_with("name", || "default");
+    /// assert_eq!(name.as_ref().as_str(), Some("web"));
+    ///
+    /// root.get_or_insert_with("env", || None::<&str>).make_mapping().insert_str("HOME", "/root");
+    ///
+    /// let mut labels = root.get_or_insert_with("labels", || None::<&str>).make_mapping();
+    /// labels.insert_str("app", "web");
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     name: web
+    ///     env:
+    ///       RUST_LOG: debug
+    ///       HOME: /root
+    ///     replicas: 1
+    ///     labels:
+    ///       app: web
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn get_or_insert_with<F, V>(&mut self, key: &str, f: F) -> ValueMut<'_>
+    where
+        F: FnOnce() -> V,
+        V: Into<Scalar>,
+    {
+        let existing = self
+            .data
+            .mapping(self.id)
+            .items
+            .iter()
+            .map(|item| self.data.mapping_item(*item))
+            .find(|item| self.data.str(item.key.id) == key)
+            .map(|item| item.value);
 
-        None
+        let id = if let Some(id) = existing {
+            id
+        } else {
+            let value = f().into().into_raw(self.data);
+            self._insert(key.as_bytes(), Separator::Auto, value)
+        };
+
+        ValueMut::new(self.data, id)
     }
 
     /// Remove the given value from the mapping, returning a boolean indicating if
@@ -407,6 +493,17 @@ impl<'a> MappingMut<'a> {
     ///     string3: "I am a quoted string!"
     ///     "#
     /// );
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// assert!(root.remove("number1"));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     number2: 20
+    ///     string3: "I am a quoted string!"
+    ///     "#
+    /// );
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     pub fn remove(&mut self, key: &str) -> bool {
@@ -426,12 +523,286 @@ impl<'a> MappingMut<'a> {
         };
 
         let item = self.data.mapping_mut(self.id).items.remove(index);
+        let prefix = self.data.layout(item).prefix;
         self.data.drop(item);
+
+        // The first item is separated from what precedes the mapping
+        // differently from the rest, so the item which takes its place
+        // inherits its prefix.
+        if index == 0 {
+            if let Some(&first) = self.data.mapping(self.id).items.first() {
+                raw::inherit_first_prefix(self.data, first, prefix);
+            }
+        }
+
         true
     }
 
+    /// Rename the key `from` to `to`, keeping the value and the position of
+    /// the item in the mapping together with any surrounding comments.
+    ///
+    /// The new key keeps the quoting style of the old one where possible.
+    /// Returns [`RenameKey::Missing`] if `from` is missing, or
+    /// [`RenameKey::Collides`] if the rename would collide with an existing
+    /// key, and leaves the mapping untouched in either case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     name: web # the service name
+    ///     'image': nginx
+    ///     port: 80
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    ///
+    /// assert_eq!(root.rename_key("name", "service"), yaml::RenameKey::Renamed);
+    /// assert_eq!(root.rename_key("image", "container image"), yaml::RenameKey::Renamed);
+    /// assert_eq!(root.rename_key("port", "service"), yaml::RenameKey::Collides);
+    /// assert_eq!(root.rename_key("missing", "other"), yaml::RenameKey::Missing);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     service: web # the service name
+    ///     'container image': nginx
+    ///     port: 80
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn rename_key(&mut self, from: &str, to: &str) -> RenameKey {
+        let Some(item) = self
+            .data
+            .mapping(self.id)
+            .items
+            .iter()
+            .copied()
+            .find(|item| self.data.str(self.data.mapping_item(*item).key.id) == from)
+        else {
+            return RenameKey::Missing;
+        };
+
+        if self.as_ref().would_collide(from, to) {
+            return RenameKey::Collides;
+        }
+
+        let inline = raw::is_inline(self.data, self.id);
+        let quote = self.data.mapping_item(item).key.quote(self.data);
+        let kind = raw::key_kind_like(self.data, quote, to, inline);
+        let string = self.data.insert_str(to);
+
+        if let Raw::MappingItem(raw) = self.data.raw_mut(item) {
+            raw.key = raw::String::new(kind, string, string);
+        }
+
+        RenameKey::Renamed
+    }
+
+    /// Remove duplicate keys from the mapping, keeping either the first or the
+    /// last value associated with each key.
+    ///
+    /// Returns the number of items which were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// const INPUT: &str = r"
+    /// name: first
+    /// port: 80
+    /// name: second
+    /// port: 443
+    /// ";
+    ///
+    /// let mut doc = yaml::from_slice(INPUT)?;
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// assert_eq!(root.dedup_keys(yaml::DedupKeys::KeepFirst), 2);
+    /// assert_eq!(doc.to_string(), "\nname: first\nport: 80\n");
+    ///
+    /// let mut doc = yaml::from_slice(INPUT)?;
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// assert_eq!(root.dedup_keys(yaml::DedupKeys::KeepLast), 2);
+    /// assert_eq!(doc.to_string(), "\nname: second\nport: 443\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn dedup_keys(&mut self, keep: DedupKeys) -> usize {
+        let items = self.data.mapping(self.id).items.clone();
+
+        let keys = items
+            .iter()
+            .map(|item| self.data.mapping_item(*item).key.id)
+            .collect::<Vec<_>>();
+
+        let mut seen = HashSet::new();
+        let mut retain = vec![false; items.len()];
+
+        match keep {
+            DedupKeys::KeepFirst => {
+                for (index, key) in keys.iter().enumerate() {
+                    retain[index] = seen.insert(key);
+                }
+            }
+            DedupKeys::KeepLast => {
+                for (index, key) in keys.iter().enumerate().rev() {
+                    retain[index] = seen.insert(key);
+                }
+            }
+        }
+
+        // The first item is separated from what precedes the mapping
+        // differently from the rest, so its prefix is kept in place.
+        let first = items.first().map(|item| self.data.layout(*item).prefix);
+
+        let mut removed = 0usize;
+        let mut kept = Vec::with_capacity(items.len());
+
+        for (item, retain) in items.into_iter().zip(retain) {
+            if retain {
+                kept.push(item);
+            } else {
+                self.data.drop(item);
+                removed = removed.wrapping_add(1);
+            }
+        }
+
+        if let (Some(first), Some(&item)) = (first, kept.first()) {
+            self.data.set_prefix(item, first);
+        }
+
+        self.data.mapping_mut(self.id).items = kept;
+        removed
+    }
+
+    /// Stably sort the items of the mapping by their keys in byte order.
+    ///
+    /// Values and comments which trail an item on the same line move along
+    /// with their keys. Comments and blank lines on their own lines are part
+    /// of the layout of the mapping, so they stay in their positions. Nested
+    /// mappings are not sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     name: web # the name
+    ///     image: nginx
+    ///     env:
+    ///       B: 2
+    ///       A: 1 # first
+    ///     args: [b, a]
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.sort_keys();
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     args: [b, a]
+    ///     env:
+    ///       B: 2
+    ///       A: 1 # first
+    ///     image: nginx
+    ///     name: web # the name
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn sort_keys(&mut self) {
+        raw::sort_mapping_with_lines(self.data, self.id, Ord::cmp);
+    }
+
+    /// Stably sort the items of the mapping by their keys using the given
+    /// comparison function.
+    ///
+    /// See [`MappingMut::sort_keys`] for how the layout of the mapping is
+    /// treated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("{b: 2, C: 3, a: 1}")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.sort_keys_by(|a, b| a.to_ascii_lowercase().cmp(&b.to_ascii_lowercase()));
+    /// assert_eq!(doc.to_string(), "{a: 1, b: 2, C: 3}");
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.sort_keys_by(|a, b| b.cmp(a));
+    /// assert_eq!(doc.to_string(), "{b: 2, a: 1, C: 3}");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn sort_keys_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&BStr, &BStr) -> Ordering,
+    {
+        raw::sort_mapping_with_lines(self.data, self.id, |a, b| compare(a.as_bstr(), b.as_bstr()));
+    }
+
+    /// Set the whitespace padding just inside of the delimiters of an inline
+    /// mapping, like the spaces in `{ one: 1, two: 2 }`.
+    ///
+    /// See [`Mapping::padding`] for details. The leading padding is only used if
+    /// the mapping has any items. This does nothing if this is not an inline
+    /// mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("{ one: 1, two: 2 }")?;
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.set_padding("", "");
+    /// assert_eq!(doc.to_string(), "{one: 1, two: 2}");
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.set_padding(" ", " ");
+    /// assert_eq!(doc.to_string(), "{ one: 1, two: 2 }");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_padding(&mut self, leading: &str, trailing: &str) {
+        let raw = self.data.mapping(self.id);
+
+        if !matches!(raw.kind, raw::MappingKind::Inline { .. }) {
+            return;
+        }
+
+        if let Some(&item) = raw.items.first() {
+            let leading = self.data.insert_str(leading);
+            self.data.set_prefix(item, leading);
+        }
+
+        let trailing = self.data.insert_str(trailing);
+
+        if let raw::MappingKind::Inline { suffix, .. } = &mut self.data.mapping_mut(self.id).kind {
+            *suffix = trailing;
+        }
+    }
+
     /// Clear all the elements in a mapping.
     ///
+    /// An inline mapping is left as `{}`, while a block mapping is left without
+    /// any items which reads back as a null.
+    ///
     /// # Examples
     ///
     /// ```
@@ -466,10 +837,12 @@ impl<'a> MappingMut<'a> {
         self.data.mapping_mut(self.id).items = items;
     }
 
-    /// Insert a new null value and return a [`ValueMut`] to the newly inserted
-    /// value.
+    /// Insert a new null value and return an [`InsertedMut`] to the newly
+    /// inserted value.
     ///
-    /// This allows for setting a custom [`Separator`].
+    /// This allows for setting a custom [`Separator`], and the returned
+    /// [`InsertedMut`] can be used to further adjust how the inserted item is
+    /// formatted.
     ///
     /// # Examples
     ///
@@ -497,12 +870,75 @@ impl<'a> MappingMut<'a> {
     /// );
     /// # Ok::<_, anyhow::Error>(())
     /// ```
-    pub fn insert<K>(&mut self, key: K, separator: Separator<'_>) -> ValueMut<'_>
+    pub fn insert<K>(&mut self, key: K, separator: Separator<'_>) -> InsertedMut<'_>
     where
         K: AsRef<[u8]>,
     {
+        let len = self.data.mapping(self.id).items.len();
         let value = self._insert(key.as_ref(), separator, Raw::Null(raw::Null::Empty));
-        ValueMut::new(self.data, value)
+        let new = self.data.mapping(self.id).items.len() > len;
+        InsertedMut::new(ValueMut::new(self.data, value), new)
+    }
+
+    /// Insert a new null value preceeded by a comment and return a
+    /// [`ValueMut`] to the newly inserted value.
+    ///
+    /// The comment is added on its own line before the key, indented to match
+    /// it. Comments spanning multiple lines are split up into multiple
+    /// comment lines.
+    ///
+    /// Comments are only added to newly inserted keys in block mappings, if
+    /// the key already exists or the mapping is inline this behaves like
+    /// [`MappingMut::insert`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     server:
+    ///       host: localhost
+    ///     ",
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut server = root.get_mut("server").and_then(|v| v.into_mapping_mut()).context("missing server")?;
+    /// server.insert_with_comment("port", yaml::Separator::Auto, "The port to listen on.").set_u32(8080);
+    ///
+    /// let mut client = root.insert("client", yaml::Separator::Auto).make_mapping();
+    /// client.insert_with_comment("retries", yaml::Separator::Auto, "Number of retries.\nSet to 0 to disable.").set_u32(3);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     server:
+    ///       host: localhost
+    ///       ## The port to listen on.
+    ///       port: 8080
+    ///     client:
+    ///       ## Number of retries.
+    ///       ## Set to 0 to disable.
+    ///       retries: 3
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn insert_with_comment<K, C>(
+        &mut self,
+        key: K,
+        separator: Separator<'_>,
+        comment: C,
+    ) -> ValueMut<'_>
+    where
+        K: AsRef<[u8]>,
+        C: AsRef<str>,
+    {
+        self.insert(key, separator)
+            .with_comment(comment)
+            .into_value_mut()
     }
 
     /// Insert a string.
@@ -693,6 +1129,107 @@ impl<'a> MappingMut<'a> {
         self._insert(key.as_ref(), Separator::Auto, value);
     }
 
+    /// Insert a null value with the given spelling.
+    ///
+    /// An empty null is inserted without anything following the `:`
+    /// separator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     number1: 10
+    ///     number2: 20
+    ///     "
+    /// )?;
+    ///
+    /// let mut value = doc.as_mut().into_mapping_mut().context("not a mapping")?;
+    /// value.insert_null("keyword", yaml::Null::Keyword);
+    /// value.insert_null("tilde", yaml::Null::Tilde);
+    /// value.insert_null("empty", yaml::Null::Empty);
+    /// value.insert_null("number2", yaml::Null::Empty);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     number1: 10
+    ///     number2:
+    ///     keyword: null
+    ///     tilde: ~
+    ///     empty:
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn insert_null<K>(&mut self, key: K, kind: Null)
+    where
+        K: AsRef<[u8]>,
+    {
+        let value = match kind {
+            Null::Keyword => raw::Null::Keyword(self.data.insert_str("null")),
+            Null::Tilde => raw::Null::Tilde,
+            Null::Empty => raw::Null::Empty,
+        };
+
+        let id = self._insert(key.as_ref(), Separator::Auto, Raw::Null(value));
+
+        if let Null::Empty = kind {
+            let empty = self.data.insert_str("");
+            self.data.set_prefix(id, empty);
+        }
+    }
+
+    /// Insert every key and scalar value produced by the given iterator.
+    ///
+    /// Values can be of any type which converts into a [`Scalar`], and are
+    /// formatted the same way as when inserted individually. Keys which
+    /// already exist have their values replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     name: web
+    ///     replicas: 1
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.extend([("replicas", 3), ("port", 8080)]);
+    /// root.extend([("image", "nginx"), ("tag", "1.25")]);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     name: web
+    ///     replicas: 3
+    ///     port: 8080
+    ///     image: nginx
+    ///     tag: '1.25'
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn extend<I, K, V>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<[u8]>,
+        V: Into<Scalar>,
+    {
+        for (key, value) in iter {
+            let value = value.into().into_raw(self.data);
+            self._insert(key.as_ref(), Separator::Auto, value);
+        }
+    }
+
     insert_float!(insert_f32, f32, "32-bit float", 10.42, F32);
     insert_float!(insert_f64, f64, "64-bit float", 10.42, F64);
     insert_number!(insert_u8, u8, "8-bit unsigned integer", 42, U8);
@@ -706,3 +1243,26 @@ impl<'a> MappingMut<'a> {
     insert_number!(insert_u128, u128, "128-bit unsigned integer", 42, U128);
     insert_number!(insert_i128, i128, "128-bit signed integer", -42, I128);
 }
+
+/// Which value to keep for each key when removing duplicate keys through
+/// [`MappingMut::dedup_keys`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DedupKeys {
+    /// Keep the first value associated with each key.
+    KeepFirst,
+    /// Keep the last value associated with each key.
+    KeepLast,
+}
+
+/// The outcome of renaming a key through [`MappingMut::rename_key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RenameKey {
+    /// The key was renamed.
+    Renamed,
+    /// The key to rename is not present in the mapping.
+    Missing,
+    /// The new key is already present in the mapping.
+    Collides,
+}
@@ -29,10 +29,13 @@
 //! ```
 
 mod iter;
-pub use self::iter::Iter;
+pub use self::iter::{Iter, Keys, Values};
 
 mod mapping;
 pub use self::mapping::Mapping;
 
 mod mapping_mut;
-pub use self::mapping_mut::MappingMut;
+pub use self::mapping_mut::{DedupKeys, MappingMut, RenameKey};
+
+mod inserted_mut;
+pub use self::inserted_mut::InsertedMut;
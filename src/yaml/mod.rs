@@ -71,30 +71,44 @@ pub use self::data::Id;
 mod error;
 pub use self::error::{Error, ErrorKind};
 
+mod eq;
+
 mod document;
-pub use self::document::Document;
+pub use self::document::{Document, MergeStrategy};
+
+mod stream;
+pub use self::stream::Stream;
 
 mod raw;
 
 mod value;
-pub use self::value::{Block, Chomp, Null, Separator, StringKind, Value};
+pub use self::value::{Block, Chomp, CollectionStyle, Null, Separator, StringKind, Value};
 
 mod value_mut;
 pub use self::value_mut::ValueMut;
 
 mod number;
-pub use self::number::Number;
+pub use self::number::{Integer, Number, NumberStyle, TryFromIntegerError};
+
+mod timestamp;
+pub use self::timestamp::Timestamp;
 
 mod string;
 pub use self::string::String;
 
+mod scalar;
+pub use self::scalar::Scalar;
+
+mod options;
+pub use self::options::{IndentConfig, ParseOptions, PrettyOptions, SerializeOptions};
+
 pub mod sequence;
 #[doc(inline)]
 pub use self::sequence::{Sequence, SequenceMut};
 
 pub mod mapping;
 #[doc(inline)]
-pub use self::mapping::{Mapping, MappingMut};
+pub use self::mapping::{DedupKeys, InsertedMut, Mapping, MappingMut, RenameKey};
 
 #[cfg(feature = "serde")]
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
@@ -183,3 +197,180 @@ where
     let parser = Parser::new(input.as_ref());
     parser.parse()
 }
+
+/// Parse a YAML document from a string.
+///
+/// This is the same as [`from_slice`], and is also available through the
+/// [`FromStr`] implementation of [`Document`].
+///
+/// [`FromStr`]: std::str::FromStr
+///
+/// # Errors
+///
+/// Errors in case the document cannot be parsed as YAML.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_str("key: 1")?;
+/// assert!(doc.as_ref().as_mapping().is_some_and(|m| m.get("key").and_then(|v| v.as_u32()) == Some(1)));
+///
+/// let doc = "key: 1".parse::<yaml::Document>()?;
+/// assert_eq!(doc.to_string(), "key: 1");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn from_str(input: &str) -> Result<Document, Error> {
+    from_slice(input)
+}
+
+/// Parse a YAML document with the given options.
+///
+/// # Errors
+///
+/// Errors in case the document cannot be parsed as YAML.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice("[yes, no]")?;
+/// assert!(doc.as_ref().as_sequence().is_some_and(|s| s.iter().all(|v| v.as_bool().is_none())));
+///
+/// let options = yaml::ParseOptions::new().yaml_1_1_bools(true);
+/// let doc = yaml::from_slice_with("[yes, no]", &options)?;
+/// assert!(doc.as_ref().as_sequence().is_some_and(|s| s.iter().all(|v| v.as_bool().is_some())));
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn from_slice_with<D>(input: D, options: &ParseOptions) -> Result<Document, Error>
+where
+    D: AsRef<[u8]>,
+{
+    let parser = Parser::new(input.as_ref()).with_options(options);
+    parser.parse()
+}
+
+/// Parse a stream of YAML documents, separated by `---` markers.
+///
+/// Directives, document markers like `---` and `...`, and any comments
+/// between documents are preserved.
+///
+/// # Errors
+///
+/// Errors in case any document in the stream cannot be parsed as YAML.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// const INPUT: &str = r"
+/// ## First document.
+/// name: web
+/// ---
+/// ## Second document.
+/// name: db
+/// ...
+/// %YAML 1.2
+/// ---
+/// name: cache
+/// ";
+///
+/// let stream = yaml::from_slice_stream(INPUT)?;
+/// assert_eq!(stream.len(), 3);
+/// assert_eq!(stream.to_string(), INPUT);
+///
+/// // Multiple documents are rejected when parsing a single document.
+/// assert!(yaml::from_slice(INPUT).is_err());
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn from_slice_stream<D>(input: D) -> Result<Stream, Error>
+where
+    D: AsRef<[u8]>,
+{
+    let parser = Parser::new(input.as_ref());
+    Ok(Stream::new(parser.parse_stream()?))
+}
+
+/// Parse a stream of YAML documents with the given options.
+///
+/// # Errors
+///
+/// Errors in case any document in the stream cannot be parsed as YAML.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let options = yaml::ParseOptions::new().yaml_1_1_bools(true);
+/// let stream = yaml::from_slice_stream_with("yes\n---\noff\n", &options)?;
+///
+/// assert_eq!(stream.get(0).and_then(|doc| doc.as_ref().as_bool()), Some(true));
+/// assert_eq!(stream.get(1).and_then(|doc| doc.as_ref().as_bool()), Some(false));
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn from_slice_stream_with<D>(input: D, options: &ParseOptions) -> Result<Stream, Error>
+where
+    D: AsRef<[u8]>,
+{
+    let parser = Parser::new(input.as_ref()).with_options(options);
+    Ok(Stream::new(parser.parse_stream()?))
+}
+
+/// Parse a YAML document from latin-1 (ISO-8859-1) encoded bytes.
+///
+/// The input is decoded into UTF-8 before it is parsed, and the document
+/// remembers the encoding so that [`Document::write_to`] encodes it back into
+/// latin-1. Formatting the document through [`std::fmt::Display`] still
+/// produces UTF-8. Spans in errors refer to the decoded input.
+///
+/// This is a best-effort shim for working with legacy files which are known
+/// to be latin-1, not a general mechanism for detecting encodings. Since every
+/// byte is a valid latin-1 character, input in any other encoding is decoded
+/// incorrectly rather than rejected.
+///
+/// # Errors
+///
+/// Errors in case the document cannot be parsed as YAML.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut doc = yaml::from_slice_latin1(b"name: Andr\xe9\ncity: K\xf6ln\n")?;
+///
+/// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+/// assert_eq!(root.get("name").and_then(|v| v.as_str()), Some("André"));
+///
+/// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+/// root.insert_str("country", "Österreich");
+///
+/// let mut out = Vec::new();
+/// doc.write_to(&mut out)?;
+/// assert_eq!(&out[..], b"name: Andr\xe9\ncity: K\xf6ln\ncountry: \xd6sterreich\n");
+///
+/// // Characters outside of latin-1 can't be written.
+/// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+/// root.insert_str("currency", "€");
+/// assert!(doc.write_to(&mut Vec::new()).is_err());
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn from_slice_latin1<D>(input: D) -> Result<Document, Error>
+where
+    D: AsRef<[u8]>,
+{
+    let input = input
+        .as_ref()
+        .iter()
+        .map(|&b| char::from(b))
+        .collect::<std::string::String>();
+
+    let mut doc = from_slice(input)?;
+    doc.latin1 = true;
+    Ok(doc)
+}
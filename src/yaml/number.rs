@@ -9,6 +9,9 @@ macro_rules! as_number {
     ($name:ident, $ty:ty, $doc:literal, $lit:literal) => {
         #[doc = concat!("Try and get the value as a ", $doc, ".")]
         ///
+        /// Hexadecimal, octal, and binary integers prefixed with `0x`, `0o`,
+        /// and `0b` are supported, as are `_` digit separators.
+        ///
         /// # Examples
         ///
         /// ```
@@ -19,16 +22,210 @@ macro_rules! as_number {
         /// let value = doc.as_ref().into_number().context("expected a number")?;
         #[doc = concat!("let value = value.", stringify!($name), "();")]
         #[doc = concat!("assert_eq!(value, Some(", stringify!($lit), "));")]
+        ///
+        /// let doc = yaml::from_slice("0x2A")?;
+        #[doc = concat!("assert_eq!(doc.as_ref().", stringify!($name), "(), Some(42));")]
         /// # Ok::<_, anyhow::Error>(())
         /// ```
         #[must_use]
         pub fn $name(&self) -> Option<$ty> {
             let string = self.data.str(self.raw.string);
-            lexical_core::parse(string).ok()
+            raw::parse_integer(string)
+        }
+    };
+}
+
+macro_rules! as_float {
+    ($name:ident, $ty:ty, $doc:literal, $lit:literal) => {
+        #[doc = concat!("Try and get the value as a ", $doc, ".")]
+        ///
+        /// The special values `.inf`, `-.inf`, and `.nan` are supported.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use anyhow::Context;
+        /// use nondestructive::yaml;
+        ///
+        #[doc = concat!("let doc = yaml::from_slice(\"", stringify!($lit), "\")?;")]
+        /// let value = doc.as_ref().into_number().context("expected a number")?;
+        #[doc = concat!("let value = value.", stringify!($name), "();")]
+        #[doc = concat!("assert_eq!(value, Some(", stringify!($lit), "));")]
+        ///
+        /// let doc = yaml::from_slice("-.inf")?;
+        /// let value = doc.as_ref().into_number().context("expected a number")?;
+        #[doc = concat!("assert_eq!(value.", stringify!($name), "(), Some(", stringify!($ty), "::NEG_INFINITY));")]
+        /// # Ok::<_, anyhow::Error>(())
+        /// ```
+        #[must_use]
+        pub fn $name(&self) -> Option<$ty> {
+            let string = self.data.str(self.raw.string);
+
+            match raw::SpecialFloat::detect(string) {
+                Some(raw::SpecialFloat::Infinity) => Some(<$ty>::INFINITY),
+                Some(raw::SpecialFloat::NegInfinity) => Some(<$ty>::NEG_INFINITY),
+                Some(raw::SpecialFloat::NaN) => Some(<$ty>::NAN),
+                None => lexical_core::parse(string).ok(),
+            }
+        }
+    };
+}
+
+/// The notation a [`Number`] is written in.
+///
+/// See [`Number::style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum NumberStyle {
+    /// A decimal number like `42`, `1_000` or `3.14`, including floats such
+    /// as `.inf` and `.nan`.
+    Decimal,
+    /// A hexadecimal integer like `0xFF`.
+    Hexadecimal,
+    /// An octal integer like `0o755`.
+    Octal,
+    /// A binary integer like `0b1010`.
+    Binary,
+}
+
+/// An integer which is wide enough to hold any value of the primitive integer
+/// types, as returned by [`Value::as_integer`].
+///
+/// This is used to tell a value which isn't an integer apart from one which
+/// doesn't fit in the requested type. It can be converted into a primitive
+/// integer with [`TryFrom`], which fails with [`TryFromIntegerError`] if the
+/// integer is out of range.
+///
+/// [`Value::as_integer`]: crate::yaml::Value::as_integer
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice("300")?;
+/// let integer = doc.as_ref().as_integer().expect("expected an integer");
+///
+/// assert!(u8::try_from(integer).is_err());
+/// assert_eq!(u16::try_from(integer)?, 300);
+/// assert_eq!(doc.as_ref().as_u8(), None);
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Integer {
+    repr: IntegerRepr,
+}
+
+/// The representation of an [`Integer`], where negative integers always sort
+/// before non-negative ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum IntegerRepr {
+    Negative(i128),
+    NonNegative(u128),
+}
+
+impl Integer {
+    /// Parse an integer in any of the notations supported for numbers.
+    pub(crate) fn parse(string: &[u8]) -> Option<Self> {
+        // NB: negative zero like `-0` is normalized, so that it compares equal
+        // to zero.
+        let repr = match raw::parse_integer::<u128>(string) {
+            Some(n) => IntegerRepr::NonNegative(n),
+            None => match raw::parse_integer::<i128>(string)? {
+                n if n < 0 => IntegerRepr::Negative(n),
+                n => IntegerRepr::NonNegative(n.unsigned_abs()),
+            },
+        };
+
+        Some(Self { repr })
+    }
+
+    /// Test if the integer is negative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[-0x10, 0]")?;
+    /// let root = doc.as_ref().as_sequence().expect("expected a sequence");
+    ///
+    /// assert!(root.get(0).and_then(|v| v.as_integer()).is_some_and(|n| n.is_negative()));
+    /// assert!(root.get(1).and_then(|v| v.as_integer()).is_some_and(|n| !n.is_negative()));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        matches!(self.repr, IntegerRepr::Negative(..))
+    }
+}
+
+impl fmt::Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.repr {
+            IntegerRepr::Negative(n) => n.fmt(f),
+            IntegerRepr::NonNegative(n) => n.fmt(f),
         }
+    }
+}
+
+macro_rules! integer_try_from {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl TryFrom<Integer> for $ty {
+                type Error = TryFromIntegerError;
+
+                #[inline]
+                fn try_from(integer: Integer) -> Result<Self, Self::Error> {
+                    let value = match integer.repr {
+                        IntegerRepr::Negative(n) => <$ty>::try_from(n).ok(),
+                        IntegerRepr::NonNegative(n) => <$ty>::try_from(n).ok(),
+                    };
+
+                    value.ok_or(TryFromIntegerError { integer })
+                }
+            }
+        )*
     };
 }
 
+integer_try_from!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+/// The error raised when an [`Integer`] doesn't fit in the requested type.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice("-1")?;
+/// let integer = doc.as_ref().as_integer().expect("expected an integer");
+///
+/// let error = u32::try_from(integer).unwrap_err();
+/// assert_eq!(error.to_string(), "integer -1 is out of range");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TryFromIntegerError {
+    integer: Integer,
+}
+
+impl TryFromIntegerError {
+    /// Get the integer which was out of range.
+    #[must_use]
+    pub fn integer(&self) -> Integer {
+        self.integer
+    }
+}
+
+impl fmt::Display for TryFromIntegerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "integer {} is out of range", self.integer)
+    }
+}
+
+impl std::error::Error for TryFromIntegerError {}
+
 /// A YAML number.
 ///
 /// The value of the number can be accessed through the various `as_*` methods.
@@ -77,8 +274,72 @@ impl<'a> Number<'a> {
         self.data.str(self.raw.string)
     }
 
-    as_number!(as_f32, f32, "32-bit float", 10.42);
-    as_number!(as_f64, f64, "64-bit float", 10.42);
+    /// Try and get the number as an [`Integer`], which can hold any value of
+    /// the primitive integer types.
+    ///
+    /// Returns `None` if the number isn't an integer, such as `3.14`, or if it
+    /// doesn't fit in 128 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("0xFFFF")?;
+    /// let value = doc.as_ref().into_number().context("expected a number")?;
+    /// let integer = value.as_integer().context("expected an integer")?;
+    /// assert_eq!(u16::try_from(integer)?, u16::MAX);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_integer(&self) -> Option<Integer> {
+        Integer::parse(self.data.str(self.raw.string))
+    }
+
+    /// Get the notation the number is written in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[42, -0x2A, 0o52, 0b101010, 4.2e1]")?;
+    /// let root = doc.as_ref().as_sequence().context("expected a sequence")?;
+    ///
+    /// let styles = root
+    ///     .iter()
+    ///     .flat_map(|v| Some(v.into_number()?.style()))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     styles,
+    ///     [
+    ///         yaml::NumberStyle::Decimal,
+    ///         yaml::NumberStyle::Hexadecimal,
+    ///         yaml::NumberStyle::Octal,
+    ///         yaml::NumberStyle::Binary,
+    ///         yaml::NumberStyle::Decimal,
+    ///     ]
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn style(&self) -> NumberStyle {
+        let string = self.data.str(self.raw.string);
+        let digits = string.strip_prefix(b"-").unwrap_or(string);
+
+        match digits {
+            [b'0', b'x', ..] => NumberStyle::Hexadecimal,
+            [b'0', b'o', ..] => NumberStyle::Octal,
+            [b'0', b'b', ..] => NumberStyle::Binary,
+            _ => NumberStyle::Decimal,
+        }
+    }
+
+    as_float!(as_f32, f32, "32-bit float", 10.42);
+    as_float!(as_f64, f64, "64-bit float", 10.42);
     as_number!(as_u8, u8, "8-bit unsigned integer", 42);
     as_number!(as_i8, i8, "8-bit signed integer", -42);
     as_number!(as_u16, u16, "16-bit unsigned integer", 42);
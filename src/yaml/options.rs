@@ -0,0 +1,898 @@
+use std::fmt;
+use std::num::NonZeroUsize;
+
+use bstr::ByteSlice;
+#[cfg(feature = "serde-edits")]
+use serde::{Deserialize, Serialize};
+
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::StringKind;
+
+/// Indentation used for collections which are freshly created through edits,
+/// set with [`Document::set_indent`].
+///
+/// Only affects new collections, which are indented by the configured step
+/// relative to their parent. The indentation of existing content is left as
+/// it is. Defaults to two spaces.
+///
+/// [`Document::set_indent`]: crate::yaml::Document::set_indent
+///
+/// # Examples
+///
+/// ```
+/// use std::num::NonZeroUsize;
+///
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut doc = yaml::from_slice(
+///     r"
+///     name: web
+///     env:
+///       RUST_LOG: debug
+///     "
+/// )?;
+///
+/// let step = NonZeroUsize::new(4).context("zero step")?;
+/// doc.set_indent(yaml::IndentConfig::new().step(step));
+///
+/// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+/// let mut ports = root.insert("ports", yaml::Separator::Auto).make_sequence();
+/// ports.push_u32(80);
+/// ports.push_u32(443);
+///
+/// assert_eq!(
+///     doc.to_string(),
+///     r"
+///     name: web
+///     env:
+///       RUST_LOG: debug
+///     ports:
+///         - 80
+///         - 443
+///     "
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
+pub struct IndentConfig {
+    pub(crate) step: NonZeroUsize,
+}
+
+impl IndentConfig {
+    /// Construct the default indentation of two spaces.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of spaces a new collection is indented by relative to
+    /// its parent.
+    ///
+    /// The step can't be zero, since collections which are not indented
+    /// relative to their parent would instead become siblings of it.
+    #[must_use]
+    pub fn step(mut self, step: NonZeroUsize) -> Self {
+        self.step = step;
+        self
+    }
+}
+
+impl Default for IndentConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            step: NonZeroUsize::MIN.saturating_add(1),
+        }
+    }
+}
+
+/// Options used when parsing a document through [`from_slice_with`].
+///
+/// The default options parse documents according to YAML 1.2, the same way
+/// as [`from_slice`].
+///
+/// [`from_slice`]: crate::yaml::from_slice
+/// [`from_slice_with`]: crate::yaml::from_slice_with
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let options = yaml::ParseOptions::new().yaml_1_1_bools(true);
+/// let doc = yaml::from_slice_with("on", &options)?;
+/// assert_eq!(doc.as_ref().as_bool(), Some(true));
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ParseOptions {
+    pub(crate) yaml_1_1_bools: bool,
+}
+
+impl ParseOptions {
+    /// Construct the default parsing options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set if the YAML 1.1 spellings of booleans should be recognized, which
+    /// are `yes`, `no`, `on` and `off` in lowercase, capitalized or uppercase
+    /// form. Their spelling is preserved when the document is written back.
+    ///
+    /// The setting is remembered by the parsed document, so strings which are
+    /// spelled like YAML 1.1 booleans are quoted when they are set, and YAML
+    /// set through [`ValueMut::set_raw_yaml`] is parsed the same way.
+    ///
+    /// Defaults to `false`, in which case they are parsed as strings like in
+    /// YAML 1.2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// const INPUT: &str = r"
+    /// enabled: yes
+    /// debug: Off
+    /// answer: NO
+    /// strict: true
+    /// ";
+    ///
+    /// let doc = yaml::from_slice(INPUT)?;
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.get("enabled").and_then(|v| v.as_str()), Some("yes"));
+    ///
+    /// let options = yaml::ParseOptions::new().yaml_1_1_bools(true);
+    /// let doc = yaml::from_slice_with(INPUT, &options)?;
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// assert_eq!(root.get("enabled").and_then(|v| v.as_bool()), Some(true));
+    /// assert_eq!(root.get("debug").and_then(|v| v.as_bool()), Some(false));
+    /// assert_eq!(root.get("answer").and_then(|v| v.as_bool()), Some(false));
+    /// assert_eq!(root.get("strict").and_then(|v| v.as_bool()), Some(true));
+    /// assert_eq!(doc.to_string(), INPUT);
+    ///
+    /// let mut doc = yaml::from_slice_with("enabled: yes", &options)?;
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.insert_str("answer", "no");
+    /// assert_eq!(doc.to_string(), "enabled: yes\nanswer: 'no'");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    ///
+    /// [`ValueMut::set_raw_yaml`]: crate::yaml::ValueMut::set_raw_yaml
+    #[must_use]
+    pub fn yaml_1_1_bools(mut self, yaml_1_1_bools: bool) -> Self {
+        self.yaml_1_1_bools = yaml_1_1_bools;
+        self
+    }
+}
+
+/// Options used when serializing a document through
+/// [`Document::to_string_with`] or [`Document::write_to_with`].
+///
+/// The default options reproduce the document exactly as it is.
+///
+/// [`Document::to_string_with`]: crate::yaml::Document::to_string_with
+/// [`Document::write_to_with`]: crate::yaml::Document::write_to_with
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice("ports: [80, 443]\nhosts: [a, b, c, d]")?;
+///
+/// let options = yaml::SerializeOptions::new().max_inline_items(Some(2));
+///
+/// assert_eq!(
+///     doc.to_string_with(&options),
+///     "ports: [80, 443]\nhosts:\n  - a\n  - b\n  - c\n  - d"
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct SerializeOptions {
+    max_inline_items: Option<usize>,
+    escape_unicode: bool,
+    max_width: Option<usize>,
+}
+
+impl SerializeOptions {
+    /// Construct the default serialization options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of items an inline collection such as `[1, 2]`
+    /// or `{a: 1}` can have before it is emitted as a block collection
+    /// instead, with each item on its own line.
+    ///
+    /// Collections which don't exceed the threshold are left as they are, and
+    /// inline collections which are nested inside of other inline collections
+    /// are only converted if their parent is. Defaults to [`None`], which
+    /// means that inline collections are never converted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     matrix: {os: [linux, macos, windows], rust: [stable, nightly]}
+    ///     "
+    /// )?;
+    ///
+    /// let options = yaml::SerializeOptions::new().max_inline_items(Some(2));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string_with(&options),
+    ///     r"
+    ///     matrix: {os: [linux, macos, windows], rust: [stable, nightly]}
+    ///     "
+    /// );
+    ///
+    /// let options = yaml::SerializeOptions::new().max_inline_items(Some(1));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string_with(&options),
+    ///     r"
+    ///     matrix:
+    ///       os:
+    ///         - linux
+    ///         - macos
+    ///         - windows
+    ///       rust:
+    ///         - stable
+    ///         - nightly
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn max_inline_items(mut self, max_inline_items: Option<usize>) -> Self {
+        self.max_inline_items = max_inline_items;
+        self
+    }
+
+    /// Set if non-ASCII characters in double-quoted strings should be
+    /// escaped, so that the output only contains ASCII.
+    ///
+    /// Every character above U+007F is written with the shortest of the `\x`,
+    /// `\u` and `\U` escape sequences. This only affects strings which are
+    /// emitted by the crate, such as those which have been set or requoted.
+    /// Strings which are unchanged from the parsed document keep their
+    /// original text. Defaults to `false`, where characters are written as
+    /// they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("name: \"caf\u{e9}\"\nnote: '\u{1f600}'\ncity: \u{6771}\u{4eac}")?;
+    ///
+    /// let options = yaml::SerializeOptions::new().escape_unicode(true);
+    /// assert_eq!(doc.to_string_with(&options), doc.to_string());
+    ///
+    /// doc.normalize_quotes(yaml::StringKind::Double);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string_with(&options),
+    ///     r#"name: "caf\xe9"
+    /// note: "\U0001f600"
+    /// city: "\u6771\u4eac""#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn escape_unicode(mut self, escape_unicode: bool) -> Self {
+        self.escape_unicode = escape_unicode;
+        self
+    }
+
+    /// Set the maximum width of the lines of folded block scalars like `>`
+    /// which have been created or modified through edits, such as with
+    /// [`ValueMut::set_block`].
+    ///
+    /// The words of such scalars are re-wrapped to fit if any of their lines
+    /// are wider than this, counting their indentation. Words which are too
+    /// long to fit are put on their own lines. Folded scalars which were
+    /// parsed as part of the document and other kinds of values keep their
+    /// original text regardless of width. Defaults to [`None`], which means
+    /// that nothing is re-wrapped.
+    ///
+    /// [`ValueMut::set_block`]: crate::yaml::ValueMut::set_block
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     parsed: >
+    ///       this line is left as it is even though it is long
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// root.insert_block(
+    ///     "created",
+    ///     ["a folded string which was created programmatically"],
+    ///     yaml::Block::Folded(yaml::Chomp::Clip),
+    /// );
+    ///
+    /// let options = yaml::SerializeOptions::new().max_width(Some(24));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string_with(&options),
+    ///     r"
+    ///     parsed: >
+    ///       this line is left as it is even though it is long
+    ///     created: >
+    ///       a folded string
+    ///       which was created
+    ///       programmatically
+    ///     "
+    /// );
+    ///
+    /// // The content of the string is unchanged.
+    /// let doc = yaml::from_slice(doc.to_string_with(&options))?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    ///
+    /// assert_eq!(
+    ///     root.get("created").and_then(|v| v.as_str()),
+    ///     Some("a folded string which was created programmatically\n")
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn max_width(mut self, max_width: Option<usize>) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Test if non-ASCII characters in double-quoted strings should be
+    /// escaped.
+    pub(crate) fn escapes_unicode(&self) -> bool {
+        self.escape_unicode
+    }
+
+    /// Test if applying the options modifies the document, as opposed to only
+    /// affecting how it is written.
+    pub(crate) fn modifies(&self) -> bool {
+        self.max_inline_items.is_some() || self.max_width.is_some()
+    }
+
+    /// Apply the options to the value with the given identifier and everything
+    /// it contains.
+    pub(crate) fn apply(&self, data: &mut Data, id: Id) {
+        if let Some(max) = self.max_inline_items {
+            // NB: collections are visited outermost first, so that inline
+            // collections nested inside of converted ones can be converted in
+            // turn.
+            let values = data.walk(id).collect::<Vec<_>>();
+
+            for id in values {
+                let len = match data.raw(id) {
+                    Raw::Sequence(raw) => raw.items.len(),
+                    Raw::Mapping(raw) => raw.items.len(),
+                    _ => continue,
+                };
+
+                if len > max {
+                    raw::force_block(data, id);
+                }
+            }
+        }
+
+        if let Some(max_width) = self.max_width {
+            let values = data.walk(id).collect::<Vec<_>>();
+
+            for id in values {
+                raw::wrap_folded(data, id, max_width);
+            }
+        }
+    }
+}
+
+/// Options used when pretty-printing a document through
+/// [`Document::to_string_pretty`].
+///
+/// Every option is disabled by default, in which case the corresponding aspect
+/// of the document is left as it is. So the default options reproduce the
+/// document exactly.
+///
+/// Options are applied in the following order, where later options take the
+/// effect of earlier ones into account:
+/// 1. [`quote_style`][PrettyOptions::quote_style].
+/// 2. [`sort_keys`][PrettyOptions::sort_keys].
+/// 3. [`flow_spacing`][PrettyOptions::flow_spacing].
+/// 4. [`max_width`][PrettyOptions::max_width].
+/// 5. [`indent`][PrettyOptions::indent].
+///
+/// Some options interact in ways which are worth noting:
+/// * Comments and blank lines are part of the layout of a mapping, so when
+///   [`sort_keys`][PrettyOptions::sort_keys] is enabled they stay in their
+///   positions rather than following the keys they were written above.
+/// * Collections which are expanded because they exceed
+///   [`max_width`][PrettyOptions::max_width] use the indentation of the
+///   document's [`IndentConfig`] unless [`indent`][PrettyOptions::indent] is
+///   set. The width of a line is measured before it is re-indented, so a
+///   larger `indent` can still produce lines which are too wide.
+/// * An [`indent`][PrettyOptions::indent] smaller than two conflicts with
+///   mappings which start on the same line as the `-` of a sequence item,
+///   which are always indented two spaces past the `-`.
+///
+/// [`Document::to_string_pretty`]: crate::yaml::Document::to_string_pretty
+///
+/// # Examples
+///
+/// ```
+/// use std::num::NonZeroUsize;
+///
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice(
+///     r#"
+///     name: "web"
+///     ports: [80,443]
+///     env:
+///        RUST_LOG: 'debug'
+///        HOME: "/root"
+///     "#
+/// )?;
+///
+/// let options = yaml::PrettyOptions::new()
+///     .indent(NonZeroUsize::new(2).context("zero indent")?)
+///     .sort_keys(true)
+///     .flow_spacing(false)
+///     .quote_style(yaml::StringKind::Bare);
+///
+/// assert_eq!(
+///     doc.to_string_pretty(&options),
+///     r"
+///     env:
+///       HOME: /root
+///       RUST_LOG: debug
+///     name: web
+///     ports: [80, 443]
+///     "
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct PrettyOptions {
+    indent: Option<NonZeroUsize>,
+    sort_keys: bool,
+    flow_spacing: Option<bool>,
+    max_width: Option<usize>,
+    quote_style: Option<StringKind>,
+}
+
+impl PrettyOptions {
+    /// Construct the default pretty-printing options, which leave the document
+    /// as it is.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of spaces used to indent each level of a block
+    /// collection.
+    ///
+    /// As with [`IndentConfig::step`], this can't be zero since collections
+    /// which are not indented relative to their parent would instead become
+    /// siblings of it.
+    ///
+    /// Sequences written without indentation under a mapping key are kept that
+    /// way, and values which start on the same line as the `-` of a sequence
+    /// item are separated from it by a single space. This doesn't affect inline
+    /// collections, nor the indentation of the contents of block strings
+    /// relative to their key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    ///
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     spec:
+    ///       ## The containers to run.
+    ///       containers:
+    ///         - name: web
+    ///           args:
+    ///             - --port
+    ///     "
+    /// )?;
+    ///
+    /// let indent = NonZeroUsize::new(4).context("zero indent")?;
+    /// let options = yaml::PrettyOptions::new().indent(indent);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string_pretty(&options),
+    ///     r"
+    ///     spec:
+    ///         ## The containers to run.
+    ///         containers:
+    ///             - name: web
+    ///               args:
+    ///                   - --port
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn indent(mut self, indent: NonZeroUsize) -> Self {
+        self.indent = Some(indent);
+        self
+    }
+
+    /// Set if the keys of every mapping should be sorted in byte order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("b: 2\na: {d: 4, c: 3}")?;
+    ///
+    /// let options = yaml::PrettyOptions::new().sort_keys(true);
+    /// assert_eq!(doc.to_string_pretty(&options), "a: {c: 3, d: 4}\nb: 2");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Set if inline collections which fit on a single line should have
+    /// spaces inside of their brackets, like `[ 1, 2 ]` as opposed to
+    /// `[1, 2]`.
+    ///
+    /// Setting this also normalizes the spacing around the `,` separators to
+    /// a single space after each separator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("a: [1,2 ,3]\nb: {x: 1}\nc: []")?;
+    ///
+    /// let options = yaml::PrettyOptions::new().flow_spacing(true);
+    /// assert_eq!(doc.to_string_pretty(&options), "a: [ 1, 2, 3 ]\nb: { x: 1 }\nc: []");
+    ///
+    /// let options = yaml::PrettyOptions::new().flow_spacing(false);
+    /// assert_eq!(doc.to_string_pretty(&options), "a: [1, 2, 3]\nb: {x: 1}\nc: []");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn flow_spacing(mut self, flow_spacing: bool) -> Self {
+        self.flow_spacing = Some(flow_spacing);
+        self
+    }
+
+    /// Set the maximum width of a line, in characters.
+    ///
+    /// Inline collections on lines which are wider than this are expanded into
+    /// block collections. Other lines such as those containing long strings are
+    /// left as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("short: [a, b]\nlong: [alpha, beta, gamma]")?;
+    ///
+    /// let options = yaml::PrettyOptions::new().max_width(20);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string_pretty(&options),
+    ///     "short: [a, b]\nlong:\n  - alpha\n  - beta\n  - gamma"
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Set the preferred style of quoting for string values.
+    ///
+    /// With [`StringKind::Bare`], quotes are removed from strings which can be
+    /// written without them, while bare strings are left as they are. With
+    /// [`StringKind::Single`] or [`StringKind::Double`] every string is quoted
+    /// accordingly, except that strings which contain control characters are
+    /// always double-quoted. Keys and block strings are never affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("a: 'hello'\nb: \"it's\"\nc: '42'\nd: bare")?;
+    ///
+    /// let options = yaml::PrettyOptions::new().quote_style(yaml::StringKind::Bare);
+    /// assert_eq!(doc.to_string_pretty(&options), "a: hello\nb: \"it's\"\nc: '42'\nd: bare");
+    ///
+    /// let options = yaml::PrettyOptions::new().quote_style(yaml::StringKind::Double);
+    /// assert_eq!(doc.to_string_pretty(&options), "a: \"hello\"\nb: \"it's\"\nc: \"42\"\nd: \"bare\"");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn quote_style(mut self, quote_style: StringKind) -> Self {
+        self.quote_style = Some(quote_style);
+        self
+    }
+
+    /// Apply the options to the value with the given identifier and everything
+    /// it contains.
+    pub(crate) fn apply(&self, data: &mut Data, id: Id) {
+        if let Some(style) = self.quote_style {
+            quote_strings(data, id, style);
+        }
+
+        if self.sort_keys {
+            let values = data.walk(id).collect::<Vec<_>>();
+
+            for id in values {
+                if matches!(data.raw(id), Raw::Mapping(..)) {
+                    raw::sort_mapping_with_lines(data, id, Ord::cmp);
+                }
+            }
+        }
+
+        if let Some(spacing) = self.flow_spacing {
+            space_inline(data, id, spacing);
+        }
+
+        if let Some(max_width) = self.max_width {
+            // NB: collections are visited outermost first, so that once a
+            // collection is expanded the collections it contains are measured
+            // on their new lines.
+            let values = data.walk(id).collect::<Vec<_>>();
+
+            for id in values {
+                if raw::is_inline(data, id) && line_width(data, id) > max_width {
+                    raw::force_block(data, id);
+                }
+            }
+        }
+
+        if let Some(indent) = self.indent {
+            reindent(data, id, indent.get());
+        }
+    }
+}
+
+/// Re-quote all string values according to the given style, as documented in
+/// [`PrettyOptions::quote_style`].
+pub(crate) fn quote_strings(data: &mut Data, id: Id, style: StringKind) {
+    let values = data.walk(id).collect::<Vec<_>>();
+
+    for id in values {
+        let Raw::String(raw) = data.raw(id) else {
+            continue;
+        };
+
+        if matches!(raw.kind, raw::RawStringKind::Multiline) {
+            continue;
+        }
+
+        let is_bare = raw.quote(data).is_none();
+        let string = raw.id;
+
+        let Ok(value) = data.str(string).to_str() else {
+            continue;
+        };
+
+        let kind = match style {
+            StringKind::Bare if is_bare => continue,
+            StringKind::Bare
+                if raw::requires_quoting(data, value, raw::is_inline_item(data, id)) =>
+            {
+                continue
+            }
+            StringKind::Bare => raw::RawStringKind::Bare,
+            StringKind::Single if !raw::has_control(value.as_bytes()) => raw::RawStringKind::Single,
+            StringKind::Single | StringKind::Double => raw::RawStringKind::Double,
+        };
+
+        if let Raw::String(raw) = data.raw_mut(id) {
+            *raw = raw::String::new(kind, string, string);
+        }
+    }
+}
+
+/// Normalize spacing inside of inline collections which fit on one line.
+fn space_inline(data: &mut Data, id: Id, spacing: bool) {
+    let values = data.walk(id).collect::<Vec<_>>();
+
+    let empty = data.insert_str("");
+    let space = data.insert_str(" ");
+    let edge = if spacing { space } else { empty };
+
+    for id in values {
+        let (items, suffix) = match data.raw(id) {
+            Raw::Sequence(raw::Sequence {
+                kind: raw::SequenceKind::Inline { suffix, .. },
+                items,
+                ..
+            })
+            | Raw::Mapping(raw::Mapping {
+                kind: raw::MappingKind::Inline { suffix, .. },
+                items,
+                ..
+            }) => (items.clone(), *suffix),
+            _ => continue,
+        };
+
+        let multiline = data.str(suffix).contains(&raw::NEWLINE)
+            || items.iter().any(|item| {
+                let suffix = match data.raw(*item) {
+                    Raw::SequenceItem(raw) => raw.suffix,
+                    Raw::MappingItem(raw) => raw.suffix,
+                    _ => None,
+                };
+
+                data.prefix(*item).contains(&raw::NEWLINE)
+                    || suffix.is_some_and(|s| data.str(s).contains(&raw::NEWLINE))
+            });
+
+        if multiline {
+            continue;
+        }
+
+        for (index, item) in items.iter().enumerate() {
+            data.set_prefix(*item, if index == 0 { edge } else { space });
+
+            match data.raw_mut(*item) {
+                Raw::SequenceItem(raw) => raw.suffix = None,
+                Raw::MappingItem(raw) => raw.suffix = None,
+                _ => {}
+            }
+        }
+
+        let suffix = if items.is_empty() { empty } else { edge };
+
+        match data.raw_mut(id) {
+            Raw::Sequence(raw::Sequence {
+                kind: raw::SequenceKind::Inline { suffix: s, .. },
+                ..
+            })
+            | Raw::Mapping(raw::Mapping {
+                kind: raw::MappingKind::Inline { suffix: s, .. },
+                ..
+            }) => *s = suffix,
+            _ => {}
+        }
+    }
+}
+
+/// Compute the width of the widest line which the value with the given
+/// identifier is written on.
+fn line_width(data: &Data, id: Id) -> usize {
+    let (start, shown) = if let Some(item) = data.layout(id).parent {
+        let start = match data.layout(item).parent.map(|id| data.raw(id)) {
+            Some(Raw::Mapping(raw)) => raw.indent,
+            // Account for the `-` indicator.
+            Some(Raw::Sequence(raw)) => raw.indent.saturating_add(1),
+            _ => 0,
+        };
+
+        (start, item)
+    } else {
+        let prefix = data.prefix(id);
+        let line = prefix
+            .rsplit(|b| *b == raw::NEWLINE)
+            .next()
+            .unwrap_or_default();
+        (line.chars().count(), id)
+    };
+
+    let rendered = Render { data, id: shown }.to_string();
+    let mut lines = rendered.split('\n');
+
+    let first = lines
+        .next()
+        .map(|line| start.saturating_add(line.chars().count()))
+        .unwrap_or_default();
+
+    lines
+        .map(|line| line.chars().count())
+        .fold(first, usize::max)
+}
+
+/// Re-indent block collections so that each level is indented by `indent`
+/// spaces.
+fn reindent(data: &mut Data, id: Id, indent: usize) {
+    let values = data.walk(id).collect::<Vec<_>>();
+
+    let space = data.insert_str(" ");
+
+    for id in values {
+        let current = match data.raw(id) {
+            Raw::Sequence(raw) if !raw::is_inline(data, id) => {
+                // Values which start on the same line as the `-` indicator are
+                // separated from it by a single space.
+                for item in raw.items.clone() {
+                    let value = data.sequence_item(item).value;
+                    let prefix = data.prefix(value);
+
+                    if !prefix.is_empty() && !prefix.contains(&raw::NEWLINE) {
+                        data.set_prefix(value, space);
+                    }
+                }
+
+                data.sequence(id).indent
+            }
+            Raw::Mapping(raw) if !raw::is_inline(data, id) => raw.indent,
+            _ => continue,
+        };
+
+        let Some(item) = data.layout(id).parent else {
+            continue;
+        };
+
+        let same_line = !data.prefix(id).contains(&raw::NEWLINE);
+
+        // NB: containers are visited before the values they contain, so the
+        // indentation of the container has already been adjusted.
+        let target = match data.layout(item).parent.map(|id| data.raw(id)) {
+            Some(Raw::Mapping(raw)) if !same_line => {
+                // Sequences which are not indented under their key are kept
+                // that way.
+                if current == raw.indent {
+                    raw.indent
+                } else {
+                    raw.indent.saturating_add(indent)
+                }
+            }
+            Some(Raw::Sequence(raw)) if same_line => raw.indent.saturating_add(2),
+            Some(Raw::Sequence(raw)) => raw.indent.saturating_add(indent),
+            _ => continue,
+        };
+
+        let (Ok(target), Ok(current)) = (isize::try_from(target), isize::try_from(current)) else {
+            continue;
+        };
+
+        let extra = target.saturating_sub(current);
+
+        if extra != 0 {
+            data.indent_by(id, extra);
+            data.indent_prefix(id, extra);
+        }
+    }
+}
+
+/// Helper to render a value without its prefix.
+struct Render<'a> {
+    data: &'a Data,
+    id: Id,
+}
+
+impl fmt::Display for Render<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.data
+            .raw(self.id)
+            .write_to(self.data, &mut raw::Fmt(f), None)
+    }
+}
@@ -1,12 +1,13 @@
 use std::array;
+use std::mem;
 
 use bstr::ByteSlice;
 
 use crate::yaml::data::{Data, Id, StringId};
 use crate::yaml::error::{Error, ErrorKind};
 use crate::yaml::raw::{self, Raw};
-use crate::yaml::serde_hint;
-use crate::yaml::Document;
+use crate::yaml::serde_hint::{self, RawNumberHint};
+use crate::yaml::{Chomp, Document, ParseOptions};
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -32,12 +33,14 @@ macro_rules! ws {
     };
 }
 
+#[derive(Clone, Copy)]
 struct State {
     prefix: StringId,
     parent: Option<Id>,
     inline: bool,
     tabular: bool,
     parent_indent: Option<usize>,
+    sequence_indent: Option<usize>,
 }
 
 impl State {
@@ -49,9 +52,15 @@ impl State {
             inline: false,
             tabular: false,
             parent_indent: None,
+            sequence_indent: None,
         }
     }
 
+    #[inline]
+    fn with_prefix(&self, prefix: StringId) -> Self {
+        Self { prefix, ..*self }
+    }
+
     #[inline]
     fn with_parent(self, parent: Id) -> Self {
         Self {
@@ -83,6 +92,14 @@ impl State {
             ..self
         }
     }
+
+    #[inline]
+    fn with_sequence_indent(self, indent: usize) -> Self {
+        Self {
+            sequence_indent: Some(indent),
+            ..self
+        }
+    }
 }
 
 /// A YAML parser.
@@ -92,6 +109,10 @@ pub struct Parser<'a> {
     data: Data,
     input: &'a [u8],
     n: usize,
+    options: ParseOptions,
+    /// The offsets at which each line of the input starts, or `None` if spans
+    /// shouldn't be recorded.
+    lines: Option<Vec<usize>>,
 }
 
 impl<'a> Parser<'a> {
@@ -102,53 +123,193 @@ impl<'a> Parser<'a> {
             data: Data::default(),
             input,
             n: 0,
+            options: ParseOptions::default(),
+            lines: Some(lines(input)),
+        }
+    }
+
+    /// Set the options to parse with.
+    pub(crate) fn with_options(self, options: &ParseOptions) -> Self {
+        Self {
+            options: options.clone(),
+            ..self
         }
     }
 
+    /// Parse a fragment of YAML into existing data, returning the identifier
+    /// of the parsed value.
+    ///
+    /// The fragment is parsed as if it's indented at column zero, and is
+    /// inserted without a parent. Since the fragment isn't a part of the input
+    /// of the document, no spans are recorded.
+    ///
+    /// The fragment is parsed separately and only imported into `data` once it
+    /// has been parsed successfully, so an error leaves `data` untouched. It
+    /// is parsed with the same options as the document `data` belongs to.
+    pub(crate) fn parse_fragment(data: &mut Data, input: &'a [u8]) -> Result<Id> {
+        let mut parser = Self {
+            scratch: Vec::new(),
+            data: Data::default(),
+            input,
+            n: 0,
+            options: ParseOptions::default().yaml_1_1_bools(data.yaml_1_1_bools()),
+            lines: None,
+        };
+
+        let value = parser.fragment()?;
+        Ok(data.import_tree(&parser.data, value, None))
+    }
+
+    fn fragment(&mut self) -> Result<Id> {
+        let prefix = self.ws();
+        let (value, _) = self.value(&State::new(prefix).with_tabular())?;
+        self.ws();
+
+        if !self.is_eof() {
+            return Err(Error::new(
+                self.input,
+                self.n..self.input.len(),
+                ErrorKind::ExpectedEof,
+            ));
+        }
+
+        Ok(value)
+    }
+
     /// Parses a single value, and returns its kind.
     pub(crate) fn parse(mut self) -> Result<Document> {
+        let (document, _) = self.document()?;
+
+        if !self.is_eof() {
+            return Err(Error::new(
+                self.input,
+                self.n..self.input.len(),
+                ErrorKind::ExpectedEof,
+            ));
+        }
+
+        Ok(document)
+    }
+
+    /// Parse a stream of documents.
+    ///
+    /// Every document after the first has to start with a `---` marker, unless
+    /// the document before it was ended with an explicit `...` marker.
+    pub(crate) fn parse_stream(mut self) -> Result<Vec<Document>> {
+        let mut documents = Vec::new();
+
+        loop {
+            let (document, ended) = self.document()?;
+            documents.push(document);
+
+            if self.is_eof() {
+                break;
+            }
+
+            if !ended && !self.is_document_marker(b'-') {
+                return Err(Error::new(
+                    self.input,
+                    self.n..self.input.len(),
+                    ErrorKind::ExpectedEof,
+                ));
+            }
+        }
+
+        Ok(documents)
+    }
+
+    /// Parse a single document, returning it and whether it was ended with an
+    /// explicit `...` marker.
+    fn document(&mut self) -> Result<(Document, bool)> {
+        let start = self.n;
         let prefix = self.start_of_document();
 
-        let (root, suffix) = self.value(&State::new(prefix).with_tabular())?;
+        // An empty document, which is immediately followed by another one.
+        let (root, suffix) = if self.is_document_marker(b'-') || self.is_document_marker(b'.') {
+            let id = self.data.insert(Raw::Null(raw::Null::Empty), prefix, None);
+            (id, None)
+        } else {
+            self.value(&State::new(prefix).with_tabular())?
+        };
 
-        let suffix = match suffix {
+        let mut suffix = match suffix {
             Some(suffix) => suffix,
             None => self.ws(),
         };
 
-        if !self.is_eof() {
-            return Err(Error::new(self.n..self.input.len(), ErrorKind::ExpectedEof));
+        // Process end-of-document.
+        let ended = self.is_document_marker(b'.');
+
+        if ended {
+            let start = self.span_back(suffix);
+            self.bump(3);
+            self.ws();
+            suffix = self.data.insert_str(self.string(start));
+        }
+
+        // NB: only the lines spanned by the document are stored with it, so
+        // that a stream of documents doesn't copy every line into each one.
+        if let Some(lines) = &self.lines {
+            let first = lines.partition_point(|n| *n <= start).saturating_sub(1);
+            let last = lines.partition_point(|n| *n <= self.n);
+            let spanned = lines.get(first..last).unwrap_or_default();
+            self.data.set_lines(spanned.to_vec(), first);
         }
 
-        Ok(Document::new(suffix, root, self.data))
+        let mut data = mem::take(&mut self.data);
+        data.set_yaml_1_1_bools(self.options.yaml_1_1_bools);
+        Ok((Document::new(suffix, root, data), ended))
     }
 
     /// Process document delimiter.
     ///
     /// This is a `---` that is allowed to exist at the beginning of the document.
+    /// Any directives and the delimiter itself are retained as part of the
+    /// prefix of the root value.
     fn start_of_document(&mut self) -> StringId {
-        let mut prefix = self.ws();
+        let start = self.n;
+        self.ws();
 
-        loop {
-            match self.peek() {
-                // Process headers.
-                [b'%', _, _] => {
-                    self.find(raw::NEWLINE);
-                    prefix = self.ws();
-                }
-                // Process start-of-document.
-                [b'-', b'-', b'-'] => {
-                    self.bump(3);
-                    prefix = self.ws();
-                    break;
-                }
-                _ => {
-                    break;
-                }
+        // Directives are only recognized in the prologue of a document, which
+        // means that they have to be followed by a `---` marker. Otherwise the
+        // `%` is the start of a plain scalar, like `%Y-%m-%d`.
+        if self.is_directive() {
+            let content = self.n;
+
+            while self.is_directive() {
+                self.find(raw::NEWLINE);
+                self.ws();
+            }
+
+            if !self.is_document_marker(b'-') {
+                self.n = content;
             }
         }
 
-        prefix
+        if self.is_document_marker(b'-') {
+            self.bump(3);
+            self.ws();
+        }
+
+        self.data.insert_str(self.string(start))
+    }
+
+    /// Test if the cursor is at a directive, like `%YAML 1.2`.
+    fn is_directive(&self) -> bool {
+        let at_line_start =
+            self.n == 0 || matches!(self.input.get(self.n.wrapping_sub(1)), Some(&raw::NEWLINE));
+
+        at_line_start && self.peek1() == b'%'
+    }
+
+    /// Test if the cursor is at a document marker, like `---` or `...`,
+    /// consisting of the given character.
+    fn is_document_marker(&self, c: u8) -> bool {
+        let at_line_start =
+            self.n == 0 || matches!(self.input.get(self.n.wrapping_sub(1)), Some(&raw::NEWLINE));
+
+        at_line_start
+            && matches!(self.peek::<4>(), [a, b, c2, ws!(EOF)] if a == c && b == c && c2 == c)
     }
 
     /// Test if eof.
@@ -200,17 +361,28 @@ impl<'a> Parser<'a> {
         }
     }
 
-    /// Find the given character.
-    fn find2(&mut self, a: u8, b: u8) {
+    /// Find any of the given characters.
+    fn find3(&mut self, a: u8, b: u8, c: u8) {
         let input = self.input.get(self.n..).unwrap_or_default();
 
-        if let Some(n) = memchr::memchr2(a, b, input) {
+        if let Some(n) = memchr::memchr3(a, b, c, input) {
             self.bump(n);
         } else {
             self.n = self.input.len();
         }
     }
 
+    /// Test if the cursor is at the start of a comment, which is a `#` that is
+    /// preceeded by whitespace. A `#` which is not, like in `http://x#frag`, is
+    /// part of the scalar it appears in.
+    fn is_comment(&self) -> bool {
+        self.peek1() == b'#'
+            && matches!(
+                self.input.get(self.n.wrapping_sub(1)).copied(),
+                Some(ws!()) | None
+            )
+    }
+
     /// Consume whitespace.
     fn ws_nl(&mut self) -> (StringId, u32) {
         let start = self.n;
@@ -237,7 +409,8 @@ impl<'a> Parser<'a> {
         self.ws_nl().0
     }
 
-    /// Test if current position contains nothing but whitespace until we reach a line end.
+    /// Test if current position contains nothing but whitespace until we reach
+    /// a line end or a comment.
     fn is_eol(&self) -> bool {
         let mut n = self.n;
 
@@ -246,6 +419,9 @@ impl<'a> Parser<'a> {
                 raw::NEWLINE => {
                     return true;
                 }
+                b'#' if n > self.n => {
+                    return true;
+                }
                 other_ws!() => {
                     n = n.wrapping_add(1);
                 }
@@ -260,6 +436,23 @@ impl<'a> Parser<'a> {
 
     /// Consume a single number.
     fn number(&mut self, s: &State, start: usize) -> Option<Raw> {
+        let rest = self.input.get(start..).unwrap_or_default();
+
+        if let Some((spelling, _)) = raw::SpecialFloat::SPELLINGS
+            .iter()
+            .find(|(spelling, _)| rest.starts_with(spelling))
+        {
+            self.bump(spelling.len());
+
+            if !self.is_number_end(s) {
+                self.n = start;
+                return None;
+            }
+
+            let string = self.data.insert_str(self.string(start));
+            return Some(Raw::Number(raw::Number::new(string, serde_hint::F64)));
+        }
+
         let mut hint = serde_hint::U64;
 
         if matches!(self.peek1(), b'-') {
@@ -267,24 +460,35 @@ impl<'a> Parser<'a> {
             self.bump(1);
         }
 
+        if let [b'0', b'x' | b'o' | b'b'] = self.peek() {
+            return self.radix_number(s, start, hint);
+        }
+
         let mut wants_dot = true;
         let mut wants_e = true;
         let mut has_number = false;
+        let mut has_underscore = false;
+        let mut trailing_underscore = false;
         let mut any = false;
 
         loop {
             match self.peek1() {
-                b'.' if wants_dot => {
+                b'_' if has_number && wants_dot && !trailing_underscore => {
+                    has_underscore = true;
+                    trailing_underscore = true;
+                }
+                b'.' if wants_dot && !has_underscore => {
                     hint = serde_hint::F64;
                     wants_dot = false;
                 }
-                b'e' | b'E' if has_number && wants_e => {
+                b'e' | b'E' if has_number && wants_e && !has_underscore => {
                     hint = serde_hint::F64;
                     wants_dot = false;
                     wants_e = false;
                 }
                 b'0'..=b'9' => {
                     has_number = true;
+                    trailing_underscore = false;
                 }
                 _ => {
                     break;
@@ -299,7 +503,44 @@ impl<'a> Parser<'a> {
             return None;
         }
 
-        if s.tabular && !self.is_eol() {
+        if !has_number || trailing_underscore || !self.is_number_end(s) {
+            self.n = start;
+            return None;
+        }
+
+        let string = self.data.insert_str(self.string(start));
+        Some(Raw::Number(raw::Number::new(string, hint)))
+    }
+
+    /// Consume an integer with a `0x`, `0o`, or `0b` radix prefix.
+    fn radix_number(&mut self, s: &State, start: usize, hint: RawNumberHint) -> Option<Raw> {
+        let [_, prefix] = self.peek();
+        self.bump(2);
+
+        let mut has_number = false;
+        let mut trailing_underscore = false;
+
+        loop {
+            match (prefix, self.peek1()) {
+                (b'x', b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F')
+                | (b'o', b'0'..=b'7')
+                | (b'b', b'0' | b'1') => {
+                    has_number = true;
+                    trailing_underscore = false;
+                }
+                (_, b'_') if has_number && !trailing_underscore => {
+                    trailing_underscore = true;
+                }
+                _ => {
+                    break;
+                }
+            }
+
+            self.bump(1);
+        }
+
+        if !has_number || trailing_underscore || !self.is_number_end(s) {
+            self.n = start;
             return None;
         }
 
@@ -307,6 +548,19 @@ impl<'a> Parser<'a> {
         Some(Raw::Number(raw::Number::new(string, hint)))
     }
 
+    /// Test if the cursor is at a position where a number can end.
+    fn is_number_end(&self, s: &State) -> bool {
+        if s.tabular {
+            return self.is_eol();
+        }
+
+        if s.inline {
+            return matches!(self.peek1(), ws!(b',' | b':' | b']' | b'}' | EOF));
+        }
+
+        true
+    }
+
     /// Insert a null value as a placeholder.
     fn placeholder(&mut self, prefix: StringId, parent: Option<Id>) -> Id {
         self.data
@@ -314,12 +568,16 @@ impl<'a> Parser<'a> {
     }
 
     /// Read a double-quoted string.
-    fn single_quoted(&mut self) -> raw::String {
+    fn single_quoted(&mut self) -> Result<raw::String> {
         let original = self.n;
         self.bump(1);
         let start = self.n;
 
         loop {
+            if self.is_eof() {
+                return Err(self.unterminated(original));
+            }
+
             match self.peek() {
                 [b'\'', b'\''] => {
                     return self.single_quoted_escaped(start, original);
@@ -334,17 +592,26 @@ impl<'a> Parser<'a> {
         }
 
         let string = self.data.insert_str(self.string(start));
-        self.bump(usize::from(!self.is_eof()));
+        self.bump(1);
         let original = self.data.insert_str(self.string(original));
 
-        raw::String::new(raw::RawStringKind::Original, string, original)
+        Ok(raw::String::new(
+            raw::RawStringKind::Original,
+            string,
+            original,
+        ))
     }
 
     /// Read a single-quoted escaped string.
-    fn single_quoted_escaped(&mut self, start: usize, original: usize) -> raw::String {
+    fn single_quoted_escaped(&mut self, start: usize, original: usize) -> Result<raw::String> {
         self.scratch.extend(self.string(start));
 
         loop {
+            if self.is_eof() {
+                self.scratch.clear();
+                return Err(self.unterminated(original));
+            }
+
             match self.peek() {
                 [b'\'', b'\''] => {
                     self.bump(2);
@@ -366,7 +633,17 @@ impl<'a> Parser<'a> {
 
         let original = self.data.insert_str(self.string(original));
 
-        raw::String::new(raw::RawStringKind::Original, string, original)
+        Ok(raw::String::new(
+            raw::RawStringKind::Original,
+            string,
+            original,
+        ))
+    }
+
+    /// Construct an error for a quoted string starting at `start` which is
+    /// missing its closing quote.
+    fn unterminated(&self, start: usize) -> Error {
+        Error::new(self.input, start..self.n, ErrorKind::UnterminatedString)
     }
 
     /// Read a double-quoted string.
@@ -431,18 +708,27 @@ impl<'a> Parser<'a> {
     }
 
     /// Unescape into the scratch buffer.
+    ///
+    /// See <https://yaml.org/spec/1.2.2/#escaped-characters>.
     fn unescape(&mut self, start: usize) -> Result<()> {
-        let b = match self.peek1() {
-            b'n' => raw::NEWLINE,
-            b'0' => b'\x00',
-            b'a' => b'\x07',
-            b'b' => b'\x08',
-            b't' => b'\x09',
-            b'v' => b'\x0b',
-            b'f' => b'\x0c',
-            b'r' => b'\r',
-            b'e' => b'\x1b',
-            b'\\' => b'\"',
+        let c = match self.peek1() {
+            b'n' => char::from(raw::NEWLINE),
+            b'0' => '\x00',
+            b'a' => '\x07',
+            b'b' => '\x08',
+            b't' | b'\t' => '\x09',
+            b'v' => '\x0b',
+            b'f' => '\x0c',
+            b'r' => '\r',
+            b'e' => '\x1b',
+            b' ' => ' ',
+            b'"' => '"',
+            b'/' => '/',
+            b'\\' => '\\',
+            b'N' => '\u{85}',
+            b'_' => '\u{a0}',
+            b'L' => '\u{2028}',
+            b'P' => '\u{2029}',
             b'x' => {
                 self.bump(1);
                 return self.unescape_unicode(start, 2, ErrorKind::BadHexEscape);
@@ -451,13 +737,17 @@ impl<'a> Parser<'a> {
                 self.bump(1);
                 return self.unescape_unicode(start, 4, ErrorKind::BadUnicodeEscape);
             }
+            b'U' => {
+                self.bump(1);
+                return self.unescape_unicode(start, 8, ErrorKind::BadUnicodeEscape);
+            }
             _ => {
                 self.bump(1);
-                return Err(Error::new(start..self.n, ErrorKind::BadEscape));
+                return Err(Error::new(self.input, start..self.n, ErrorKind::BadEscape));
             }
         };
 
-        self.scratch.push(b);
+        self.scratch.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
         self.bump(1);
         Ok(())
     }
@@ -475,7 +765,7 @@ impl<'a> Parser<'a> {
                 b @ b'A'..=b'F' => u32::from(b - b'A') + 0xa,
                 _ => {
                     self.bump(1);
-                    return Err(Error::new(start..self.n, err));
+                    return Err(Error::new(self.input, start..self.n, err));
                 }
             };
 
@@ -483,7 +773,7 @@ impl<'a> Parser<'a> {
         }
 
         let Some(c) = char::from_u32(c) else {
-            return Err(Error::new(start..self.n, err));
+            return Err(Error::new(self.input, start..self.n, err));
         };
 
         self.scratch.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
@@ -497,7 +787,6 @@ impl<'a> Parser<'a> {
         self.bump(1);
 
         let mut items = Vec::new();
-        let mut last = false;
         let mut trailing = false;
         let mut item_prefix = self.ws();
 
@@ -509,27 +798,30 @@ impl<'a> Parser<'a> {
             let (value, next_prefix) =
                 self.value(&State::new(value_prefix).with_parent(item_id).with_inline())?;
 
-            self.data.replace(item_id, raw::SequenceItem { value });
-
-            items.push(item_id);
-
-            if last {
-                item_prefix = next_prefix.unwrap_or_else(|| self.ws());
-                break;
-            }
+            let ws = next_prefix.unwrap_or_else(|| self.ws());
+            let mut suffix = None;
 
             if matches!(self.peek1(), b',') {
                 self.bump(1);
                 trailing = true;
+                suffix = Some(ws);
+                item_prefix = self.ws();
             } else {
-                last = true;
+                item_prefix = ws;
             }
 
-            item_prefix = next_prefix.unwrap_or_else(|| self.ws());
+            self.data
+                .replace(item_id, raw::SequenceItem { value, suffix });
+            items.push(item_id);
+
+            if !trailing {
+                break;
+            }
         }
 
         if !matches!(self.peek1(), b']') {
             return Err(Error::new(
+                self.input,
                 self.span_back(item_prefix)..self.n,
                 ErrorKind::BadSequenceTerminator,
             ));
@@ -558,7 +850,6 @@ impl<'a> Parser<'a> {
         self.bump(1);
 
         let mut items = Vec::new();
-        let mut last = false;
         let mut trailing = false;
         let mut start = self.n;
         let mut item_prefix = self.ws();
@@ -567,7 +858,11 @@ impl<'a> Parser<'a> {
             trailing = false;
 
             let Some(key) = self.until_colon(self.n) else {
-                return Err(Error::new(start..self.n, ErrorKind::BadMappingSeparator));
+                return Err(Error::new(
+                    self.input,
+                    start..self.n,
+                    ErrorKind::BadMappingSeparator,
+                ));
             };
 
             let item_id = self.placeholder(item_prefix, Some(id));
@@ -576,28 +871,34 @@ impl<'a> Parser<'a> {
             let (value, next_prefix) =
                 self.value(&State::new(value_prefix).with_parent(item_id).with_inline())?;
 
-            self.data.replace(item_id, raw::MappingItem { key, value });
-            items.push(item_id);
-
-            item_prefix = next_prefix.unwrap_or_else(|| self.ws());
-
-            if last {
-                break;
-            }
+            let ws = next_prefix.unwrap_or_else(|| self.ws());
+            let mut suffix = None;
 
             if matches!(self.peek1(), b',') {
                 self.bump(1);
                 trailing = true;
+                suffix = Some(ws);
+                start = self.n;
+                item_prefix = self.ws();
             } else {
-                last = true;
+                item_prefix = ws;
             }
 
-            start = self.n;
-            item_prefix = self.ws();
+            self.data
+                .replace(item_id, raw::MappingItem { key, value, suffix });
+            items.push(item_id);
+
+            if !trailing {
+                break;
+            }
         }
 
         if !matches!(self.peek1(), b'}') {
-            return Err(Error::new(start..self.n, ErrorKind::BadMappingTerminator));
+            return Err(Error::new(
+                self.input,
+                start..self.n,
+                ErrorKind::BadMappingTerminator,
+            ));
         }
 
         self.bump(1);
@@ -626,6 +927,17 @@ impl<'a> Parser<'a> {
         let mut previous_ws = None;
         let indent = self.indent();
 
+        // A sequence which is less indented than a parent mapping, or which
+        // isn't indented further than a parent sequence is a sibling, which
+        // means that we've encountered a nul value.
+        if matches!(s.parent_indent, Some(i) if i > indent)
+            || matches!(s.sequence_indent, Some(i) if i >= indent)
+        {
+            self.data
+                .replace_with(mapping_id, empty, Raw::Null(raw::Null::Empty));
+            return Ok((mapping_id, Some(s.prefix)));
+        }
+
         loop {
             let item_prefix = previous_ws.take().unwrap_or(empty);
             let item_id = self.placeholder(item_prefix, Some(mapping_id));
@@ -633,16 +945,29 @@ impl<'a> Parser<'a> {
             self.bump(1);
 
             let value_prefix = self.ws();
-            let (value, ws) =
-                self.value(&State::new(value_prefix).with_parent(item_id).with_tabular())?;
+            let (value, ws) = self.value(
+                &State::new(value_prefix)
+                    .with_parent(item_id)
+                    .with_tabular()
+                    .with_sequence_indent(indent),
+            )?;
 
-            self.data.replace(item_id, raw::SequenceItem { value });
+            self.data.replace(
+                item_id,
+                raw::SequenceItem {
+                    value,
+                    suffix: None,
+                },
+            );
             items.push(item_id);
 
             let ws = ws.unwrap_or_else(|| self.ws());
             previous_ws = Some(ws);
 
-            if self.indent() != indent || !matches!(self.peek1(), b'-') {
+            if self.indent() != indent
+                || !matches!(self.peek1(), b'-')
+                || self.is_document_marker(b'-')
+            {
                 break;
             }
         }
@@ -689,7 +1014,11 @@ impl<'a> Parser<'a> {
         while let Some(key) = current_key.take() {
             if !matches!(self.peek1(), b':') {
                 self.bump(1);
-                return Err(Error::new(start..self.n, ErrorKind::BadMappingSeparator));
+                return Err(Error::new(
+                    self.input,
+                    start..self.n,
+                    ErrorKind::BadMappingSeparator,
+                ));
             }
 
             let item_prefix = previous_ws.take().unwrap_or(empty);
@@ -705,13 +1034,23 @@ impl<'a> Parser<'a> {
                     .with_parent_indent(indent),
             )?;
 
-            self.data.replace(item_id, raw::MappingItem { key, value });
+            self.data.replace(
+                item_id,
+                raw::MappingItem {
+                    key,
+                    value,
+                    suffix: None,
+                },
+            );
             items.push(item_id);
 
             let ws = ws.unwrap_or_else(|| self.ws());
             previous_ws = Some(ws);
 
-            if self.indent() != indent {
+            if self.indent() != indent
+                || self.is_document_marker(b'-')
+                || self.is_document_marker(b'.')
+            {
                 break;
             }
 
@@ -746,12 +1085,15 @@ impl<'a> Parser<'a> {
     /// Process a key up until `:` or end of the current line.
     fn key_or_eol(&mut self, start: usize) -> Option<raw::String> {
         loop {
-            self.find2(b':', raw::NEWLINE);
+            self.find3(b':', b'#', raw::NEWLINE);
 
             match self.peek() {
                 [raw::NEWLINE | EOF, _] => {
                     return None;
                 }
+                [b'#', _] if self.is_comment() => {
+                    return None;
+                }
                 // Only treat something as a key if it's a colon immediately
                 // followed by spacing.
                 [b':', ws!(EOF)] => {
@@ -765,8 +1107,35 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Process a quoted key, which has to be immediately followed by a `:`.
+    ///
+    /// If there is no such key, the cursor is left where it was.
+    fn quoted_key(&mut self) -> Option<raw::String> {
+        let start = self.n;
+
+        let key = match self.peek1() {
+            b'"' => self.double_quoted().ok(),
+            b'\'' => self.single_quoted().ok(),
+            _ => return None,
+        };
+
+        if key.is_some() && self.peek1() == b':' {
+            return key;
+        }
+
+        self.scratch.clear();
+        self.n = start;
+        None
+    }
+
     /// Process a key up until `:`.
     fn until_colon(&mut self, start: usize) -> Option<raw::String> {
+        if start == self.n {
+            if let Some(key) = self.quoted_key() {
+                return Some(key);
+            }
+        }
+
         while !matches!(self.peek1(), b':' | EOF) {
             self.bump(1);
         }
@@ -775,82 +1144,402 @@ impl<'a> Parser<'a> {
             return None;
         }
 
-        let key = self.data.insert_str(self.string(start));
-        Some(raw::String::new(raw::RawStringKind::Bare, key, key))
+        let original = self.string(start);
+        let string = original.trim_end_with(|c| matches!(c, '\t' | '\x0C' | '\r' | ' ' | '\n'));
+
+        // Whitespace preceeding the `:` is not part of the key, but has to be
+        // retained in its original representation.
+        if string.len() == original.len() {
+            let key = self.data.insert_str(string);
+            return Some(raw::String::new(raw::RawStringKind::Bare, key, key));
+        }
+
+        let key = self.data.insert_str(string);
+        let original = self.data.insert_str(original);
+        Some(raw::String::new(
+            raw::RawStringKind::Original,
+            key,
+            original,
+        ))
     }
 
-    /// Process a block as a string.
-    fn block(
-        &mut self,
-        n: usize,
-        join: u8,
-        folded: bool,
-        chomp: bool,
-        clip: bool,
-    ) -> (Raw, Option<StringId>) {
-        let start = self.n;
-        self.bump(n);
-        let prefix = self.data.insert_str(self.string(start));
+    /// Consume the header of a block scalar following its `|` or `>`
+    /// indicator, returning the chomping indicator and the explicit indentation
+    /// indicator if there is one.
+    fn block_header(&mut self) -> (Chomp, Option<usize>) {
+        let mut chomp = Chomp::Clip;
+        let mut explicit = None;
 
-        let start = self.n;
-        let (mut ws, mut nl) = self.ws_nl();
+        for _ in 0..2 {
+            match self.peek1() {
+                b'-' => chomp = Chomp::Strip,
+                b'+' => chomp = Chomp::Keep,
+                b @ b'1'..=b'9' => explicit = Some(usize::from(b - b'0')),
+                _ => break,
+            }
 
-        if nl == 0 {
-            self.find(raw::NEWLINE);
-            let out = self.input.get(start..self.n).unwrap_or_default().trim();
-            self.scratch.extend_from_slice(out);
+            self.bump(1);
+        }
 
-            (ws, nl) = self.ws_nl();
+        while matches!(self.peek1(), other_ws!()) {
+            self.bump(1);
+        }
 
-            if !self.is_eof() {
-                self.scratch.push(join);
-            }
+        (chomp, explicit)
+    }
+
+    /// Collect the lines of a block scalar with their indentation removed,
+    /// where empty lines are `None`, returning them along with the offset at
+    /// which the last line of content ends.
+    ///
+    /// The indentation of the block is detected from its first line of content
+    /// unless it is given, and the block ends at the first line which is less
+    /// indented or which isn't more indented than `parent`. The cursor is
+    /// left at the end of the header line.
+    fn block_lines(
+        &mut self,
+        parent: Option<usize>,
+        mut indent: Option<usize>,
+    ) -> (Vec<Option<&'a [u8]>>, usize) {
+        let mut lines = Vec::new();
+
+        // Content on the same line as the header isn't permitted by YAML, but
+        // is accepted as the first line of the block. Empty lines immediately
+        // following it are ignored.
+        let head = !self.is_comment() && !matches!(self.peek1(), raw::NEWLINE | EOF);
+
+        if head {
+            let start = self.n;
+            self.find(raw::NEWLINE);
+            lines.push(Some(self.string(start).trim_end()));
+        } else {
+            self.find(raw::NEWLINE);
         }
 
         let mut end = self.n;
-        let indent = self.indent();
+        let mut pos = self.n;
+
+        // The line following the last line break only exists if it has any
+        // input in it.
+        while pos.wrapping_add(1) < self.input.len() {
+            let line_start = pos.wrapping_add(1);
+            let rest = self.input.get(line_start..).unwrap_or_default();
+            let line_end = memchr::memchr(raw::NEWLINE, rest)
+                .map_or(self.input.len(), |n| line_start.wrapping_add(n));
+            let line = self.input.get(line_start..line_end).unwrap_or_default();
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+            let spaces = line.iter().take_while(|&&b| b == raw::SPACE).count();
+
+            if spaces == line.len() && indent.map_or(true, |indent| spaces <= indent) {
+                if !head || lines.len() > 1 {
+                    lines.push(None);
+                }
 
-        while !self.is_eof() {
-            let s = self.n;
-            self.find(raw::NEWLINE);
-            let out = self.input.get(s..self.n).unwrap_or_default().trim();
-            self.scratch.extend_from_slice(out);
+                pos = line_end;
+                continue;
+            }
 
-            end = self.n;
-            (ws, nl) = self.ws_nl();
+            let indent = *indent.get_or_insert(spaces);
 
-            if self.indent() < indent {
+            if spaces < indent || parent.is_some_and(|parent| indent <= parent) {
                 break;
             }
 
-            for _ in 0..if folded { 1 } else { nl } {
-                self.scratch.push(join);
+            lines.push(Some(line.get(indent..).unwrap_or_default()));
+            end = line_end;
+            pos = line_end;
+        }
+
+        (lines, end)
+    }
+
+    /// Process a block scalar, like `|` or `>-`, as a string.
+    ///
+    /// The content of the block is every line which is more indented than
+    /// the collection it belongs to, or the indentation given by an explicit
+    /// indentation indicator. The returned whitespace starts at the end of the
+    /// last line of content, so any trailing empty lines are stored as part of
+    /// whatever follows the block.
+    fn block(&mut self, s: &State, folded: bool) -> (Raw, Option<StringId>) {
+        let original = self.n;
+        let parent = s.parent_indent.or(s.sequence_indent);
+        self.bump(1);
+
+        let (chomp, explicit) = self.block_header();
+        let indent = explicit.map(|n| parent.unwrap_or(0).wrapping_add(n));
+        let (mut lines, end) = self.block_lines(parent, indent);
+
+        // Empty lines following the last line of content only count towards
+        // the content when they are kept.
+        let trailing = lines.iter().rev().take_while(|line| line.is_none()).count();
+        lines.truncate(lines.len().wrapping_sub(trailing));
+
+        if folded {
+            let mut previous = None;
+            let mut empty = 0usize;
+
+            for line in &lines {
+                let Some(line) = line else {
+                    empty = empty.wrapping_add(1);
+                    continue;
+                };
+
+                // Lines which are more indented preserve the line breaks
+                // surrounding them.
+                let more = matches!(line.first(), Some(b' ' | b'\t'));
+
+                let breaks = match previous {
+                    None => empty,
+                    Some(false) if !more && empty == 0 => {
+                        self.scratch.push(raw::SPACE);
+                        0
+                    }
+                    Some(false) if !more => empty,
+                    Some(_) => empty.wrapping_add(1),
+                };
+
+                self.scratch
+                    .extend(std::iter::repeat(raw::NEWLINE).take(breaks));
+                self.scratch.extend_from_slice(line);
+                previous = Some(more);
+                empty = 0;
+            }
+        } else {
+            for line in &lines {
+                if let Some(line) = line {
+                    self.scratch.extend_from_slice(line);
+                }
+
+                self.scratch.push(raw::NEWLINE);
             }
+
+            self.scratch.pop();
         }
 
-        for _ in 0..chomp.then_some(nl).unwrap_or_default() {
-            self.scratch.push(raw::NEWLINE);
+        // The final line break is only present if the last line of content
+        // was terminated by one.
+        let terminated = end < self.input.len() && lines.iter().any(Option::is_some);
 
-            if clip {
-                break;
+        match chomp {
+            Chomp::Strip => {}
+            Chomp::Clip => {
+                if terminated {
+                    self.scratch.push(raw::NEWLINE);
+                }
+            }
+            Chomp::Keep => {
+                if terminated {
+                    self.scratch.push(raw::NEWLINE);
+                }
+
+                self.scratch
+                    .extend(std::iter::repeat(raw::NEWLINE).take(trailing));
             }
         }
 
+        self.n = end;
+
         let string = self.data.insert_str(&self.scratch);
         self.scratch.clear();
 
-        let out = self.input.get(start..end).unwrap_or_default();
+        let out = self.input.get(original..end).unwrap_or_default();
         let original = self.data.insert_str(out);
 
-        let kind = raw::RawStringKind::Multiline { prefix };
         (
-            Raw::String(raw::String::new(kind, string, original)),
-            Some(ws),
+            Raw::String(raw::String::new(
+                raw::RawStringKind::Multiline,
+                string,
+                original,
+            )),
+            Some(self.ws()),
         )
     }
 
-    /// Consume a single value.
+    /// Consume a single value, including any tag or anchor which is
+    /// associated with it.
     fn value(&mut self, s: &State) -> Result<(Id, Option<StringId>)> {
+        let mut s = *s;
+        let mut tag = None;
+        let mut anchor = None;
+
+        loop {
+            let is_tag = match self.peek() {
+                [b'!', _] if tag.is_none() => true,
+                [b'&', b] if anchor.is_none() && !matches!(b, ws!(EOF)) => false,
+                _ => break,
+            };
+
+            // Properties which are followed by a key on the same line are
+            // properties of the key, which we treat as a part of the key.
+            if !s.inline && self.properties_key() {
+                break;
+            }
+
+            let start = self.n;
+            self.tag();
+
+            let mut string = self.data.str(s.prefix).to_vec();
+            string.extend_from_slice(self.string(start));
+            let string = self.data.insert_str(string);
+
+            if is_tag {
+                tag = Some(string);
+            } else {
+                anchor = Some(raw::Anchor {
+                    string,
+                    after_tag: tag.is_some(),
+                });
+            }
+
+            let prefix = self.ws();
+            s = s.with_prefix(prefix);
+        }
+
+        let start = self.n;
+        let (value, ws) = self.untagged_value(&s)?;
+
+        if self.lines.is_some() {
+            // Whitespace consumed after the value is not a part of it.
+            let end = match ws {
+                Some(ws) => self.span_back(ws).max(start),
+                None => self.n,
+            };
+
+            self.data.set_span(value, start..end);
+        }
+
+        self.data.set_parsed(value);
+
+        if tag.is_some() {
+            self.data.set_tag(value, tag);
+            self.apply_tag(value);
+        }
+
+        self.data.set_anchor(value, anchor);
+        Ok((value, ws))
+    }
+
+    /// Test if the tags and anchors at the current position are followed by
+    /// a plain mapping key on the same line, without consuming any input.
+    fn properties_key(&mut self) -> bool {
+        let start = self.n;
+
+        while matches!(self.peek1(), b'!' | b'&') {
+            self.tag();
+
+            while matches!(self.peek1(), other_ws!()) {
+                self.bump(1);
+            }
+        }
+
+        let is_key = !matches!(self.peek1(), b'[' | b'{') && self.key_or_eol(self.n).is_some();
+        self.n = start;
+        is_key
+    }
+
+    /// Consume a tag, like `!!str`, `!custom` or `!<tag:yaml.org,2002:str>`.
+    ///
+    /// This is also used for anchors and aliases, like `&name` and `*name`.
+    fn tag(&mut self) {
+        if let [b'!', b'<'] = self.peek() {
+            self.find(b'>');
+            self.bump(1);
+            return;
+        }
+
+        while !matches!(self.peek1(), ws!(b',' | b'[' | b']' | b'{' | b'}' | EOF)) {
+            self.bump(1);
+        }
+    }
+
+    /// Test if the given plain scalar is a boolean, and if so which one.
+    ///
+    /// The YAML 1.1 spellings like `yes` and `off` are only recognized if
+    /// they are enabled through [`ParseOptions::yaml_1_1_bools`].
+    fn boolean(&self, string: &[u8]) -> Option<bool> {
+        if string.eq_ignore_ascii_case(b"true") {
+            return Some(true);
+        }
+
+        if string.eq_ignore_ascii_case(b"false") {
+            return Some(false);
+        }
+
+        if !self.options.yaml_1_1_bools {
+            return None;
+        }
+
+        match string {
+            b"yes" | b"Yes" | b"YES" | b"on" | b"On" | b"ON" => Some(true),
+            b"no" | b"No" | b"NO" | b"off" | b"Off" | b"OFF" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Apply a standard tag associated with a value, which allows it to
+    /// override the kind that was inferred for a plain scalar.
+    ///
+    /// Quoted strings and collections are left as-is.
+    fn apply_tag(&mut self, id: Id) {
+        let Some(tag) = self.data.tag(id).and_then(|tag| raw::CoreTag::detect(tag)) else {
+            return;
+        };
+
+        let string = match self.data.raw(id) {
+            Raw::Null(raw::Null::Keyword(string)) => *string,
+            Raw::Null(raw::Null::Tilde) => self.data.insert_str("~"),
+            Raw::Null(raw::Null::Empty) => self.data.insert_str(""),
+            Raw::Boolean(raw) => raw.string,
+            Raw::Number(raw) => raw.string,
+            Raw::String(raw::String {
+                kind: raw::RawStringKind::Bare,
+                id,
+                ..
+            }) => *id,
+            _ => return,
+        };
+
+        let text = self.data.str(string);
+
+        let raw = match tag {
+            raw::CoreTag::Str => Raw::String(raw::String::new(
+                raw::RawStringKind::Original,
+                string,
+                string,
+            )),
+            raw::CoreTag::Int if raw::parse_integer::<i128>(text).is_some() => {
+                let hint = if text.starts_with(b"-") {
+                    serde_hint::I64
+                } else {
+                    serde_hint::U64
+                };
+
+                Raw::Number(raw::Number::new(string, hint))
+            }
+            raw::CoreTag::Float
+                if raw::SpecialFloat::detect(text).is_some()
+                    || lexical_core::parse::<f64>(text).is_ok() =>
+            {
+                Raw::Number(raw::Number::new(string, serde_hint::F64))
+            }
+            raw::CoreTag::Bool => match self.boolean(text) {
+                Some(value) => Raw::Boolean(raw::Boolean::new(value, string)),
+                None => return,
+            },
+            raw::CoreTag::Null if text.is_empty() => Raw::Null(raw::Null::Empty),
+            raw::CoreTag::Null if text == "~" => Raw::Null(raw::Null::Tilde),
+            raw::CoreTag::Null if text.eq_ignore_ascii_case(b"null") => {
+                Raw::Null(raw::Null::Keyword(string))
+            }
+            _ => return,
+        };
+
+        *self.data.raw_mut(id) = raw;
+    }
+
+    /// Consume a single value which is not preceeded by a tag.
+    fn untagged_value(&mut self, s: &State) -> Result<(Id, Option<StringId>)> {
         let (raw, ws) = match self.peek() {
             [b'-', ws!()] if !s.inline => {
                 return self.sequence(s);
@@ -867,7 +1556,7 @@ impl<'a> Parser<'a> {
             }
             [b'\'', _] => {
                 let start = self.n;
-                let string = self.single_quoted();
+                let string = self.single_quoted()?;
 
                 if !s.inline && self.peek1() == b':' {
                     return self.mapping_or_nul(s, start, string);
@@ -877,17 +1566,25 @@ impl<'a> Parser<'a> {
             }
             [b'[', _] => return Ok((self.inline_sequence(s)?, None)),
             [b'{', _] => return Ok((self.inline_mapping(s)?, None)),
-            [a @ (b'>' | b'|'), b] => self.block(
-                matches!(b, b'-' | b'+').then_some(2).unwrap_or(1),
-                if a == b'>' { raw::SPACE } else { raw::NEWLINE },
-                a == b'>',
-                b != b'-',
-                b == b'-',
-            ),
+            [b'|', _] => self.block(s, false),
+            [b'>', _] => self.block(s, true),
             _ => {
                 'default: {
                     let start = self.n;
 
+                    if self.is_alias() {
+                        // An alias followed by `:` is a key.
+                        if !s.inline {
+                            if let Some(key) = self.key_or_eol(start) {
+                                return self.mapping_or_nul(s, start, key);
+                            }
+
+                            self.n = start;
+                        }
+
+                        break 'default (self.alias(), None);
+                    }
+
                     if let Some(number) = self.number(s, start) {
                         break 'default (number, None);
                     }
@@ -895,26 +1592,48 @@ impl<'a> Parser<'a> {
                     if s.inline {
                         // Seek until we find a control character, since we're
                         // simply treating the current segment as a string.
-                        while !matches!(self.peek1(), ctl!()) {
+                        while !matches!(self.peek1(), ctl!()) && !self.is_comment() {
                             self.bump(1);
                         }
                     } else if let Some(key) = self.key_or_eol(start) {
                         return self.mapping_or_nul(s, start, key);
                     }
 
+                    // Trailing whitespace is not part of the string, and will
+                    // instead be picked up as part of whatever follows it.
+                    while self.n > start
+                        && matches!(self.input.get(self.n.wrapping_sub(1)).copied(), Some(ws!()))
+                    {
+                        self.n = self.n.wrapping_sub(1);
+                    }
+
                     // NB: calling `key_or_eol` will have consumed up until end
                     // of line for us, so use the current span as the production
                     // string.
                     let string = self.string(start);
+
+                    // An empty value at the end of the document, like `a:`.
+                    // The whitespace preceeding it is treated as whitespace
+                    // following the value, the same way as for any other
+                    // empty value in a block collection.
+                    if !s.inline && string.is_empty() {
+                        let empty = self.data.insert_str("");
+                        let id = self
+                            .data
+                            .insert(Raw::Null(raw::Null::Empty), empty, s.parent);
+                        return Ok((id, Some(s.prefix)));
+                    }
+
                     (
-                        if string == b"~" {
+                        if s.inline && string.is_empty() {
+                            // An empty slot in an inline collection, like `[a, , c]`.
+                            Raw::Null(raw::Null::Empty)
+                        } else if string == b"~" {
                             Raw::Null(raw::Null::Tilde)
                         } else if string.eq_ignore_ascii_case(b"null") {
                             Raw::Null(raw::Null::Keyword(self.data.insert_str(string)))
-                        } else if string.eq_ignore_ascii_case(b"true") {
-                            Raw::Boolean(raw::Boolean::new(true, self.data.insert_str(string)))
-                        } else if string.eq_ignore_ascii_case(b"false") {
-                            Raw::Boolean(raw::Boolean::new(false, self.data.insert_str(string)))
+                        } else if let Some(value) = self.boolean(string) {
+                            Raw::Boolean(raw::Boolean::new(value, self.data.insert_str(string)))
                         } else {
                             let string = self.data.insert_str(string);
                             Raw::String(raw::String::new(raw::RawStringKind::Bare, string, string))
@@ -929,8 +1648,26 @@ impl<'a> Parser<'a> {
         Ok((value, ws))
     }
 
+    /// Test if the current position is at an alias like `*name`.
+    fn is_alias(&self) -> bool {
+        matches!(self.peek(), [b'*', b] if !matches!(b, ws!(EOF)))
+    }
+
+    /// Consume an alias like `*name`.
+    fn alias(&mut self) -> Raw {
+        self.bump(1);
+        let start = self.n;
+        self.tag();
+        let name = self.data.insert_str(self.string(start));
+        Raw::Alias(raw::Alias { name })
+    }
+
     /// Parse next mapping key.
     fn next_mapping_key(&mut self) -> Option<raw::String> {
+        if let Some(key) = self.quoted_key() {
+            return Some(key);
+        }
+
         let start = self.n;
 
         let string = loop {
@@ -954,3 +1691,16 @@ impl<'a> Parser<'a> {
         Some(raw::String::new(raw::RawStringKind::Bare, string, string))
     }
 }
+
+/// Collect the offsets at which each line of the input starts.
+fn lines(input: &[u8]) -> Vec<usize> {
+    let mut lines = vec![0];
+
+    for (n, b) in input.iter().enumerate() {
+        if *b == raw::NEWLINE {
+            lines.push(n.wrapping_add(1));
+        }
+    }
+
+    lines
+}
@@ -1,4 +1,7 @@
-use std::fmt::{self, Write};
+use std::cmp::Ordering;
+#[cfg(feature = "serde")]
+use std::collections::HashMap;
+use std::fmt;
 use std::io;
 use std::iter;
 use std::mem;
@@ -32,6 +35,300 @@ pub(crate) fn count_indent(string: &[u8]) -> usize {
     indent(string).chars().count()
 }
 
+/// Add `extra` spaces of indentation to every line in the given string after
+/// the first, except for lines which are empty. If `extra` is negative, up to
+/// that many spaces of indentation are removed instead.
+///
+/// Returns `None` if the string doesn't span multiple lines.
+pub(crate) fn indent_lines(string: &[u8], extra: isize) -> Option<Vec<u8>> {
+    memchr::memchr(NEWLINE, string)?;
+
+    let mut out = Vec::with_capacity(string.len());
+    let mut it = string.iter().peekable();
+
+    while let Some(&b) = it.next() {
+        out.push(b);
+
+        if b == NEWLINE && !matches!(it.peek(), Some(&&(NEWLINE | b'\r'))) {
+            if extra >= 0 {
+                out.extend(iter::repeat(SPACE).take(extra.unsigned_abs()));
+            } else {
+                for _ in 0..extra.unsigned_abs() {
+                    if it.next_if_eq(&&SPACE).is_none() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    Some(out)
+}
+
+/// Encode a UTF-8 string into latin-1.
+///
+/// Errors if the string contains anything which can't be represented in
+/// latin-1, including invalid UTF-8.
+pub(crate) fn encode_latin1(string: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(string.len());
+
+    for (start, _, c) in string.char_indices() {
+        match u8::try_from(u32::from(c)) {
+            Ok(b) => out.push(b),
+            Err(..) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("character at byte {start} can't be encoded as latin-1"),
+                ));
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Strip trailing spaces and tabs from every line in the given string which
+/// is followed by a line break.
+///
+/// Returns `None` if there is nothing to strip.
+pub(crate) fn trim_lines(string: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(string.len());
+    let mut modified = false;
+    let mut lines = string.split(|b| *b == NEWLINE).peekable();
+
+    while let Some(line) = lines.next() {
+        if lines.peek().is_none() {
+            out.extend_from_slice(line);
+            break;
+        }
+
+        let (line, cr) = match line.strip_suffix(b"\r") {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+
+        let trimmed = line.trim_end_with(|c| matches!(c, ' ' | '\t'));
+        modified |= trimmed.len() != line.len();
+        out.extend_from_slice(trimmed);
+
+        if cr {
+            out.push(b'\r');
+        }
+
+        out.push(NEWLINE);
+    }
+
+    modified.then_some(out)
+}
+
+/// A standard tag from the YAML core schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CoreTag {
+    /// `!!str`.
+    Str,
+    /// `!!int`.
+    Int,
+    /// `!!float`.
+    Float,
+    /// `!!bool`.
+    Bool,
+    /// `!!null`.
+    Null,
+}
+
+impl CoreTag {
+    /// Detect a standard tag, which is either in its shorthand form like
+    /// `!!str` or its verbatim form like `!<tag:yaml.org,2002:str>`.
+    ///
+    /// The tag may be preceeded by whitespace.
+    pub(crate) fn detect(tag: &[u8]) -> Option<Self> {
+        let tag = tag.trim_start();
+
+        let name = match tag.strip_prefix(b"!!") {
+            Some(name) => name,
+            None => tag
+                .strip_prefix(b"!<tag:yaml.org,2002:")?
+                .strip_suffix(b">")?,
+        };
+
+        match name {
+            b"str" => Some(Self::Str),
+            b"int" => Some(Self::Int),
+            b"float" => Some(Self::Float),
+            b"bool" => Some(Self::Bool),
+            b"null" => Some(Self::Null),
+            _ => None,
+        }
+    }
+}
+
+/// A special float value from the YAML core schema, which isn't supported
+/// when parsing numbers through lexical.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SpecialFloat {
+    /// `.inf` or `+.inf`.
+    Infinity,
+    /// `-.inf`.
+    NegInfinity,
+    /// `.nan`.
+    NaN,
+}
+
+impl SpecialFloat {
+    /// Every recognized spelling of a special float.
+    pub(crate) const SPELLINGS: [(&'static [u8], Self); 12] = [
+        (b".inf", Self::Infinity),
+        (b".Inf", Self::Infinity),
+        (b".INF", Self::Infinity),
+        (b"+.inf", Self::Infinity),
+        (b"+.Inf", Self::Infinity),
+        (b"+.INF", Self::Infinity),
+        (b"-.inf", Self::NegInfinity),
+        (b"-.Inf", Self::NegInfinity),
+        (b"-.INF", Self::NegInfinity),
+        (b".nan", Self::NaN),
+        (b".NaN", Self::NaN),
+        (b".NAN", Self::NaN),
+    ];
+
+    /// Detect a special float from its string.
+    pub(crate) fn detect(string: &[u8]) -> Option<Self> {
+        Self::SPELLINGS
+            .iter()
+            .find(|(spelling, _)| *spelling == string)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// Parse an integer, supporting `_` digit separators and the `0x`, `0o`, and
+/// `0b` radix prefixes on top of what [`lexical_core`] supports.
+pub(crate) fn parse_integer<T>(string: &[u8]) -> Option<T>
+where
+    T: lexical_core::FromLexical + TryFrom<u128> + TryFrom<i128>,
+{
+    let (negative, rest) = match string {
+        [b'-', rest @ ..] => (true, rest),
+        [b'+', rest @ ..] | rest => (false, rest),
+    };
+
+    let (radix, digits) = match rest {
+        [b'0', b'x', digits @ ..] => (16, digits),
+        [b'0', b'o', digits @ ..] => (8, digits),
+        [b'0', b'b', digits @ ..] => (2, digits),
+        digits if digits.contains(&b'_') => (10, digits),
+        _ => return lexical_core::parse(string).ok(),
+    };
+
+    // Separators are only allowed between digits.
+    if matches!(digits.first(), None | Some(b'_'))
+        || digits.last() == Some(&b'_')
+        || digits.windows(2).any(|w| w == b"__")
+    {
+        return None;
+    }
+
+    let mut magnitude = 0u128;
+
+    for &b in digits {
+        if b == b'_' {
+            continue;
+        }
+
+        let digit = char::from(b).to_digit(radix)?;
+        magnitude = magnitude
+            .checked_mul(u128::from(radix))?
+            .checked_add(u128::from(digit))?;
+    }
+
+    if negative {
+        T::try_from(0i128.checked_sub_unsigned(magnitude)?).ok()
+    } else {
+        T::try_from(magnitude).ok()
+    }
+}
+
+/// Format a float, using the spelling of the YAML core schema for values which
+/// are not finite rather than the one produced by ryu.
+pub(crate) fn format_float<F>(buffer: &mut ryu::Buffer, value: F) -> &str
+where
+    F: ryu::Float + Into<f64>,
+{
+    let float: f64 = value.into();
+
+    if float.is_nan() {
+        ".nan"
+    } else if float.is_infinite() {
+        if float.is_sign_negative() {
+            "-.inf"
+        } else {
+            ".inf"
+        }
+    } else {
+        buffer.format_finite(value)
+    }
+}
+
+/// Test if the given tag is the core `!!binary` tag.
+pub(crate) fn is_binary_tag(tag: &[u8]) -> bool {
+    tag == b"!!binary" || tag == b"!<tag:yaml.org,2002:binary>"
+}
+
+/// Test if the given tag is the core `!!timestamp` tag.
+pub(crate) fn is_timestamp_tag(tag: &[u8]) -> bool {
+    tag == b"!!timestamp" || tag == b"!<tag:yaml.org,2002:timestamp>"
+}
+
+/// Decode base64 in the standard alphabet, ignoring whitespace such as the
+/// line breaks of a block scalar.
+pub(crate) fn decode_base64(string: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(string.len() / 4 * 3);
+    let mut bits = 0u32;
+    let mut len = 0u32;
+    let mut padding = 0u32;
+
+    for &b in string {
+        if b.is_ascii_whitespace() {
+            continue;
+        }
+
+        if b == b'=' {
+            padding = padding.wrapping_add(1);
+            continue;
+        }
+
+        // Padding is only allowed at the end.
+        if padding > 0 {
+            return None;
+        }
+
+        let digit = match b {
+            b'A'..=b'Z' => b.wrapping_sub(b'A'),
+            b'a'..=b'z' => b.wrapping_sub(b'a').wrapping_add(26),
+            b'0'..=b'9' => b.wrapping_sub(b'0').wrapping_add(52),
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+
+        bits = (bits << 6) | u32::from(digit);
+        len = len.wrapping_add(6);
+
+        if len >= 8 {
+            len = len.wrapping_sub(8);
+            out.push((bits >> len).to_le_bytes()[0]);
+            bits &= (1 << len) - 1;
+        }
+    }
+
+    // Whatever is left over has to be zeroes, and is what the padding stands
+    // in for.
+    if bits != 0 || len >= 6 || padding > 2 || (padding > 0 && len / 2 != padding) {
+        return None;
+    }
+
+    Some(out)
+}
+
 /// Construct a raw kind associated with booleans.
 pub(crate) fn new_bool(data: &mut Data, value: bool) -> Raw {
     let string = data.insert_str(if value { "true" } else { "false" });
@@ -39,11 +336,20 @@ pub(crate) fn new_bool(data: &mut Data, value: bool) -> Raw {
 }
 
 /// Construct a raw kind associated with a string.
+///
+/// The string is quoted if it would otherwise be read back as something else,
+/// as determined by [`requires_quoting`].
 pub(crate) fn new_string<S>(data: &mut Data, string: S) -> Raw
 where
     S: AsRef<str>,
 {
-    let kind = RawStringKind::detect(string.as_ref());
+    let kind = match RawStringKind::detect(string.as_ref()) {
+        RawStringKind::Bare if requires_quoting(data, string.as_ref(), false) => {
+            RawStringKind::Single
+        }
+        kind => kind,
+    };
+
     let string = data.insert_str(string.as_ref());
     Raw::String(String::new(kind, string, string))
 }
@@ -89,17 +395,456 @@ pub(crate) fn make_indent(data: &mut Data, id: Id, extra: usize) -> (usize, Stri
         // - two: 2
         //   three: 3
         // ```
-        if let Some((Raw::SequenceItem(..), layout)) = parent.map(|id| data.pair(id)) {
-            let indent = self::count_indent(data.str(layout.prefix))
-                .saturating_add(2)
-                .saturating_add(extra);
+        //
+        // That's not possible if the value has a tag or an anchor, since the
+        // first item would then be read as the value they belong to.
+        if let Some(Raw::SequenceItem(..)) = parent.map(|id| data.raw(id)) {
+            let indent = indent.saturating_add(2).saturating_add(extra);
+
+            if has_properties(data, id) {
+                let prefix = line_prefix(data.str(layout.prefix), indent);
+                return (indent, data.insert_str(prefix));
+            }
+
             return (indent, data.insert_str(" "));
         }
     }
 
-    let indent = indent.saturating_add(2);
-    // Take some pains to preserve the existing suffix, synthesize extra spaces characters where needed.
-    let mut existing = self::indent(data.str(layout.prefix)).chars();
+    let indent = indent.saturating_add(data.indent().step.get());
+    let prefix = line_prefix(data.str(layout.prefix), indent);
+    (indent, data.insert_str(prefix))
+}
+
+/// Get the indentation of the value with the given identifier. For block
+/// collections this is the indentation of their items, for anything else it is
+/// the indentation a block collection would have in its place as per
+/// [`make_indent`].
+pub(crate) fn indent_of(data: &Data, id: Id) -> usize {
+    match data.raw(id) {
+        Raw::Mapping(raw) if !is_inline(data, id) => return raw.indent,
+        Raw::Sequence(raw) if !is_inline(data, id) => return raw.indent,
+        _ => {}
+    }
+
+    let parent = data.layout(id).parent;
+    let container = parent.and_then(|id| data.layout(id).parent);
+
+    match container.map(|id| data.raw(id)) {
+        Some(Raw::Mapping(raw)) => raw.indent.saturating_add(data.indent().step.get()),
+        Some(Raw::Sequence(raw)) => {
+            if matches!(raw.kind, SequenceKind::Mapping) {
+                raw.indent.saturating_add(2)
+            } else {
+                raw.indent.saturating_add(data.indent().step.get())
+            }
+        }
+        _ => count_indent(data.prefix(id)),
+    }
+}
+
+/// Construct an indentation prefix for a tabular sequence which is the value
+/// of a tabular mapping, where the sequence is on the same indentation level
+/// as the mapping.
+///
+/// Returns `None` if the value is not in a tabular mapping.
+pub(crate) fn make_indentless(data: &mut Data, id: Id) -> Option<(usize, StringId)> {
+    let container = data
+        .layout(id)
+        .parent
+        .and_then(|id| data.layout(id).parent)?;
+
+    let (
+        Raw::Mapping(Mapping {
+            indent,
+            kind: MappingKind::Mapping,
+            ..
+        }),
+        layout,
+    ) = data.pair(container)
+    else {
+        return None;
+    };
+
+    let indent = *indent;
+    let prefix = line_prefix(data.str(layout.prefix), indent);
+    Some((indent, data.insert_str(prefix)))
+}
+
+/// Convert the inline collection with the given identifier into a block
+/// collection, with each item on its own line.
+///
+/// Comments inside of the inline collection are moved onto the lines of the
+/// block collection. Comments preceding the first item are placed on their own
+/// lines before it, and comments following an item are placed at the end of
+/// its line.
+///
+/// This does nothing if the value is not an inline collection, or if it is
+/// contained in an inline collection since those can't contain block
+/// collections.
+pub(crate) fn force_block(data: &mut Data, id: Id) {
+    if !is_inline(data, id) || is_inline_item(data, id) {
+        return;
+    }
+
+    let mut pending = comments(data.prefix(id));
+
+    let (indent, prefix) = make_indent(data, id, 0);
+
+    let prefix = if !pending.is_empty() && data.str(prefix).starts_with(&[NEWLINE]) {
+        let mut out = trailing_comments(&mut pending, data.str(prefix));
+        out.extend_from_slice(data.str(prefix));
+        data.insert_str(out)
+    } else {
+        pending.clear();
+        prefix
+    };
+
+    data.set_prefix(id, prefix);
+
+    let (items, suffix) = match data.raw_mut(id) {
+        Raw::Sequence(raw) => {
+            let SequenceKind::Inline { suffix, .. } = raw.kind else {
+                return;
+            };
+
+            raw.indent = indent;
+            raw.kind = SequenceKind::Mapping;
+            (raw.items.clone(), suffix)
+        }
+        Raw::Mapping(raw) => {
+            let MappingKind::Inline { suffix, .. } = raw.kind else {
+                return;
+            };
+
+            raw.indent = indent;
+            raw.kind = MappingKind::Mapping;
+            (raw.items.clone(), suffix)
+        }
+        _ => return,
+    };
+
+    let mut out = vec![NEWLINE];
+    out.resize(indent.saturating_add(1), SPACE);
+    let line_prefix = data.insert_str(out);
+
+    let value_prefix = data.insert_str(" ");
+    let empty_prefix = data.insert_str("");
+
+    for (n, item) in items.into_iter().enumerate() {
+        pending.extend(comments(data.prefix(item)));
+
+        let line = data.str(line_prefix);
+
+        let item_prefix = if n == 0 {
+            let mut out = Vec::new();
+
+            for comment in pending.drain(..) {
+                out.extend_from_slice(&comment);
+                out.extend_from_slice(line);
+            }
+
+            out
+        } else {
+            let mut out = trailing_comments(&mut pending, line);
+            out.extend_from_slice(line);
+            out
+        };
+
+        let item_prefix = data.insert_str(item_prefix);
+        data.set_prefix(item, item_prefix);
+
+        let (value, suffix) = match data.raw_mut(item) {
+            Raw::SequenceItem(raw) => (raw.value, raw.suffix.take()),
+            Raw::MappingItem(raw) => (raw.value, raw.suffix.take()),
+            _ => continue,
+        };
+
+        pending.extend(comments(data.prefix(value)));
+
+        if let Some(suffix) = suffix {
+            pending.extend(comments(data.str(suffix)));
+        }
+
+        if matches!(data.raw(value), Raw::Null(Null::Empty)) {
+            data.set_prefix(value, empty_prefix);
+        } else {
+            data.set_prefix(value, value_prefix);
+        }
+
+        separate_properties(data, value);
+    }
+
+    pending.extend(comments(data.str(suffix)));
+
+    if pending.is_empty() {
+        return;
+    }
+
+    // Comments following the last item end up in the prefix of whatever
+    // follows the collection, since that is where the parser puts comments
+    // trailing a block collection.
+    let Some(following) = Following::locate(data, id) else {
+        return;
+    };
+
+    let existing = following.get(data);
+    let line = self::indent(existing);
+    let mut out = trailing_comments(&mut pending, line);
+
+    // The following line might already have a comment of its own.
+    let first = existing.lines().next().unwrap_or_default();
+
+    if first.contains(&b'#') {
+        out.push(NEWLINE);
+        out.extend_from_slice(line);
+        out.extend_from_slice(existing.trim_start_with(|c| c == ' ' || c == '\t'));
+    } else {
+        out.extend_from_slice(existing);
+    }
+
+    let string = data.insert_str(out);
+    following.set(data, string);
+}
+
+/// Collect the comments in the given whitespace, which is expected to only
+/// consist of whitespace and comments.
+fn comments(string: &[u8]) -> Vec<Vec<u8>> {
+    let mut out = Vec::new();
+
+    for line in string.lines() {
+        if let Some(n) = memchr::memchr(b'#', line) {
+            out.push(line[n..].trim_end().to_vec());
+        }
+    }
+
+    out
+}
+
+/// Construct a string which places the given comments at the end of the
+/// current line, where the first comment stays on the current line and any
+/// subsequent ones are placed on lines of their own using `line` as their
+/// prefix.
+///
+/// The returned string is expected to be followed by something starting with
+/// a newline.
+fn trailing_comments(comments: &mut Vec<Vec<u8>>, line: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    for (n, comment) in comments.drain(..).enumerate() {
+        if n == 0 {
+            out.push(SPACE);
+        } else {
+            out.push(NEWLINE);
+            out.extend_from_slice(self::indent(line));
+        }
+
+        out.extend_from_slice(&comment);
+    }
+
+    out
+}
+
+/// Separate the tag or anchor which comes first on the value with the given
+/// identifier from what precedes it with a single space, since inside of an
+/// inline collection it might directly follow a `[` or `,`.
+fn separate_properties(data: &mut Data, id: Id) {
+    let layout = data.layout(id);
+
+    if let Some(anchor) = layout
+        .anchor
+        .filter(|a| !a.after_tag || layout.tag.is_none())
+    {
+        let string = [b" ", data.str(anchor.string).trim_start()].concat();
+        let string = data.insert_str(string);
+        data.set_anchor(id, Some(Anchor { string, ..anchor }));
+    } else if let Some(tag) = layout.tag {
+        let string = [b" ", data.str(tag).trim_start()].concat();
+        let string = data.insert_str(string);
+        data.set_tag(id, Some(string));
+    }
+}
+
+/// Test if the value with the given identifier has a tag or an anchor.
+fn has_properties(data: &Data, id: Id) -> bool {
+    let layout = data.layout(id);
+    layout.tag.is_some() || layout.anchor.is_some()
+}
+
+/// Convert the value with the given identifier into a value which can be
+/// written inside of an inline collection, by recursively converting block
+/// collections into inline collections and block scalars into double-quoted
+/// strings.
+///
+/// Comments inside of converted block collections are dropped, since they
+/// can't be represented inside of an inline collection.
+pub(crate) fn force_flow(data: &mut Data, id: Id) {
+    let empty = data.insert_str("");
+    let space = data.insert_str(" ");
+
+    if data.prefix(id).contains(&NEWLINE) {
+        let is_mapping_value = data
+            .layout(id)
+            .parent
+            .is_some_and(|parent| matches!(data.raw(parent), Raw::MappingItem(..)));
+
+        data.set_prefix(id, if is_mapping_value { space } else { empty });
+    }
+
+    for value in data.walk(id).collect::<Vec<_>>() {
+        let (items, value_prefix) = match data.raw_mut(value) {
+            Raw::Sequence(raw) if matches!(raw.kind, SequenceKind::Mapping) => {
+                raw.kind = SequenceKind::Inline {
+                    trailing: false,
+                    suffix: empty,
+                };
+
+                (raw.items.clone(), empty)
+            }
+            Raw::Mapping(raw) if matches!(raw.kind, MappingKind::Mapping) => {
+                raw.kind = MappingKind::Inline {
+                    trailing: false,
+                    suffix: empty,
+                };
+
+                (raw.items.clone(), space)
+            }
+            Raw::String(raw) if matches!(raw.kind, RawStringKind::Multiline) => {
+                raw.kind = RawStringKind::Double;
+                continue;
+            }
+            _ => continue,
+        };
+
+        let mut item_prefix = empty;
+
+        for item in items {
+            data.set_prefix(item, item_prefix);
+            item_prefix = space;
+
+            let value = match data.raw(item) {
+                Raw::SequenceItem(raw) => raw.value,
+                Raw::MappingItem(raw) => raw.value,
+                _ => continue,
+            };
+
+            data.set_prefix(value, value_prefix);
+        }
+    }
+}
+
+/// Stably sort the items of the mapping with the given identifier by their
+/// keys, moving the rest of the line which follows each item along with it as
+/// described in [`reorder_items`].
+pub(crate) fn sort_mapping_with_lines<F>(data: &mut Data, id: Id, mut compare: F)
+where
+    F: FnMut(&[u8], &[u8]) -> Ordering,
+{
+    let mut order = data.mapping(id).items.clone();
+
+    order.sort_by(|a, b| {
+        let a = data.str(data.mapping_item(*a).key.id);
+        let b = data.str(data.mapping_item(*b).key.id);
+        compare(a, b)
+    });
+
+    reorder_items(data, id, order);
+}
+
+/// Put the items of the collection with the given identifier in the given
+/// order, which must be a permutation of its current items.
+///
+/// The prefixes and suffixes of the items are kept in their original
+/// positions, so that the layout of the collection is retained. In block
+/// collections, the rest of the line which follows each item, such as a
+/// trailing comment, moves along with it.
+pub(crate) fn reorder_items(data: &mut Data, id: Id, order: Vec<Id>) {
+    let (items, block) = match data.raw(id) {
+        Raw::Mapping(raw) => (raw.items.clone(), matches!(raw.kind, MappingKind::Mapping)),
+        Raw::Sequence(raw) => (raw.items.clone(), matches!(raw.kind, SequenceKind::Mapping)),
+        _ => return,
+    };
+
+    if order == items {
+        return;
+    }
+
+    let tail = if block {
+        Following::locate(data, id)
+    } else {
+        None
+    };
+
+    // The whitespace following the item at the given position.
+    let following = |items: &[Id], index: usize| match items.get(index.wrapping_add(1)) {
+        Some(next) if block => Some(Following::Prefix(*next)),
+        _ => tail,
+    };
+
+    let mut lines = Vec::new();
+
+    for (index, item) in items.iter().enumerate() {
+        if let Some(following) = following(&items, index) {
+            let string = following.get(data);
+            let n = string.find_byte(NEWLINE).unwrap_or(string.len());
+            lines.push((*item, string[..n].to_vec()));
+        }
+    }
+
+    let layout = items
+        .iter()
+        .map(|item| (data.layout(*item).prefix, item_suffix(data, *item)))
+        .collect::<Vec<_>>();
+
+    for (item, (prefix, suffix)) in order.iter().zip(layout) {
+        data.set_prefix(*item, prefix);
+
+        match data.raw_mut(*item) {
+            Raw::MappingItem(raw) => raw.suffix = suffix,
+            Raw::SequenceItem(raw) => raw.suffix = suffix,
+            _ => {}
+        }
+    }
+
+    for (index, item) in order.iter().enumerate() {
+        let Some(following) = following(&order, index) else {
+            continue;
+        };
+
+        let Some((_, line)) = lines.iter().find(|(id, _)| id == item) else {
+            continue;
+        };
+
+        let string = following.get(data);
+        let n = string.find_byte(NEWLINE).unwrap_or(string.len());
+
+        let mut new = line.clone();
+        new.extend_from_slice(&string[n..]);
+        let new = data.insert_str(new);
+        following.set(data, new);
+    }
+
+    match data.raw_mut(id) {
+        Raw::Mapping(raw) => raw.items = order,
+        Raw::Sequence(raw) => raw.items = order,
+        _ => {}
+    }
+}
+
+/// Get the suffix of a mapping or sequence item.
+fn item_suffix(data: &Data, id: Id) -> Option<StringId> {
+    match data.raw(id) {
+        Raw::MappingItem(raw) => raw.suffix,
+        Raw::SequenceItem(raw) => raw.suffix,
+        _ => None,
+    }
+}
+
+/// Construct a prefix which starts a new line with the given indentation.
+///
+/// Take some pains to preserve the existing indentation, synthesize extra
+/// spaces characters where needed.
+fn line_prefix(existing: &[u8], indent: usize) -> Vec<u8> {
+    let mut existing = self::indent(existing).chars();
 
     let mut prefix = Vec::new();
 
@@ -113,7 +858,64 @@ pub(crate) fn make_indent(data: &mut Data, id: Id, extra: usize) -> (usize, Stri
         }
     }
 
-    (indent, data.insert_str(prefix))
+    prefix
+}
+
+/// Construct a raw kind associated with a string which replaces the existing
+/// value with the given identifier, preserving the kind of quoting used where
+/// possible.
+///
+/// If the existing value is not quoted, the new string is only quoted if it
+/// has to be.
+pub(crate) fn new_string_like<S>(data: &mut Data, id: Id, string: S) -> Raw
+where
+    S: AsRef<str>,
+{
+    let string = string.as_ref();
+
+    let quote = match data.raw(id) {
+        Raw::String(existing) => existing.quote(data),
+        _ => None,
+    };
+
+    let kind = kind_like(data, quote, string, is_inline_item(data, id));
+    let string = data.insert_str(string);
+    Raw::String(String::new(kind, string, string))
+}
+
+/// Pick the kind of a mapping key which replaces a key quoted with the given
+/// quote character, if any.
+///
+/// Unlike values, bare keys are always quoted if they contain a `:`, since it
+/// would otherwise be read back as the end of the key.
+pub(crate) fn key_kind_like(
+    data: &Data,
+    quote: Option<u8>,
+    string: &str,
+    inline: bool,
+) -> RawStringKind {
+    match kind_like(data, quote, string, inline) {
+        RawStringKind::Bare if string.contains(':') => RawStringKind::Single,
+        kind => kind,
+    }
+}
+
+/// Pick the kind of a string which replaces a string quoted with the given
+/// quote character, if any.
+pub(crate) fn kind_like(
+    data: &Data,
+    quote: Option<u8>,
+    string: &str,
+    inline: bool,
+) -> RawStringKind {
+    match quote {
+        Some(b'\'') if !has_control(string.as_bytes()) => RawStringKind::Single,
+        Some(b'"') => RawStringKind::Double,
+        _ => match RawStringKind::detect(string) {
+            RawStringKind::Bare if requires_quoting(data, string, inline) => RawStringKind::Single,
+            kind => kind,
+        },
+    }
 }
 
 /// Construct a raw kind associated with a string with a custom string kind.
@@ -137,9 +939,24 @@ where
     I: IntoIterator,
     I::Item: AsRef<str>,
 {
-    let (indent, prefix) = match data.raw(id) {
-        Raw::Mapping(raw) => (raw.indent.wrapping_add(2), BStr::new(b"")),
-        Raw::Sequence(raw) => (raw.indent.wrapping_add(2), BStr::new(b"")),
+    // A value inside of a collection is indented relative to the collection
+    // rather than to whatever precedes it on its line.
+    let nested = match data.raw(id) {
+        Raw::Mapping(..) | Raw::Sequence(..) => None,
+        _ if data.layout(id).parent.is_some() => Some(make_indent(data, id, 0).0),
+        _ => None,
+    };
+
+    let (indent, prefix) = match (data.raw(id), nested) {
+        (Raw::Mapping(raw), _) => (
+            raw.indent.wrapping_add(data.indent().step.get()),
+            BStr::new(b""),
+        ),
+        (Raw::Sequence(raw), _) => (
+            raw.indent.wrapping_add(data.indent().step.get()),
+            BStr::new(b""),
+        ),
+        (_, Some(indent)) => (indent, BStr::new(b"")),
         _ => {
             let prefix = data.str(data.layout(id).prefix);
 
@@ -181,7 +998,113 @@ where
     let original = data.insert_str(&original);
     let string = data.insert_str(out);
 
-    Raw::String(self::String::new(RawStringKind::Original, string, original))
+    Raw::String(self::String::new(
+        RawStringKind::Multiline,
+        string,
+        original,
+    ))
+}
+
+/// Get the kind of block scalar from the original text of a string, which
+/// starts with its header such as `|` or `>-`.
+pub(crate) fn block_of(original: &[u8]) -> Option<Block> {
+    let chomp = match original.get(1..).unwrap_or_default() {
+        [b'-', ..] | [b'1'..=b'9', b'-', ..] => Chomp::Strip,
+        [b'+', ..] | [b'1'..=b'9', b'+', ..] => Chomp::Keep,
+        _ => Chomp::Clip,
+    };
+
+    match original.first() {
+        Some(b'|') => Some(Block::Literal(chomp)),
+        Some(b'>') => Some(Block::Folded(chomp)),
+        _ => None,
+    }
+}
+
+/// Re-wrap the words of the folded block scalar with the given identifier so
+/// that its lines fit within `max_width` columns, if any of them don't.
+///
+/// Only scalars which weren't parsed are affected, since the text of parsed
+/// scalars and of copies of them is retained as it is. Scalars which can't be
+/// re-wrapped without changing their content, such as those containing line
+/// breaks, are left as they are. Words which are too long to fit are put on
+/// their own lines.
+pub(crate) fn wrap_folded(data: &mut Data, id: Id, max_width: usize) {
+    if data.is_parsed(id) {
+        return;
+    }
+
+    let Raw::String(raw) = data.raw(id) else {
+        return;
+    };
+
+    if !matches!(raw.kind, RawStringKind::Multiline) {
+        return;
+    }
+
+    let original = data.str(raw.original);
+
+    let Some(Block::Folded(chomp)) = block_of(original) else {
+        return;
+    };
+
+    let mut lines = original.lines().skip(1);
+
+    let Some(first) = lines.next() else {
+        return;
+    };
+
+    if first.chars().count() <= max_width && lines.all(|line| line.chars().count() <= max_width) {
+        return;
+    }
+
+    let column = first
+        .len()
+        .wrapping_sub(first.trim_start_with(|c| c == ' ').len());
+
+    let Ok(mut content) = data.str(raw.id).to_str() else {
+        return;
+    };
+
+    if !matches!(chomp, Chomp::Strip) {
+        content = content.strip_suffix('\n').unwrap_or(content);
+    }
+
+    // Line breaks are preserved as they are by folded scalars, and lines
+    // starting with a space are not folded.
+    if content.contains('\n') || content.starts_with(' ') {
+        return;
+    }
+
+    // Breaking a line replaces a single space, so runs of spaces are kept
+    // together with the word they follow.
+    let mut words = Vec::<std::string::String>::new();
+
+    for word in content.split(' ') {
+        match words.last_mut() {
+            Some(last) if word.is_empty() || last.ends_with(' ') => {
+                last.push(' ');
+                last.push_str(word);
+            }
+            _ => words.push(word.to_owned()),
+        }
+    }
+
+    let available = max_width.saturating_sub(column);
+    let mut wrapped = Vec::<std::string::String>::new();
+
+    for word in words {
+        match wrapped.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= available => {
+                line.push(' ');
+                line.push_str(&word);
+            }
+            _ => wrapped.push(word),
+        }
+    }
+
+    let value = new_block(data, id, wrapped, Block::Folded(chomp));
+    *data.raw_mut(id) = value;
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -190,8 +1113,193 @@ pub(crate) struct Layout {
     /// Reference to the indentation just preceeding the current value.
     pub(crate) prefix: StringId,
     /// Reference to the parent of a value.
-    #[allow(unused)]
     pub(crate) parent: Option<Id>,
+    /// An explicit tag like `!!str` associated with the value, including the
+    /// whitespace preceeding it.
+    pub(crate) tag: Option<StringId>,
+    /// An anchor like `&name` associated with the value.
+    pub(crate) anchor: Option<Anchor>,
+    /// If the value is a copy made through [`Document::clone_value`] which
+    /// hasn't been moved into place yet.
+    ///
+    /// [`Document::clone_value`]: crate::yaml::Document::clone_value
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    pub(crate) detached: bool,
+}
+
+/// An anchor like `&name` which is associated with a value.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
+pub(crate) struct Anchor {
+    /// The anchor, including the whitespace preceeding it.
+    pub(crate) string: StringId,
+    /// If the anchor follows the tag of the value, like `!!str &name`.
+    pub(crate) after_tag: bool,
+}
+
+/// Get the properties of the value with the given identifier, which are its
+/// anchor and its tag, in the order in which they should be emitted.
+pub(crate) fn properties(data: &Data, id: Id) -> [Option<&BStr>; 2] {
+    let layout = data.layout(id);
+    let tag = layout.tag.map(|tag| data.str(tag));
+    let anchor = layout.anchor.map(|anchor| data.str(anchor.string));
+
+    match layout.anchor {
+        Some(Anchor {
+            after_tag: true, ..
+        }) => [tag, anchor],
+        _ => [anchor, tag],
+    }
+}
+
+/// Resolve the alias with the given identifier to the value which has the
+/// anchor it refers to.
+///
+/// This is the closest preceeding value in document order with a matching
+/// anchor. An anchor is only defined once the value it is attached to is
+/// complete, so aliases which refer to a value they are contained in are not
+/// resolved.
+pub(crate) fn resolve_alias(data: &Data, id: Id) -> Option<Id> {
+    fn visit(data: &Data, id: Id, alias: Id, name: &BStr, found: &mut Option<Id>) -> bool {
+        if id == alias {
+            return true;
+        }
+
+        let items = match data.raw(id) {
+            Raw::Mapping(raw) => &raw.items[..],
+            Raw::Sequence(raw) => &raw.items[..],
+            _ => &[],
+        };
+
+        for item in items {
+            let value = match data.raw(*item) {
+                Raw::MappingItem(item) => item.value,
+                Raw::SequenceItem(item) => item.value,
+                _ => continue,
+            };
+
+            if visit(data, value, alias, name, found) {
+                return true;
+            }
+        }
+
+        if data.anchor(id) == Some(name) {
+            *found = Some(id);
+        }
+
+        false
+    }
+
+    let Raw::Alias(raw) = data.raw(id) else {
+        return None;
+    };
+
+    let name = data.str(raw.name);
+    let mut root = id;
+
+    while let Some(parent) = data.parent(root) {
+        root = parent;
+    }
+
+    let mut found = None;
+
+    if !visit(data, root, id, name, &mut found) {
+        return None;
+    }
+
+    found
+}
+
+/// Resolve every alias contained in the value with the given identifier in a
+/// single pass, returning a map from each alias to the value it refers to.
+///
+/// Aliases are resolved the same way as through [`resolve_alias`], and aliases
+/// which can't be resolved are left out.
+#[cfg(feature = "serde")]
+pub(crate) fn resolve_aliases(data: &Data, id: Id) -> HashMap<Id, Id> {
+    fn visit<'a>(
+        data: &'a Data,
+        id: Id,
+        anchors: &mut HashMap<&'a BStr, Id>,
+        aliases: &mut HashMap<Id, Id>,
+    ) {
+        let items = match data.raw(id) {
+            Raw::Mapping(raw) => &raw.items[..],
+            Raw::Sequence(raw) => &raw.items[..],
+            Raw::Alias(raw) => {
+                if let Some(value) = anchors.get(data.str(raw.name)) {
+                    aliases.insert(id, *value);
+                }
+
+                &[]
+            }
+            _ => &[],
+        };
+
+        for item in items {
+            let value = match data.raw(*item) {
+                Raw::MappingItem(item) => item.value,
+                Raw::SequenceItem(item) => item.value,
+                _ => continue,
+            };
+
+            visit(data, value, anchors, aliases);
+        }
+
+        if let Some(anchor) = data.anchor(id) {
+            anchors.insert(anchor, id);
+        }
+    }
+
+    let mut aliases = HashMap::new();
+    visit(data, id, &mut HashMap::new(), &mut aliases);
+    aliases
+}
+
+/// Count the number of values in the value with the given identifier,
+/// including itself. Aliases count as a single value.
+#[cfg(feature = "serde")]
+pub(crate) fn count_values(data: &Data, id: Id) -> usize {
+    let items = match data.raw(id) {
+        Raw::Mapping(raw) => &raw.items[..],
+        Raw::Sequence(raw) => &raw.items[..],
+        _ => &[],
+    };
+
+    let mut count = 1usize;
+
+    for item in items {
+        let value = match data.raw(*item) {
+            Raw::MappingItem(item) => item.value,
+            Raw::SequenceItem(item) => item.value,
+            _ => continue,
+        };
+
+        count = count.saturating_add(count_values(data, value));
+    }
+
+    count
+}
+
+/// Test if the alias with the given identifier refers to an anchor on a value
+/// which contains it, which would make that value contain itself.
+pub(crate) fn is_anchor_cycle(data: &Data, id: Id) -> bool {
+    let Raw::Alias(raw) = data.raw(id) else {
+        return false;
+    };
+
+    let name = data.str(raw.name);
+    let mut current = id;
+
+    while let Some(parent) = data.parent(current) {
+        if data.anchor(parent) == Some(name) {
+            return true;
+        }
+
+        current = parent;
+    }
+
+    false
 }
 
 /// A raw value.
@@ -215,128 +1323,467 @@ pub(crate) enum Raw {
     Sequence(Sequence),
     /// A single item inside of a sequence.
     SequenceItem(SequenceItem),
+    /// An alias like `*name`, referring to a value with a matching anchor.
+    Alias(Alias),
 }
 
 impl Raw {
-    pub(crate) fn display(
+    /// Write the raw value to the given output.
+    ///
+    /// If `prefix` is set, the tag, anchor and prefix of the value with the
+    /// given identifier are written first.
+    pub(crate) fn write_to<O>(
         &self,
         data: &Data,
-        f: &mut fmt::Formatter<'_>,
+        o: &mut O,
         prefix: Option<Id>,
-    ) -> fmt::Result {
+    ) -> Result<(), O::Error>
+    where
+        O: ?Sized + Output,
+    {
+        if let Some(id) = prefix {
+            // Tags and anchors are always emitted, since they are significant
+            // even if the value they are associated with is empty.
+            for property in properties(data, id).into_iter().flatten() {
+                o.write_bytes(property)?;
+            }
+
+            if self.has_prefix(data, id) {
+                o.write_bytes(data.prefix(id))?;
+            }
+        }
+
         match self {
             Raw::Null(raw) => {
-                raw.display(data, f, prefix)?;
+                raw.write_to(data, o)?;
             }
             Raw::Boolean(raw) => {
-                if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
-                }
-
-                raw.display(data, f)?;
+                raw.write_to(data, o)?;
             }
             Raw::Number(raw) => {
-                if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
-                }
-
-                raw.display(data, f)?;
+                raw.write_to(data, o)?;
             }
             Raw::String(raw) => {
-                if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
-                }
-
-                raw.display(data, f)?;
+                raw.write_to(data, o)?;
             }
             Raw::Mapping(raw) => {
-                raw.display(data, f, prefix)?;
+                raw.write_to(data, o)?;
             }
             Raw::MappingItem(raw) => {
-                if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
-                }
-
-                raw.display(data, f)?;
+                raw.write_to(data, o)?;
             }
             Raw::Sequence(raw) => {
-                raw.display(data, f, prefix)?;
+                raw.write_to(data, o)?;
             }
             Raw::SequenceItem(raw) => {
-                if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
-                }
+                raw.write_to(data, o)?;
+            }
+            Raw::Alias(raw) => {
+                raw.write_to(data, o)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Test if the prefix of the value with the given identifier should be
+    /// emitted.
+    ///
+    /// Empty values and empty tabular collections don't emit their prefix,
+    /// since that would only produce trailing whitespace. Unless they are
+    /// inside of an inline collection, where the prefix is used to space out
+    /// the empty slot.
+    pub(crate) fn has_prefix(&self, data: &Data, id: Id) -> bool {
+        match self {
+            Raw::Null(Null::Empty) => is_inline_item(data, id),
+            Raw::Mapping(Mapping {
+                kind: MappingKind::Mapping,
+                items,
+                ..
+            })
+            | Raw::Sequence(Sequence {
+                kind: SequenceKind::Mapping,
+                items,
+                ..
+            }) => !items.is_empty(),
+            _ => true,
+        }
+    }
+
+    pub(crate) fn is_tabular(&self) -> bool {
+        matches!(
+            self,
+            Self::Sequence(Sequence {
+                kind: SequenceKind::Mapping,
+                ..
+            }) | Self::Mapping(Mapping {
+                kind: MappingKind::Mapping,
+                ..
+            })
+        )
+    }
+}
+
+macro_rules! from {
+    ($ident:ident) => {
+        impl From<$ident> for Raw {
+            #[inline]
+            fn from(value: $ident) -> Self {
+                Raw::$ident(value)
+            }
+        }
+    };
+}
+
+from!(Mapping);
+from!(MappingItem);
+from!(Sequence);
+from!(SequenceItem);
+from!(Alias);
+
+/// Test if the string contains control characters, which can't be represented
+/// in a single-quoted string.
+///
+/// Tabs are the exception, since they can be written as they are.
+pub(crate) fn has_control(string: &[u8]) -> bool {
+    string.chars().any(|c| c != '\t' && c.is_control())
+}
+
+/// Get the comment which trails the value with the given identifier on the
+/// same line, not including the leading `#` or surrounding whitespace.
+///
+/// Comments are stored as part of the whitespace which follows a value, so
+/// this looks at the prefix of whatever comes next. For block collections it
+/// is the line which opens the collection that is inspected, since everything
+/// after it belongs to the nested items.
+pub(crate) fn trailing_comment(data: &Data, id: Id) -> Option<&BStr> {
+    let following = match data.raw(id) {
+        Raw::Mapping(Mapping {
+            kind: MappingKind::Mapping,
+            items,
+            ..
+        })
+        | Raw::Sequence(Sequence {
+            kind: SequenceKind::Mapping,
+            items,
+            ..
+        }) => {
+            let prefix = data.prefix(id);
+
+            if prefix.contains(&NEWLINE) {
+                prefix
+            } else {
+                data.prefix(*items.first()?)
+            }
+        }
+        _ => following(data, id)?,
+    };
+
+    let line = following.lines().next()?;
+    let comment = line.trim_start_with(|c| matches!(c, ' ' | '\t'));
+    let comment = comment.strip_prefix(b"#")?;
+    Some(comment.trim().as_bstr())
+}
+
+/// Where the whitespace which immediately follows a value is stored.
+#[derive(Clone, Copy)]
+pub(crate) enum Following {
+    /// The prefix of the next item in document order.
+    Prefix(Id),
+    /// The suffix of the inline collection with the given identifier.
+    Suffix(Id),
+    /// The suffix of the document.
+    Document,
+}
+
+impl Following {
+    /// Locate the whitespace which immediately follows the value with the
+    /// given identifier.
+    pub(crate) fn locate(data: &Data, mut id: Id) -> Option<Self> {
+        loop {
+            let Some(item) = data.layout(id).parent else {
+                return Some(Self::Document);
+            };
+
+            let container = data.layout(item).parent?;
+
+            let (items, inline) = match data.raw(container) {
+                Raw::Mapping(raw) => (&raw.items, matches!(raw.kind, MappingKind::Inline { .. })),
+                Raw::Sequence(raw) => (&raw.items, matches!(raw.kind, SequenceKind::Inline { .. })),
+                _ => return None,
+            };
+
+            let index = items.iter().position(|i| *i == item)?;
+
+            if let Some(next) = items.get(index.wrapping_add(1)) {
+                return Some(Self::Prefix(*next));
+            }
+
+            if inline {
+                return Some(Self::Suffix(container));
+            }
+
+            id = container;
+        }
+    }
+
+    /// Get the whitespace.
+    pub(crate) fn get(self, data: &Data) -> &BStr {
+        match self {
+            Self::Prefix(id) => data.prefix(id),
+            Self::Suffix(id) => match data.raw(id) {
+                Raw::Mapping(Mapping {
+                    kind: MappingKind::Inline { suffix, .. },
+                    ..
+                })
+                | Raw::Sequence(Sequence {
+                    kind: SequenceKind::Inline { suffix, .. },
+                    ..
+                }) => data.str(*suffix),
+                _ => BStr::new(b""),
+            },
+            Self::Document => data.suffix(),
+        }
+    }
+
+    /// Replace the whitespace.
+    pub(crate) fn set(self, data: &mut Data, string: StringId) {
+        match self {
+            Self::Prefix(id) => data.set_prefix(id, string),
+            Self::Suffix(id) => match data.raw_mut(id) {
+                Raw::Mapping(Mapping {
+                    kind: MappingKind::Inline { suffix, .. },
+                    ..
+                })
+                | Raw::Sequence(Sequence {
+                    kind: SequenceKind::Inline { suffix, .. },
+                    ..
+                }) => *suffix = string,
+                _ => {}
+            },
+            Self::Document => data.set_suffix(string),
+        }
+    }
+}
+
+/// Get the whitespace which immediately follows the value with the given
+/// identifier, which is the prefix of the next item in document order.
+fn following(data: &Data, id: Id) -> Option<&BStr> {
+    Some(Following::locate(data, id)?.get(data))
+}
+
+/// Give the item with the given identifier, which takes the place of the
+/// removed first item of a collection, the `prefix` of the removed item.
+///
+/// Only the rest of the line of the removed item is replaced, such as its
+/// trailing comment, so that lines preceding the new first item like
+/// full-line comments are kept.
+pub(crate) fn inherit_first_prefix(data: &mut Data, id: Id, prefix: StringId) {
+    let existing = data.prefix(id);
+
+    let Some(n) = existing.find_byte(NEWLINE) else {
+        data.set_prefix(id, prefix);
+        return;
+    };
+
+    // Blank lines separating the new first item from the removed one are
+    // dropped along with it.
+    let rest = existing[n.saturating_add(1)..].trim_start();
+
+    let mut new = data.str(prefix).to_vec();
+    new.extend_from_slice(rest);
+    let new = data.insert_str(new);
+    data.set_prefix(id, new);
+}
+
+/// Detach the rest of the line which follows the last item of the block
+/// collection with the given identifier, such as a trailing comment, so that
+/// it can be put in front of a new item appended to the collection.
+///
+/// Returns `None` if the collection is empty or not a block collection.
+pub(crate) fn take_rest_of_line(data: &mut Data, id: Id) -> Option<Vec<u8>> {
+    let last = match data.raw(id) {
+        Raw::Mapping(Mapping {
+            kind: MappingKind::Mapping,
+            items,
+            ..
+        }) => data.mapping_item(*items.last()?).value,
+        Raw::Sequence(Sequence {
+            kind: SequenceKind::Mapping,
+            items,
+            ..
+        }) => data.sequence_item(*items.last()?).value,
+        _ => return None,
+    };
+
+    let following = Following::locate(data, last)?;
+    let string = following.get(data);
+
+    let n = string.find_byte(NEWLINE).unwrap_or(string.len());
+
+    if n == 0 {
+        return Some(Vec::new());
+    }
+
+    let (line, rest) = string.split_at(n);
+    let (line, rest) = (line.to_vec(), rest.to_vec());
+    let rest = data.insert_str(rest);
+    following.set(data, rest);
+    Some(line)
+}
+
+/// Test if the value with the given identifier is an inline collection.
+pub(crate) fn is_inline(data: &Data, id: Id) -> bool {
+    matches!(
+        data.raw(id),
+        Raw::Mapping(Mapping {
+            kind: MappingKind::Inline { .. },
+            ..
+        }) | Raw::Sequence(Sequence {
+            kind: SequenceKind::Inline { .. },
+            ..
+        })
+    )
+}
+
+/// Test if the value with the given identifier is an empty null which wasn't
+/// parsed.
+///
+/// Such values are written as `null` in inline sequences, since an empty slot
+/// like in `[1, , 2]` isn't valid YAML and one at the end like in `[1, ]`
+/// reads back as a trailing comma. Empty slots which were parsed are kept as
+/// they are.
+fn is_unparsed_empty(data: &Data, id: Id) -> bool {
+    matches!(data.raw(id), Raw::Null(Null::Empty)) && !data.is_parsed(id)
+}
+
+/// Test if the value with the given identifier is an item inside of an inline
+/// collection.
+pub(crate) fn is_inline_item(data: &Data, id: Id) -> bool {
+    let Some(container) = data.layout(id).parent.and_then(|id| data.layout(id).parent) else {
+        return false;
+    };
+
+    matches!(
+        data.raw(container),
+        Raw::Mapping(Mapping {
+            kind: MappingKind::Inline { .. },
+            ..
+        }) | Raw::Sequence(Sequence {
+            kind: SequenceKind::Inline { .. },
+            ..
+        })
+    )
+}
+
+/// An output which raw values are written to.
+///
+/// This is what lets formatting through [`fmt::Display`] and writing through
+/// [`io::Write`] share a single implementation, using the [`Fmt`] and [`Io`]
+/// adapters respectively.
+pub(crate) trait Output {
+    /// The error raised when writing fails.
+    type Error;
+
+    /// Write raw bytes. Outputs which can only hold strings write invalid
+    /// UTF-8 using replacement characters.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Write formatted arguments, which allows the output to be used with
+    /// [`write!`].
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<(), Self::Error>;
+
+    /// Test if non-ASCII characters in double-quoted strings should be
+    /// escaped.
+    #[inline]
+    fn escape_unicode(&self) -> bool {
+        false
+    }
+}
+
+/// Adapter for writing to a [`fmt::Write`], like a [`fmt::Formatter`].
+pub(crate) struct Fmt<'a, W: ?Sized>(pub(crate) &'a mut W);
+
+impl<W> Output for Fmt<'_, W>
+where
+    W: ?Sized + fmt::Write,
+{
+    type Error = fmt::Error;
+
+    #[inline]
+    fn write_bytes(&mut self, bytes: &[u8]) -> fmt::Result {
+        write!(self.0, "{}", bytes.as_bstr())
+    }
+
+    #[inline]
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        self.0.write_fmt(args)
+    }
+}
 
-                raw.display(data, f)?;
-            }
-        }
+/// Adapter for writing to an [`io::Write`].
+pub(crate) struct Io<'a, W: ?Sized>(pub(crate) &'a mut W);
 
-        Ok(())
+impl<W> Output for Io<'_, W>
+where
+    W: ?Sized + io::Write,
+{
+    type Error = io::Error;
+
+    #[inline]
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.0.write_all(bytes)
     }
 
-    pub(crate) fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
-    where
-        O: ?Sized + io::Write,
-    {
-        match self {
-            Raw::Null(raw) => {
-                raw.write_to(data, o)?;
-            }
-            Raw::Boolean(raw) => {
-                raw.write_to(data, o)?;
-            }
-            Raw::Number(raw) => {
-                raw.write_to(data, o)?;
-            }
-            Raw::String(raw) => {
-                raw.write_to(data, o)?;
-            }
-            Raw::Mapping(raw) => {
-                raw.write_to(data, o)?;
-            }
-            Raw::MappingItem(raw) => {
-                raw.write_to(data, o)?;
-            }
-            Raw::Sequence(raw) => {
-                raw.write_to(data, o)?;
-            }
-            Raw::SequenceItem(raw) => {
-                raw.write_to(data, o)?;
-            }
-        }
+    #[inline]
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> io::Result<()> {
+        self.0.write_fmt(args)
+    }
+}
 
-        Ok(())
+/// Adapter which escapes non-ASCII characters in double-quoted strings written
+/// to the wrapped output.
+pub(crate) struct EscapeUnicode<'a, O: ?Sized>(pub(crate) &'a mut O);
+
+impl<O> Output for EscapeUnicode<'_, O>
+where
+    O: ?Sized + Output,
+{
+    type Error = O::Error;
+
+    #[inline]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.write_bytes(bytes)
     }
 
-    pub(crate) fn is_tabular(&self) -> bool {
-        matches!(
-            self,
-            Self::Sequence(Sequence {
-                kind: SequenceKind::Mapping,
-                ..
-            }) | Self::Mapping(Mapping {
-                kind: MappingKind::Mapping,
-                ..
-            })
-        )
+    #[inline]
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<(), Self::Error> {
+        self.0.write_fmt(args)
+    }
+
+    #[inline]
+    fn escape_unicode(&self) -> bool {
+        true
     }
 }
 
-macro_rules! from {
-    ($ident:ident) => {
-        impl From<$ident> for Raw {
-            #[inline]
-            fn from(value: $ident) -> Self {
-                Raw::$ident(value)
-            }
-        }
-    };
+/// A YAML alias like `*name`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
+pub(crate) struct Alias {
+    /// The name of the anchor being referred to, without the leading `*`.
+    pub(crate) name: StringId,
 }
 
-from!(Mapping);
-from!(MappingItem);
-from!(Sequence);
-from!(SequenceItem);
+impl Alias {
+    fn write_to<O>(&self, data: &Data, o: &mut O) -> Result<(), O::Error>
+    where
+        O: ?Sized + Output,
+    {
+        o.write_bytes(b"*")?;
+        o.write_bytes(data.str(self.name))
+    }
+}
 
 /// A YAML null.
 #[derive(Debug, Clone)]
@@ -351,45 +1798,16 @@ pub(crate) enum Null {
 }
 
 impl Null {
-    pub(crate) fn display(
-        &self,
-        data: &Data,
-        f: &mut fmt::Formatter<'_>,
-        prefix: Option<Id>,
-    ) -> fmt::Result {
-        match self {
-            Null::Keyword(string) => {
-                if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
-                }
-
-                write!(f, "{}", data.str(*string))?;
-            }
-            Null::Tilde => {
-                if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
-                }
-
-                write!(f, "~")?;
-            }
-            Null::Empty => {
-                // empty values count as null.
-            }
-        }
-
-        Ok(())
-    }
-
-    pub(crate) fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    fn write_to<O>(&self, data: &Data, o: &mut O) -> Result<(), O::Error>
     where
-        O: ?Sized + io::Write,
+        O: ?Sized + Output,
     {
         match self {
             Null::Keyword(string) => {
-                o.write_all(data.str(*string))?;
+                o.write_bytes(data.str(*string))?;
             }
             Null::Tilde => {
-                write!(o, "~")?;
+                o.write_bytes(b"~")?;
             }
             Null::Empty => {
                 // empty values count as null.
@@ -414,16 +1832,11 @@ impl Boolean {
     }
 
     #[inline]
-    fn display(&self, data: &Data, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", data.str(self.string))
-    }
-
-    #[inline]
-    pub(crate) fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    fn write_to<O>(&self, data: &Data, o: &mut O) -> Result<(), O::Error>
     where
-        O: ?Sized + io::Write,
+        O: ?Sized + Output,
     {
-        o.write_all(data.str(self.string))
+        o.write_bytes(data.str(self.string))
     }
 }
 
@@ -443,16 +1856,11 @@ impl Number {
     }
 
     #[inline]
-    fn display(&self, data: &Data, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", data.str(self.string))
-    }
-
-    #[inline]
-    pub(crate) fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    fn write_to<O>(&self, data: &Data, o: &mut O) -> Result<(), O::Error>
     where
-        O: ?Sized + io::Write,
+        O: ?Sized + Output,
     {
-        o.write_all(data.str(self.string))
+        o.write_bytes(data.str(self.string))
     }
 }
 
@@ -470,14 +1878,15 @@ pub(crate) enum RawStringKind {
     Double,
     /// An escaped string, where the string id points to the original string.
     Original,
-    /// A multiline string.
-    Multiline { prefix: StringId },
+    /// A multiline block string, where the string id points to the original
+    /// string including the block indicator.
+    Multiline,
 }
 
 impl RawStringKind {
     /// Detect the appropriate kind to use for the given string.
     pub(crate) fn detect(string: &str) -> RawStringKind {
-        if matches!(string, "true" | "false" | "null") {
+        if matches!(string, "" | "true" | "false" | "null") {
             return RawStringKind::Single;
         }
 
@@ -494,9 +1903,6 @@ impl RawStringKind {
                 '\'' => {
                     return RawStringKind::Double;
                 }
-                ':' => {
-                    kind = RawStringKind::Single;
-                }
                 b if b.is_control() => {
                     return RawStringKind::Double;
                 }
@@ -508,6 +1914,97 @@ impl RawStringKind {
     }
 }
 
+/// Test if the given string has to be quoted to be written as a string, as
+/// opposed to being written bare.
+///
+/// This builds on the rules used by [`RawStringKind::detect`], but is more
+/// thorough. If `inline` is set, the string is tested as if it is written
+/// inside of an inline collection where the `,[]{}` indicators also are
+/// significant.
+pub(crate) fn requires_quoting(data: &Data, string: &str, inline: bool) -> bool {
+    if !matches!(RawStringKind::detect(string), RawStringKind::Bare) {
+        return true;
+    }
+
+    if is_yaml_1_1_bool(data, string) {
+        return true;
+    }
+
+    let Some(first) = string.chars().next() else {
+        return true;
+    };
+
+    // Words which would be interpreted as something other than a string.
+    if matches!(
+        string,
+        "~" | "True" | "TRUE" | "False" | "FALSE" | "Null" | "NULL"
+    ) || SpecialFloat::detect(string.as_bytes()).is_some()
+    {
+        return true;
+    }
+
+    let second = string.chars().nth(1);
+
+    // Values which look like numbers, like `-1`, `+1` or `.5`.
+    if matches!(first, '-' | '+' | '.') && second.is_some_and(|c| c.is_ascii_digit()) {
+        return true;
+    }
+
+    if first.is_whitespace() || string.ends_with(char::is_whitespace) {
+        return true;
+    }
+
+    if matches!(
+        first,
+        ',' | '[' | ']' | '{' | '}' | '#' | '&' | '*' | '!' | '|' | '>' | '"' | '%' | '@' | '`'
+    ) {
+        return true;
+    }
+
+    if matches!(first, '-' | '?') && second.map_or(true, char::is_whitespace) {
+        return true;
+    }
+
+    if string.contains(" #") {
+        return true;
+    }
+
+    // A `:` followed by whitespace or the end of the string would start a
+    // mapping, while one followed by anything else is part of the string.
+    if string.ends_with(':') || string.contains(": ") || string.contains(":\t") {
+        return true;
+    }
+
+    inline && string.contains([',', '[', ']', '{', '}', ':'])
+}
+
+/// Test if the given string is one of the YAML 1.1 spellings of booleans,
+/// which have to be quoted if the document recognizes them.
+///
+/// This covers every spelling in the YAML 1.1 specification, including `y`
+/// and `n` which aren't parsed as booleans by this crate but are by others.
+fn is_yaml_1_1_bool(data: &Data, string: &str) -> bool {
+    data.yaml_1_1_bools()
+        && matches!(
+            string,
+            "y" | "Y"
+                | "yes"
+                | "Yes"
+                | "YES"
+                | "n"
+                | "N"
+                | "no"
+                | "No"
+                | "NO"
+                | "on"
+                | "On"
+                | "ON"
+                | "off"
+                | "Off"
+                | "OFF"
+        )
+}
+
 /// A YAML string.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
@@ -526,129 +2023,63 @@ impl String {
         Self { kind, id, original }
     }
 
-    fn display(&self, data: &Data, f: &mut fmt::Formatter) -> fmt::Result {
-        /// Single-quoted escape sequences:
-        /// <https://yaml.org/spec/1.2.2/#escaped-characters>.
-        fn escape_single_quoted(mut string: &bstr::BStr, f: &mut fmt::Formatter) -> fmt::Result {
-            f.write_char('\'')?;
-
-            loop {
-                let Some(n) = memchr::memchr(b'\'', string) else {
-                    write!(f, "{string}")?;
-                    break;
-                };
-
-                write!(f, "{}", &string[..n])?;
-                f.write_str("''")?;
-                string = &string[n.saturating_add(1)..];
-            }
-
-            f.write_char('\'')?;
-            Ok(())
-        }
-
-        /// Double-quoted escape sequences:
-        /// <https://yaml.org/spec/1.2.2/#escaped-characters>.
-        fn escape_double_quoted(string: &bstr::BStr, f: &mut fmt::Formatter) -> fmt::Result {
-            f.write_char('"')?;
-
-            let mut start = 0;
-
-            for (index, end, c) in string.char_indices() {
-                let esc = match c {
-                    '\u{00}' => "\\0",
-                    '\u{07}' => "\\a",
-                    '\u{08}' => "\\b",
-                    '\u{09}' => "\\t",
-                    '\n' => "\\n",
-                    '\u{0b}' => "\\v",
-                    '\u{0c}' => "\\f",
-                    '\r' => "\\r",
-                    '\u{1b}' => "\\e",
-                    '\"' => "\\\"",
-                    c if c.is_ascii_control() => {
-                        write!(f, "{}\\x{:02x}", &string[start..index], c as u8)?;
-                        start = end;
-                        continue;
-                    }
-                    _ => {
-                        continue;
-                    }
-                };
-
-                write!(f, "{}{esc}", &string[start..index])?;
-                start = end;
-            }
-
-            write!(f, "{}", &string[start..])?;
-            f.write_char('"')?;
-            Ok(())
-        }
-
-        match &self.kind {
-            RawStringKind::Bare => {
-                let string = data.str(self.id);
-                write!(f, "{string}")?;
-            }
-            RawStringKind::Double => {
-                let string = data.str(self.id);
-                escape_double_quoted(string, f)?;
-            }
-            RawStringKind::Single => {
-                let string = data.str(self.id);
-                escape_single_quoted(string, f)?;
-            }
-            RawStringKind::Original => {
-                let string = data.str(self.original);
-                write!(f, "{string}")?;
-            }
-            RawStringKind::Multiline { prefix } => {
-                let string = data.str(self.original);
-                write!(f, "{}{string}", data.str(*prefix))?;
-            }
+    /// Get the quote character used by this string, if any.
+    pub(crate) fn quote(&self, data: &Data) -> Option<u8> {
+        match self.kind {
+            RawStringKind::Single => Some(b'\''),
+            RawStringKind::Double => Some(b'"'),
+            RawStringKind::Original => match data.str(self.original).first() {
+                Some(&b @ (b'\'' | b'"')) => Some(b),
+                _ => None,
+            },
+            _ => None,
         }
-
-        Ok(())
     }
 
-    fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    fn write_to<O>(&self, data: &Data, o: &mut O) -> Result<(), O::Error>
     where
-        O: ?Sized + io::Write,
+        O: ?Sized + Output,
     {
         /// Single-quoted escape sequences:
         /// <https://yaml.org/spec/1.2.2/#escaped-characters>.
-        fn escape_single_quoted<O>(mut string: &bstr::BStr, f: &mut O) -> io::Result<()>
+        fn escape_single_quoted<O>(mut string: &bstr::BStr, o: &mut O) -> Result<(), O::Error>
         where
-            O: ?Sized + io::Write,
+            O: ?Sized + Output,
         {
-            f.write_all(b"\'")?;
+            o.write_bytes(b"\'")?;
 
             loop {
                 let Some(index) = memchr::memchr(b'\'', string) else {
-                    f.write_all(string)?;
+                    o.write_bytes(string)?;
                     break;
                 };
 
-                f.write_all(&string[..index])?;
-                f.write_all(b"''")?;
+                o.write_bytes(&string[..index])?;
+                o.write_bytes(b"''")?;
                 string = &string[index.saturating_add(1)..];
             }
 
-            f.write_all(b"\'")?;
+            o.write_bytes(b"\'")?;
             Ok(())
         }
 
         /// Double-quoted escape sequences:
         /// <https://yaml.org/spec/1.2.2/#escaped-characters>.
-        fn escape_double_quoted<O>(string: &bstr::BStr, o: &mut O) -> io::Result<()>
+        fn escape_double_quoted<O>(
+            string: &bstr::BStr,
+            unicode: bool,
+            o: &mut O,
+        ) -> Result<(), O::Error>
         where
-            O: ?Sized + io::Write,
+            O: ?Sized + Output,
         {
-            o.write_all(b"\"")?;
+            o.write_bytes(b"\"")?;
             let mut s = 0;
 
             for (index, b) in string.bytes().enumerate() {
                 let esc = match b {
+                    // Continuation bytes of an already escaped character.
+                    _ if index < s => continue,
                     b'\0' => b"\\0",
                     0x07 => b"\\a",
                     0x08 => b"\\b",
@@ -658,46 +2089,60 @@ impl String {
                     0x0c => b"\\f",
                     b'\r' => b"\\r",
                     0x1b => b"\\e",
+                    b'\\' => b"\\\\",
                     b'\"' => b"\\\"",
                     c if c.is_ascii_control() => {
-                        o.write_all(&string[s..index])?;
+                        o.write_bytes(&string[s..index])?;
                         write!(o, "\\x{c:02x}")?;
                         s = index.saturating_add(1);
                         continue;
                     }
+                    b if unicode && !b.is_ascii() => {
+                        let (Some(c), len) = bstr::decode_utf8(&string[index..]) else {
+                            continue;
+                        };
+
+                        o.write_bytes(&string[s..index])?;
+                        write!(o, "{}", UnicodeEscape(c))?;
+                        s = index.saturating_add(len);
+                        continue;
+                    }
                     _ => {
                         continue;
                     }
                 };
 
-                o.write_all(&string[s..index])?;
-                o.write_all(esc)?;
+                o.write_bytes(&string[s..index])?;
+                o.write_bytes(esc)?;
                 s = index.saturating_add(1);
             }
 
-            o.write_all(&string[s..])?;
-            o.write_all(b"\"")?;
+            o.write_bytes(&string[s..])?;
+            o.write_bytes(b"\"")?;
             Ok(())
         }
 
         match &self.kind {
             RawStringKind::Bare => {
-                o.write_all(data.str(self.id))?;
+                o.write_bytes(data.str(self.id))?;
             }
             RawStringKind::Double => {
                 let string = data.str(self.id);
-                escape_double_quoted(string, o)?;
+                escape_double_quoted(string, o.escape_unicode(), o)?;
             }
             RawStringKind::Single => {
                 let string = data.str(self.id);
-                escape_single_quoted(string, o)?;
-            }
-            RawStringKind::Original => {
-                o.write_all(data.str(self.original))?;
+
+                // Single-quoted strings can't represent control characters,
+                // so those have to be promoted to double-quoted strings.
+                if has_control(string) {
+                    escape_double_quoted(string, o.escape_unicode(), o)?;
+                } else {
+                    escape_single_quoted(string, o)?;
+                }
             }
-            RawStringKind::Multiline { prefix } => {
-                o.write_all(data.str(*prefix))?;
-                o.write_all(data.str(self.original))?;
+            RawStringKind::Original | RawStringKind::Multiline => {
+                o.write_bytes(data.str(self.original))?;
             }
         }
 
@@ -705,6 +2150,20 @@ impl String {
     }
 }
 
+/// Escape a non-ASCII character in a double-quoted string using the shortest
+/// of the `\x`, `\u` and `\U` escape sequences.
+struct UnicodeEscape(char);
+
+impl fmt::Display for UnicodeEscape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match u32::from(self.0) {
+            n @ ..=0xff => write!(f, "\\x{n:02x}"),
+            n @ ..=0xffff => write!(f, "\\u{n:04x}"),
+            n => write!(f, "\\U{n:08x}"),
+        }
+    }
+}
+
 /// The kind of a raw sequence.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
@@ -744,85 +2203,48 @@ pub(crate) struct Sequence {
 }
 
 impl Sequence {
-    /// Display the sequence.
-    pub(crate) fn display(
-        &self,
-        data: &Data,
-        f: &mut fmt::Formatter,
-        prefix: Option<Id>,
-    ) -> fmt::Result {
-        if matches!(self.kind, SequenceKind::Inline { .. }) || !self.items.is_empty() {
-            if let Some(id) = prefix {
-                write!(f, "{}", data.prefix(id))?;
-            }
-        }
-
+    /// Write the sequence to the given output.
+    pub(crate) fn write_to<O>(&self, data: &Data, o: &mut O) -> Result<(), O::Error>
+    where
+        O: ?Sized + Output,
+    {
         if let SequenceKind::Inline { .. } = &self.kind {
-            write!(f, "[")?;
+            o.write_bytes(b"[")?;
         }
 
         let mut it = self.items.iter().peekable();
 
         while let Some(item) = it.next() {
-            write!(f, "{}", data.prefix(*item))?;
+            o.write_bytes(data.prefix(*item))?;
 
             if let SequenceKind::Mapping = self.kind {
-                write!(f, "-")?;
-            }
-
-            data.sequence_item(*item).display(data, f)?;
-
-            if it.peek().is_some() {
-                if let SequenceKind::Inline { .. } = self.kind {
-                    write!(f, ",")?;
-                }
-            }
-        }
-
-        if let SequenceKind::Inline { trailing, suffix } = &self.kind {
-            if *trailing {
-                write!(f, ",")?;
+                o.write_bytes(b"-")?;
             }
 
-            write!(f, "{}]", data.str(*suffix))?;
-        }
-
-        Ok(())
-    }
-
-    fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
-    where
-        O: ?Sized + io::Write,
-    {
-        if let SequenceKind::Inline { .. } = &self.kind {
-            write!(o, "[")?;
-        }
-
-        let mut it = self.items.iter().peekable();
-
-        while let Some(item) = it.next() {
-            write!(o, "{}", data.prefix(*item))?;
+            let item = data.sequence_item(*item);
 
-            if let SequenceKind::Mapping = self.kind {
-                write!(o, "-")?;
+            if matches!(self.kind, SequenceKind::Inline { .. })
+                && is_unparsed_empty(data, item.value)
+            {
+                o.write_bytes(b"null")?;
+            } else {
+                item.write_to(data, o)?;
             }
 
-            data.sequence_item(*item).write_to(data, o)?;
+            if let SequenceKind::Inline { trailing, .. } = self.kind {
+                if it.peek().is_some() || trailing {
+                    if let Some(suffix) = item.suffix {
+                        o.write_bytes(data.str(suffix))?;
+                    }
 
-            if it.peek().is_some() {
-                if let SequenceKind::Inline { .. } = self.kind {
-                    write!(o, ",")?;
+                    o.write_bytes(b",")?;
                 }
             }
         }
 
-        if let SequenceKind::Inline { trailing, suffix } = &self.kind {
-            if *trailing {
-                write!(o, ",")?;
-            }
-
-            o.write_all(data.str(*suffix))?;
-            write!(o, "]")?;
+        if let SequenceKind::Inline { suffix, .. } = &self.kind {
+            o.write_bytes(data.str(*suffix))?;
+            o.write_bytes(b"]")?;
         }
 
         Ok(())
@@ -834,21 +2256,17 @@ impl Sequence {
 #[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
 pub(crate) struct SequenceItem {
     pub(crate) value: Id,
+    /// Whitespace following the value in an inline sequence, before the `,`
+    /// separator.
+    pub(crate) suffix: Option<StringId>,
 }
 
 impl SequenceItem {
-    fn display(&self, data: &Data, f: &mut fmt::Formatter) -> fmt::Result {
-        data.raw(self.value).display(data, f, Some(self.value))?;
-        Ok(())
-    }
-
-    fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    fn write_to<O>(&self, data: &Data, o: &mut O) -> Result<(), O::Error>
     where
-        O: ?Sized + io::Write,
+        O: ?Sized + Output,
     {
-        o.write_all(data.prefix(self.value))?;
-        data.raw(self.value).write_to(data, o)?;
-        Ok(())
+        data.raw(self.value).write_to(data, o, Some(self.value))
     }
 }
 
@@ -890,77 +2308,36 @@ pub(crate) struct Mapping {
 }
 
 impl Mapping {
-    /// Display the mapping.
-    pub(crate) fn display(
-        &self,
-        data: &Data,
-        f: &mut fmt::Formatter,
-        prefix: Option<Id>,
-    ) -> fmt::Result {
-        if matches!(self.kind, MappingKind::Inline { .. }) || !self.items.is_empty() {
-            if let Some(id) = prefix {
-                write!(f, "{}", data.prefix(id))?;
-            }
-        }
-
-        if let MappingKind::Inline { .. } = &self.kind {
-            write!(f, "{{")?;
-        }
-
-        let mut it = self.items.iter().peekable();
-
-        while let Some(id) = it.next() {
-            let item = data.mapping_item(*id);
-            write!(f, "{}", data.prefix(*id))?;
-            item.display(data, f)?;
-
-            if it.peek().is_some() {
-                if let MappingKind::Inline { .. } = &self.kind {
-                    write!(f, ",")?;
-                }
-            }
-        }
-
-        if let MappingKind::Inline { trailing, suffix } = &self.kind {
-            if *trailing {
-                write!(f, ",")?;
-            }
-
-            let suffix = data.str(*suffix);
-            write!(f, "{suffix}}}")?;
-        }
-
-        Ok(())
-    }
-
-    fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    /// Write the mapping to the given output.
+    pub(crate) fn write_to<O>(&self, data: &Data, o: &mut O) -> Result<(), O::Error>
     where
-        O: ?Sized + io::Write,
+        O: ?Sized + Output,
     {
         if let MappingKind::Inline { .. } = &self.kind {
-            write!(o, "{{")?;
+            o.write_bytes(b"{")?;
         }
 
         let mut it = self.items.iter().peekable();
 
         while let Some(id) = it.next() {
-            o.write_all(data.prefix(*id))?;
-            data.mapping_item(*id).write_to(data, o)?;
+            let item = data.mapping_item(*id);
+            o.write_bytes(data.prefix(*id))?;
+            item.write_to(data, o)?;
 
-            if it.peek().is_some() {
-                if let MappingKind::Inline { .. } = &self.kind {
-                    write!(o, ",")?;
+            if let MappingKind::Inline { trailing, .. } = self.kind {
+                if it.peek().is_some() || trailing {
+                    if let Some(suffix) = item.suffix {
+                        o.write_bytes(data.str(suffix))?;
+                    }
+
+                    o.write_bytes(b",")?;
                 }
             }
         }
 
-        if let MappingKind::Inline { trailing, suffix } = &self.kind {
-            if *trailing {
-                write!(o, ",")?;
-            }
-
-            o.write_all(data.str(*suffix))?;
-            write!(o, "}}")?;
+        if let MappingKind::Inline { suffix, .. } = &self.kind {
+            o.write_bytes(data.str(*suffix))?;
+            o.write_bytes(b"}")?;
         }
 
         Ok(())
@@ -973,24 +2350,18 @@ impl Mapping {
 pub(crate) struct MappingItem {
     pub(crate) key: String,
     pub(crate) value: Id,
+    /// Whitespace following the value in an inline mapping, before the `,`
+    /// separator.
+    pub(crate) suffix: Option<StringId>,
 }
 
 impl MappingItem {
-    fn display(&self, data: &Data, f: &mut fmt::Formatter) -> fmt::Result {
-        self.key.display(data, f)?;
-        write!(f, ":")?;
-        data.raw(self.value).display(data, f, Some(self.value))?;
-        Ok(())
-    }
-
-    fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    fn write_to<O>(&self, data: &Data, o: &mut O) -> Result<(), O::Error>
     where
-        O: ?Sized + io::Write,
+        O: ?Sized + Output,
     {
         self.key.write_to(data, o)?;
-        write!(o, ":")?;
-        o.write_all(data.prefix(self.value))?;
-        data.raw(self.value).write_to(data, o)?;
-        Ok(())
+        o.write_bytes(b":")?;
+        data.raw(self.value).write_to(data, o, Some(self.value))
     }
 }
@@ -0,0 +1,152 @@
+use crate::yaml::data::Data;
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::serde_hint::{self, RawNumberHint};
+
+/// An owned scalar value which can be inserted into a document.
+///
+/// This is constructed through one of its [`From`] implementations, and is used
+/// by methods which accept values of any scalar type such as
+/// [`MappingMut::extend`].
+///
+/// Strings are quoted as necessary when they are inserted, and [`None`] is
+/// inserted as a `null`.
+///
+/// [`MappingMut::extend`]: crate::yaml::MappingMut::extend
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut doc = yaml::from_slice("name: web")?;
+///
+/// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+///
+/// root.extend([
+///     ("image", yaml::Scalar::from("nginx:1.25")),
+///     ("replicas", yaml::Scalar::from(3u32)),
+///     ("enabled", yaml::Scalar::from(true)),
+///     ("command", yaml::Scalar::from(None::<&str>)),
+/// ]);
+///
+/// assert_eq!(
+///     doc.to_string(),
+///     "name: web\nimage: nginx:1.25\nreplicas: 3\nenabled: true\ncommand: null"
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scalar {
+    kind: ScalarKind,
+}
+
+#[derive(Debug, Clone)]
+enum ScalarKind {
+    Null,
+    Bool(bool),
+    Number(std::string::String, RawNumberHint),
+    String(std::string::String),
+}
+
+impl Scalar {
+    /// Construct the raw value corresponding to this scalar.
+    pub(crate) fn into_raw(self, data: &mut Data) -> Raw {
+        match self.kind {
+            ScalarKind::Null => Raw::Null(raw::Null::Keyword(data.insert_str("null"))),
+            ScalarKind::Bool(value) => raw::new_bool(data, value),
+            ScalarKind::Number(number, hint) => {
+                let number = data.insert_str(number);
+                Raw::Number(raw::Number::new(number, hint))
+            }
+            ScalarKind::String(string) => raw::new_string(data, string),
+        }
+    }
+}
+
+impl From<bool> for Scalar {
+    #[inline]
+    fn from(value: bool) -> Self {
+        Self {
+            kind: ScalarKind::Bool(value),
+        }
+    }
+}
+
+impl From<&str> for Scalar {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self {
+            kind: ScalarKind::String(value.to_owned()),
+        }
+    }
+}
+
+impl From<std::string::String> for Scalar {
+    #[inline]
+    fn from(value: std::string::String) -> Self {
+        Self {
+            kind: ScalarKind::String(value),
+        }
+    }
+}
+
+impl<T> From<Option<T>> for Scalar
+where
+    T: Into<Scalar>,
+{
+    #[inline]
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => Self {
+                kind: ScalarKind::Null,
+            },
+        }
+    }
+}
+
+macro_rules! from_float {
+    ($ty:ty, $hint:ident) => {
+        impl From<$ty> for Scalar {
+            #[inline]
+            fn from(value: $ty) -> Self {
+                let mut buffer = ryu::Buffer::new();
+                let number = raw::format_float(&mut buffer, value).to_owned();
+
+                Self {
+                    kind: ScalarKind::Number(number, serde_hint::$hint),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! from_number {
+    ($ty:ty, $hint:ident) => {
+        impl From<$ty> for Scalar {
+            #[inline]
+            fn from(value: $ty) -> Self {
+                let mut buffer = itoa::Buffer::new();
+                let number = buffer.format(value).to_owned();
+
+                Self {
+                    kind: ScalarKind::Number(number, serde_hint::$hint),
+                }
+            }
+        }
+    };
+}
+
+from_float!(f32, F32);
+from_float!(f64, F64);
+from_number!(u8, U8);
+from_number!(i8, I8);
+from_number!(u16, U16);
+from_number!(i16, I16);
+from_number!(u32, U32);
+from_number!(i32, I32);
+from_number!(u64, U64);
+from_number!(i64, I64);
+from_number!(u128, U128);
+from_number!(i128, I128);
@@ -1,6 +1,10 @@
 use core::fmt;
 
+use bstr::BStr;
+
 use crate::yaml::data::{Data, Id};
+use crate::yaml::eq;
+use crate::yaml::raw;
 use crate::yaml::sequence::Iter;
 use crate::yaml::Value;
 
@@ -245,6 +249,10 @@ impl<'a> Sequence<'a> {
     /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
     ///
     /// assert_eq!(root.first().and_then(|v| v.as_str()), Some("one"));
+    ///
+    /// let doc = yaml::from_slice("[]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// assert!(root.first().is_none());
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[must_use]
@@ -274,6 +282,10 @@ impl<'a> Sequence<'a> {
     /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
     ///
     /// assert_eq!(root.last().and_then(|v| v.as_str()), Some("three"));
+    ///
+    /// let doc = yaml::from_slice("[]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// assert!(root.last().is_none());
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[must_use]
@@ -301,7 +313,9 @@ impl<'a> Sequence<'a> {
     /// )?;
     ///
     /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
-    /// root.iter().flat_map(|v| v.as_str()).eq(["one", "two", "three"]);
+    /// assert!(root.iter().flat_map(|v| v.as_str()).eq(["one", "two", "three"]));
+    /// assert!(root.iter().rev().flat_map(|v| v.as_str()).eq(["three", "two", "one"]));
+    /// assert_eq!(root.iter().len(), 3);
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[must_use]
@@ -309,12 +323,151 @@ impl<'a> Sequence<'a> {
     pub fn iter(&self) -> Iter<'a> {
         Iter::new(self.data, &self.data.sequence(self.id).items)
     }
+
+    /// Get the whitespace padding just inside of the delimiters of an inline
+    /// sequence, like the spaces in `[ 1, 2, 3 ]`.
+    ///
+    /// The leading padding is the whitespace following `[` and the
+    /// trailing padding is the whitespace preceeding `]`. An empty
+    /// sequence only has trailing padding.
+    ///
+    /// Returns `None` if this is not an inline sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[ 1, 2, 3 ]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// assert_eq!(root.padding(), Some((" ".into(), " ".into())));
+    ///
+    /// let doc = yaml::from_slice("[1, 2, 3]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// assert_eq!(root.padding(), Some(("".into(), "".into())));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn padding(&self) -> Option<(&'a BStr, &'a BStr)> {
+        let raw = self.data.sequence(self.id);
+
+        let raw::SequenceKind::Inline { suffix, .. } = &raw.kind else {
+            return None;
+        };
+
+        let leading = match raw.items.first() {
+            Some(item) => self.data.prefix(*item),
+            None => BStr::new(""),
+        };
+
+        Some((leading, self.data.str(*suffix)))
+    }
+
+    /// Test if the sequence contains a string equal to the given one.
+    ///
+    /// Only string values are compared, so `"42"` does not match the number
+    /// `42`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     - one
+    ///     - "two"
+    ///     - 3
+    ///     "#,
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// assert!(root.contains_str("one"));
+    /// assert!(root.contains_str("two"));
+    /// assert!(!root.contains_str("3"));
+    /// assert!(!root.contains_str("four"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn contains_str(&self, s: &str) -> bool {
+        self.iter().any(|v| v.as_bstr() == Some(s.into()))
+    }
+
+    /// Test if the sequence contains a value which is semantically equal to
+    /// the given `value`, as in [`Document::semantic_eq`].
+    ///
+    /// The value might belong to a different document.
+    ///
+    /// [`Document::semantic_eq`]: crate::yaml::Document::semantic_eq
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     - one
+    ///     - {a: 1, b: 2}
+    ///     - [1, 2]
+    ///     "#,
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// let other = yaml::from_slice("b: 2\na: 1\n")?;
+    /// assert!(root.contains(&other.as_ref()));
+    ///
+    /// let other = yaml::from_slice("'one'")?;
+    /// assert!(root.contains(&other.as_ref()));
+    ///
+    /// let other = yaml::from_slice("[2, 1]")?;
+    /// assert!(!root.contains(&other.as_ref()));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn contains(&self, value: &Value<'_>) -> bool {
+        self.data.sequence(self.id).items.iter().any(|item| {
+            let item = self.data.sequence_item(*item).value;
+            eq::semantic_eq(self.data, item, value.data, value.id, eq::Mode::UNORDERED)
+        })
+    }
+
+    /// Test if the sequence contains a number equal to the given signed
+    /// integer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[80, 443, -1, '8080']")?;
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// assert!(root.contains_i64(443));
+    /// assert!(root.contains_i64(-1));
+    /// assert!(!root.contains_i64(8080));
+    /// assert!(!root.contains_i64(22));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn contains_i64(&self, n: i64) -> bool {
+        self.iter().any(|v| v.as_i64() == Some(n))
+    }
 }
 
 impl fmt::Display for Sequence<'_> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.data.sequence(self.id).display(self.data, f, None)
+        self.data
+            .sequence(self.id)
+            .write_to(self.data, &mut raw::Fmt(f))
     }
 }
 
@@ -342,7 +495,13 @@ impl fmt::Debug for Sequence<'_> {
 /// )?;
 ///
 /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
-/// root.into_iter().flat_map(|v| v.as_str()).eq(["one", "two", "three"]);
+/// let mut strings = Vec::new();
+///
+/// for value in root {
+///     strings.extend(value.as_str());
+/// }
+///
+/// assert_eq!(strings, ["one", "two", "three"]);
 /// # Ok::<_, anyhow::Error>(())
 /// ```
 impl<'a> IntoIterator for Sequence<'a> {
@@ -372,7 +531,7 @@ impl<'a> IntoIterator for Sequence<'a> {
 /// )?;
 ///
 /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
-/// (&root).into_iter().flat_map(|v| v.as_str()).eq(["one", "two", "three"]);
+/// assert!((&root).into_iter().flat_map(|v| v.as_str()).eq(["one", "two", "three"]));
 /// # Ok::<_, anyhow::Error>(())
 /// ```
 impl<'a> IntoIterator for &Sequence<'a> {
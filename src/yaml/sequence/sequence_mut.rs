@@ -1,8 +1,12 @@
+use core::cmp::Ordering;
 use core::mem;
 
+use bstr::ByteSlice;
+
 use crate::yaml::data::{Data, Id, StringId};
+use crate::yaml::eq;
 use crate::yaml::raw::{self, Raw};
-use crate::yaml::{Block, Separator, Sequence, ValueMut};
+use crate::yaml::{Block, Separator, Sequence, Value, ValueMut};
 
 /// Mutator for a sequence.
 pub struct SequenceMut<'a> {
@@ -40,7 +44,7 @@ macro_rules! push_float {
         /// ```
         pub fn $name(&mut self, value: $ty) {
             let mut buffer = ryu::Buffer::new();
-            let number = self.data.insert_str(buffer.format(value));
+            let number = self.data.insert_str(raw::format_float(&mut buffer, value));
             let value = Raw::Number(raw::Number::new(number, crate::yaml::serde_hint::$hint));
             self._push(Separator::Auto, value);
         }
@@ -103,36 +107,90 @@ impl<'a> SequenceMut<'a> {
 
     /// Push a value on the sequence.
     fn _push(&mut self, separator: Separator, value: Raw) -> Id {
-        let item_prefix = if self.data.sequence(self.id).items.last().is_some() {
-            self.make_prefix()
-        } else {
-            self.data.insert_str("")
+        let index = self.data.sequence(self.id).items.len();
+        self._insert(index, separator, value)
+    }
+
+    /// Insert a value into the sequence at the given index.
+    ///
+    /// The new item takes over the prefix of the item it displaces, since the
+    /// first item is separated from what precedes the sequence differently
+    /// from the rest and a prefix might hold the trailing comment of the item
+    /// before it.
+    fn _insert(&mut self, index: usize, separator: Separator, value: Raw) -> Id {
+        let len = self.data.sequence(self.id).items.len();
+
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+
+        let inline = matches!(
+            self.data.sequence(self.id).kind,
+            raw::SequenceKind::Inline { .. }
+        );
+
+        let item_prefix = match self.data.sequence(self.id).items.get(index) {
+            Some(&next) => {
+                let prefix = self.data.layout(next).prefix;
+                let rest = self.rest_prefix(inline);
+                self.data.set_prefix(next, rest);
+                prefix
+            }
+            None if len == 0 => self.data.insert_str(""),
+            None if inline => self.rest_prefix(inline),
+            None => {
+                // Anything which trails the last item on the same line, like
+                // a comment, stays in front of the new item.
+                let mut prefix = raw::take_rest_of_line(self.data, self.id).unwrap_or_default();
+                let rest = self.rest_prefix(inline);
+                prefix.extend_from_slice(self.data.str(rest));
+                self.data.insert_str(prefix)
+            }
         };
 
         let item_id = self
             .data
             .insert(Raw::Null(raw::Null::Empty), item_prefix, Some(self.id));
 
-        let value_prefix = match separator {
-            Separator::Auto => match self.data.sequence(self.id).items.last() {
-                Some(last) => {
+        let value_prefix = if let Some(value_prefix) = separator.insert(self.data, 1) {
+            value_prefix
+        } else {
+            let items = &self.data.sequence(self.id).items;
+
+            match items.get(index.saturating_sub(1)) {
+                Some(neighbour) => {
                     self.data
-                        .layout(self.data.sequence_item(*last).value)
+                        .layout(self.data.sequence_item(*neighbour).value)
                         .prefix
                 }
+                None if inline => self.data.insert_str(""),
                 None => self.data.insert_str(" "),
-            },
-            Separator::Custom(separator) => self.data.insert_str(separator),
+            }
         };
 
         let value = self.data.insert(value, value_prefix, Some(item_id));
 
-        self.data
-            .replace(item_id, Raw::SequenceItem(raw::SequenceItem { value }));
-        self.data.sequence_mut(self.id).items.push(item_id);
+        self.data.replace(
+            item_id,
+            Raw::SequenceItem(raw::SequenceItem {
+                value,
+                suffix: None,
+            }),
+        );
+        self.data.sequence_mut(self.id).items.insert(index, item_id);
         value
     }
 
+    /// Prefix used by items which are not the first in the sequence.
+    fn rest_prefix(&mut self, inline: bool) -> StringId {
+        match &self.data.sequence(self.id).items[..] {
+            [.., _, last] if inline => self.data.layout(*last).prefix,
+            [..] if inline => self.data.insert_str(" "),
+            [..] => self.make_prefix(),
+        }
+    }
+
     /// Coerce a mutable sequence as an immutable [Sequence].
     ///
     /// This is useful to be able to directly use methods only available on
@@ -311,6 +369,17 @@ impl<'a> SequenceMut<'a> {
     ///     - "I am a quoted string!"
     ///     "#
     /// );
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// assert!(root.remove(0));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     - 20
+    ///     - "I am a quoted string!"
+    ///     "#
+    /// );
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     pub fn remove(&mut self, index: usize) -> bool {
@@ -321,12 +390,572 @@ impl<'a> SequenceMut<'a> {
         }
 
         let item = raw.items.remove(index);
+        let prefix = self.data.layout(item).prefix;
         self.data.drop(item);
+
+        // The first item is separated from what precedes the sequence
+        // differently from the rest, so the item which takes its place
+        // inherits its prefix.
+        if index == 0 {
+            if let Some(&first) = self.data.sequence(self.id).items.first() {
+                raw::inherit_first_prefix(self.data, first, prefix);
+            }
+        }
+
         true
     }
 
+    /// Swap the items at positions `a` and `b` in the sequence.
+    ///
+    /// This swaps the values rather than whole formatted lines. The whitespace
+    /// and comments on their own lines between items are part of the layout of
+    /// the sequence, so they stay in their positions and indentation is
+    /// retained. Comments which trail an item on the same line in a block
+    /// sequence move along with it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` are out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     - one # first
+    ///     ## Separator.
+    ///     - two
+    ///     - three
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.swap(0, 2);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     - three
+    ///     ## Separator.
+    ///     - two
+    ///     - one # first
+    ///     "
+    /// );
+    ///
+    /// let mut doc = yaml::from_slice("[a, b,  c]")?;
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.swap(0, 2);
+    /// assert_eq!(doc.to_string(), "[c, b,  a]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn swap(&mut self, a: usize, b: usize) {
+        let mut order = self.data.sequence(self.id).items.clone();
+        order.swap(a, b);
+        raw::reorder_items(self.data, self.id, order);
+    }
+
+    /// Remove the item at the given index from the sequence, replacing it with
+    /// the last item.
+    ///
+    /// This mirrors [`Vec::swap_remove`], and like [`SequenceMut::swap`], the
+    /// layout of the sequence stays in place while the last item moves into the
+    /// position of the removed one. A comment which trails the removed item on
+    /// the same line is removed along with it.
+    ///
+    /// Returns `false` if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     - one # first
+    ///     - two # second
+    ///     - three # third
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// assert!(!root.swap_remove(3));
+    /// assert!(root.swap_remove(0));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     - three # third
+    ///     - two # second
+    ///     "
+    /// );
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// assert!(root.swap_remove(1));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     - three # third
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn swap_remove(&mut self, index: usize) -> bool {
+        let len = self.data.sequence(self.id).items.len();
+
+        if index >= len {
+            return false;
+        }
+
+        let last = len.wrapping_sub(1);
+        self.swap(index, last);
+
+        // The rest of the line following the removed item is dropped along
+        // with it, and the rest of the line following the item before it,
+        // which is stored in the prefix of the removed item, takes its place.
+        if raw::take_rest_of_line(self.data, self.id).is_some() && last > 0 {
+            let item = self.data.sequence(self.id).items[last];
+            let prefix = self.data.prefix(item);
+            let n = prefix.find_byte(raw::NEWLINE).unwrap_or(prefix.len());
+            let mut line = prefix[..n].to_vec();
+
+            if let Some(following) = raw::Following::locate(self.data, self.id) {
+                line.extend_from_slice(following.get(self.data));
+                let line = self.data.insert_str(line);
+                following.set(self.data, line);
+            }
+        }
+
+        self.remove(last)
+    }
+
+    /// Stably sort the items of the sequence using the given comparison
+    /// function.
+    ///
+    /// Note that sorting affects formatting. The whitespace and comments on
+    /// their own lines between items are part of the layout of the sequence,
+    /// so they stay in their positions. Comments which trail an item on the
+    /// same line in a block sequence move along with it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     - 3 # three
+    ///     - 1
+    ///     - 2
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.sort_by(|a, b| a.as_u32().cmp(&b.as_u32()));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     - 1
+    ///     - 2
+    ///     - 3 # three
+    ///     "
+    /// );
+    ///
+    /// let mut doc = yaml::from_slice("[b, c, a]")?;
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+    /// assert_eq!(doc.to_string(), "[a, b, c]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&Value<'_>, &Value<'_>) -> Ordering,
+    {
+        let data = &*self.data;
+        let mut order = data.sequence(self.id).items.clone();
+
+        order.sort_by(|a, b| {
+            let a = Value::new(data, data.sequence_item(*a).value);
+            let b = Value::new(data, data.sequence_item(*b).value);
+            compare(&a, &b)
+        });
+
+        raw::reorder_items(self.data, self.id, order);
+    }
+
+    /// Remove consecutive repeated items from the sequence, keeping the first
+    /// of each run.
+    ///
+    /// Items are compared with [`Value::eq_logical`], so `1` and `1.0` are
+    /// considered to be repeated. Like [`Vec::dedup`], this only removes
+    /// duplicates which are next to each other, so the sequence should be
+    /// sorted with [`SequenceMut::sort_by`] first to remove all of them.
+    ///
+    /// Returns the number of items which were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[a, 'a', b, a, {x: 1}, {x: 1.0}]")?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// assert_eq!(root.dedup(), 2);
+    /// assert_eq!(doc.to_string(), "[a, b, a, {x: 1}]");
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+    /// assert_eq!(root.dedup(), 1);
+    /// assert_eq!(doc.to_string(), "[{x: 1}, a, b]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn dedup(&mut self) -> usize {
+        let items = self.data.sequence(self.id).items.clone();
+        let block = !raw::is_inline(self.data, self.id);
+        let tail = raw::Following::locate(self.data, self.id);
+
+        // The first item is separated from what precedes the sequence
+        // differently from the rest, so its prefix is kept in place.
+        let first = items.first().map(|item| self.data.layout(*item).prefix);
+
+        let mut removed = 0usize;
+        let mut kept = Vec::<Id>::with_capacity(items.len());
+
+        for (index, &item) in items.iter().enumerate() {
+            if let Some(&last) = kept.last() {
+                let a = self.data.sequence_item(last).value;
+                let b = self.data.sequence_item(item).value;
+
+                if eq::semantic_eq(self.data, a, self.data, b, eq::Mode::LOGICAL) {
+                    // The prefix of the removed item starts with the rest of
+                    // the line of the item before it, such as a trailing
+                    // comment, which is moved to whatever follows.
+                    let following = match items.get(index.wrapping_add(1)) {
+                        Some(next) => Some(raw::Following::Prefix(*next)),
+                        None => tail,
+                    };
+
+                    if let (true, Some(following)) = (block, following) {
+                        let prefix = self.data.prefix(item);
+                        let string = following.get(self.data);
+                        let a = prefix.find_byte(raw::NEWLINE).unwrap_or(prefix.len());
+                        let b = string.find_byte(raw::NEWLINE).unwrap_or(string.len());
+
+                        let mut new = prefix[..a].to_vec();
+                        new.extend_from_slice(&string[b..]);
+                        let new = self.data.insert_str(new);
+                        following.set(self.data, new);
+                    }
+
+                    self.data.drop(item);
+                    removed = removed.wrapping_add(1);
+                    continue;
+                }
+            }
+
+            kept.push(item);
+        }
+
+        if let (Some(first), Some(&item)) = (first, kept.first()) {
+            self.data.set_prefix(item, first);
+        }
+
+        self.data.sequence_mut(self.id).items = kept;
+        removed
+    }
+
+    /// Set the whitespace padding just inside of the delimiters of an inline
+    /// sequence, like the spaces in `[ 1, 2, 3 ]`.
+    ///
+    /// See [`Sequence::padding`] for details. The leading padding is only used if
+    /// the sequence has any items. This does nothing if this is not an inline
+    /// sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[ 1, 2, 3 ]")?;
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.set_padding("", "");
+    /// assert_eq!(doc.to_string(), "[1, 2, 3]");
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.set_padding(" ", " ");
+    /// assert_eq!(doc.to_string(), "[ 1, 2, 3 ]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_padding(&mut self, leading: &str, trailing: &str) {
+        let raw = self.data.sequence(self.id);
+
+        if !matches!(raw.kind, raw::SequenceKind::Inline { .. }) {
+            return;
+        }
+
+        if let Some(&item) = raw.items.first() {
+            let leading = self.data.insert_str(leading);
+            self.data.set_prefix(item, leading);
+        }
+
+        let trailing = self.data.insert_str(trailing);
+
+        if let raw::SequenceKind::Inline { suffix, .. } = &mut self.data.sequence_mut(self.id).kind
+        {
+            *suffix = trailing;
+        }
+    }
+
+    /// Set the whitespace which separates each item in a block sequence from
+    /// its `-` indicator, like the spaces in `-   value`.
+    ///
+    /// Only items whose values start on the same line as the indicator are
+    /// affected, and block collections which start on the same line are
+    /// re-indented so that they stay aligned. The separator is typically a
+    /// single space, and this does nothing if it is empty or contains anything
+    /// other than spaces. This also does nothing if this is an inline
+    /// sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     -   one
+    ///     - two
+    ///     -    name: three
+    ///          value: 3
+    ///     -
+    ///       four
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.normalize_item_separators(" ");
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     - one
+    ///     - two
+    ///     - name: three
+    ///       value: 3
+    ///     -
+    ///       four
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn normalize_item_separators(&mut self, separator: &str) {
+        if separator.is_empty() || separator.bytes().any(|b| b != b' ') {
+            return;
+        }
+
+        let raw = self.data.sequence(self.id);
+
+        if !matches!(raw.kind, raw::SequenceKind::Mapping) {
+            return;
+        }
+
+        let items = raw.items.clone();
+
+        for item in items {
+            let value = self.data.sequence_item(item).value;
+
+            // NB: the whitespace preceeding a tag or an anchor is stored as
+            // part of it, and whichever comes first follows the indicator.
+            let layout = *self.data.layout(value);
+
+            if let Some(anchor) = layout
+                .anchor
+                .filter(|a| !a.after_tag || layout.tag.is_none())
+            {
+                if let Some(string) = separated(self.data, anchor.string, b'&', separator) {
+                    self.data
+                        .set_anchor(value, Some(raw::Anchor { string, ..anchor }));
+                }
+
+                continue;
+            }
+
+            if let Some(tag) = layout.tag {
+                if let Some(tag) = separated(self.data, tag, b'!', separator) {
+                    self.data.set_tag(value, Some(tag));
+                }
+
+                continue;
+            }
+
+            let prefix = self.data.prefix(value);
+
+            if prefix.is_empty() || prefix.contains(&raw::NEWLINE) {
+                continue;
+            }
+
+            let (Ok(old), Ok(len)) = (
+                isize::try_from(prefix.len()),
+                isize::try_from(separator.len()),
+            ) else {
+                continue;
+            };
+
+            let new = self.data.insert_str(separator);
+            self.data.set_prefix(value, new);
+
+            // Block collections which start on the same line as the indicator
+            // have the rest of their lines aligned with the first one.
+            if matches!(self.data.raw(value), Raw::Mapping(..) | Raw::Sequence(..))
+                && !raw::is_inline(self.data, value)
+            {
+                let extra = len - old;
+
+                if extra != 0 {
+                    self.data.indent_by(value, extra);
+                }
+            }
+        }
+    }
+
+    /// Convert an inline sequence into a block sequence, with each item on
+    /// its own line.
+    ///
+    /// Sequences which are created programmatically, such as through
+    /// [`ValueMut::make_sequence`], are already block sequences. This does
+    /// nothing if the sequence is already a block sequence, or if it is
+    /// contained in an inline collection since those can't contain block
+    /// collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     name: web
+    ///     args: [--port, 80, {verbose: true}]
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut args = root.get_mut("args").and_then(|v| v.into_sequence_mut()).context("missing args")?;
+    /// args.force_block();
+    /// args.push_string("--debug");
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     name: web
+    ///     args:
+    ///       - --port
+    ///       - 80
+    ///       - {verbose: true}
+    ///       - --debug
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn force_block(&mut self) {
+        if matches!(self.data.raw(self.id), Raw::Sequence(..)) {
+            raw::force_block(self.data, self.id);
+        }
+    }
+
+    /// Transform every number in the sequence using the given function.
+    ///
+    /// Elements which are not numbers are left as they are, and so are
+    /// numbers for which the function returns a value which is not finite.
+    /// Numbers which were written as integers stay integers as long as the
+    /// result is integral, otherwise the result is written as a float.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     timeouts: [10, 2.5, 15, off, -3]
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut timeouts = root
+    ///     .get_mut("timeouts")
+    ///     .and_then(|v| v.into_sequence_mut())
+    ///     .context("missing timeouts")?;
+    ///
+    /// timeouts.map_numbers(|n| n * 1.5);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     timeouts: [15, 3.75, 22.5, off, -4.5]
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn map_numbers<F>(&mut self, f: F)
+    where
+        F: Fn(f64) -> f64,
+    {
+        let items = self.data.sequence(self.id).items.clone();
+
+        for item in items {
+            let value = self.data.sequence_item(item).value;
+
+            let Raw::Number(raw) = self.data.raw(value) else {
+                continue;
+            };
+
+            let (number, integer) = match eq::number(self.data.str(raw.string)) {
+                // NB: the function operates on floats, so large integers are
+                // necessarily rounded.
+                #[allow(clippy::cast_precision_loss)]
+                Some(eq::Number::Integer(n)) => (n as f64, true),
+                Some(eq::Number::Float(f)) => (f, false),
+                None => continue,
+            };
+
+            let result = f(number);
+
+            if !result.is_finite() {
+                continue;
+            }
+
+            let mut value = ValueMut::new(self.data, value);
+
+            let integer = eq::float_to_integer(result).filter(|_| integer);
+
+            if let Some(n) = integer.and_then(|n| u64::try_from(n).ok()) {
+                value.set_u64(n);
+            } else if let Some(n) = integer.and_then(|n| i64::try_from(n).ok()) {
+                value.set_i64(n);
+            } else {
+                value.set_f64(result);
+            }
+        }
+    }
+
     /// Clear all the elements in a sequence.
     ///
+    /// An inline sequence is left as `[]`, while a block sequence is left without
+    /// any items which reads back as a null.
+    ///
     /// # Examples
     ///
     /// ```
@@ -395,6 +1024,53 @@ impl<'a> SequenceMut<'a> {
         ValueMut::new(self.data, value)
     }
 
+    /// Insert a new null value at the given index and return a [`ValueMut`] to
+    /// the newly inserted value, shifting all items after it to the right.
+    ///
+    /// This allows for setting a custom [`Separator`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`, like [`Vec::insert`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     - two
+    ///     - four
+    ///     ",
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.insert(1, yaml::Separator::Auto).set_string("three");
+    /// root.insert(0, yaml::Separator::Auto).set_string("one");
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     - one
+    ///     - two
+    ///     - three
+    ///     - four
+    ///     "
+    /// );
+    ///
+    /// let mut doc = yaml::from_slice("[2, 3]")?;
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.insert(0, yaml::Separator::Auto).set_u32(1);
+    /// assert_eq!(doc.to_string(), "[1, 2, 3]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn insert(&mut self, index: usize, separator: Separator<'_>) -> ValueMut<'_> {
+        let value = self._insert(index, separator, Raw::Null(raw::Null::Empty));
+        ValueMut::new(self.data, value)
+    }
+
     /// Push a string.
     ///
     /// # Examples
@@ -593,3 +1269,25 @@ impl<'a> SequenceMut<'a> {
     push_number!(push_u128, u128, "128-bit unsigned integer", 42, U128);
     push_number!(push_i128, i128, "128-bit signed integer", -42, I128);
 }
+
+/// Replace the spaces preceding a property of a value which starts with the
+/// given `indicator` with `separator`.
+///
+/// Returns `None` if the property isn't preceded by spaces.
+fn separated(
+    data: &mut Data,
+    string: StringId,
+    indicator: u8,
+    separator: &str,
+) -> Option<StringId> {
+    let string = data.str(string);
+    let n = string.iter().take_while(|&&b| b == b' ').count();
+
+    if n == 0 || string.get(n) != Some(&indicator) {
+        return None;
+    }
+
+    let mut new = separator.as_bytes().to_vec();
+    new.extend_from_slice(&string[n..]);
+    Some(data.insert_str(new))
+}
@@ -1,12 +1,31 @@
+use std::rc::Rc;
+
 use bstr::{BStr, ByteSlice};
 use serde::de::{self, Error as _, IntoDeserializer, MapAccess, SeqAccess, Visitor};
 use serde::Deserializer;
 
 use crate::yaml::raw;
+use crate::yaml::serde::expand::Expander;
 use crate::yaml::serde::Error;
 use crate::yaml::serde_hint::RawNumberHint;
 use crate::yaml::{mapping, sequence, Document, Mapping, Sequence, Value};
 
+/// Forward the given deserializer methods to a [`ValueDeserializer`] with a
+/// fresh [`Expander`].
+macro_rules! forward {
+    ($($method:ident $(($($arg:ident: $ty:ty),*))?),* $(,)?) => {
+        $(
+            #[inline]
+            fn $method<V>(self, $($($arg: $ty,)*)? visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                ValueDeserializer::new(self, Rc::default()).$method($($($arg,)*)? visitor)
+            }
+        )*
+    };
+}
+
 impl<'de, 'a: 'de> IntoDeserializer<'de, Error> for &'a Document {
     type Deserializer = Value<'de>;
 
@@ -31,30 +50,101 @@ impl<'de, 'a: 'de> IntoDeserializer<'de, Error> for Value<'a> {
 impl<'de> Deserializer<'de> for Value<'de> {
     type Error = Error;
 
+    forward! {
+        deserialize_any,
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_unit,
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq,
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map,
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier,
+        deserialize_ignored_any,
+    }
+}
+
+/// The deserializer of a value, which keeps track of the aliases being
+/// expanded.
+struct ValueDeserializer<'de> {
+    value: Value<'de>,
+    expander: Rc<Expander>,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    #[inline]
+    fn new(value: Value<'de>, expander: Rc<Expander>) -> Self {
+        Self { value, expander }
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
     #[inline]
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        match self.data.raw(self.id) {
+        match self.value.data.raw(self.value.id) {
             raw::Raw::Null(..) => visitor.visit_none(),
             raw::Raw::Boolean(bool) => visitor.visit_bool(bool.value),
-            raw::Raw::Number(raw) => match raw.hint {
-                RawNumberHint::Float32 => self.deserialize_f32(visitor),
-                RawNumberHint::Float64 => self.deserialize_f64(visitor),
-                RawNumberHint::Unsigned8 => self.deserialize_u8(visitor),
-                RawNumberHint::Unsigned16 => self.deserialize_u16(visitor),
-                RawNumberHint::Unsigned32 => self.deserialize_u32(visitor),
-                RawNumberHint::Unsigned64 => self.deserialize_u64(visitor),
-                RawNumberHint::Unsigned128 => self.deserialize_u128(visitor),
-                RawNumberHint::Signed8 => self.deserialize_i8(visitor),
-                RawNumberHint::Signed16 => self.deserialize_i16(visitor),
-                RawNumberHint::Signed32 => self.deserialize_i32(visitor),
-                RawNumberHint::Signed64 => self.deserialize_i64(visitor),
-                RawNumberHint::Signed128 => self.deserialize_i128(visitor),
-            },
+            raw::Raw::Number(raw) => {
+                macro_rules! visit {
+                    ($as:ident, $visit:ident) => {
+                        if let Some(value) = self.value.$as() {
+                            return visitor.$visit(value);
+                        }
+                    };
+                }
+
+                match raw.hint {
+                    RawNumberHint::Float32 => visit!(as_f32, visit_f32),
+                    RawNumberHint::Float64 => visit!(as_f64, visit_f64),
+                    RawNumberHint::Unsigned8 => visit!(as_u8, visit_u8),
+                    RawNumberHint::Unsigned16 => visit!(as_u16, visit_u16),
+                    RawNumberHint::Unsigned32 => visit!(as_u32, visit_u32),
+                    RawNumberHint::Unsigned64 => visit!(as_u64, visit_u64),
+                    RawNumberHint::Unsigned128 => visit!(as_u128, visit_u128),
+                    RawNumberHint::Signed8 => visit!(as_i8, visit_i8),
+                    RawNumberHint::Signed16 => visit!(as_i16, visit_i16),
+                    RawNumberHint::Signed32 => visit!(as_i32, visit_i32),
+                    RawNumberHint::Signed64 => visit!(as_i64, visit_i64),
+                    RawNumberHint::Signed128 => visit!(as_i128, visit_i128),
+                }
+
+                // The number doesn't fit the type it was hinted as, so fall
+                // back to wider representations.
+                visit!(as_u64, visit_u64);
+                visit!(as_i64, visit_i64);
+                visit!(as_u128, visit_u128);
+                visit!(as_i128, visit_i128);
+                visit!(as_f64, visit_f64);
+                Err(Self::Error::custom("invalid number"))
+            }
             raw::Raw::String(raw) => {
-                let string = self.data.str(raw.id);
+                let string = self.value.data.str(raw.id);
 
                 if let Ok(string) = string.to_str() {
                     visitor.visit_borrowed_str(string)
@@ -63,11 +153,20 @@ impl<'de> Deserializer<'de> for Value<'de> {
                 }
             }
             raw::Raw::Mapping(..) => visitor.visit_map(MappingIter::new(
-                Mapping::new(self.data, self.id).into_iter(),
+                Mapping::new(self.value.data, self.value.id).into_iter(),
+                self.expander,
             )),
             raw::Raw::Sequence(..) => visitor.visit_seq(SequenceIter::new(
-                Sequence::new(self.data, self.id).into_iter(),
+                Sequence::new(self.value.data, self.value.id).into_iter(),
+                self.expander,
             )),
+            raw::Raw::Alias(..) => {
+                let value = self.expander.enter(&self.value).map_err(Error::custom)?;
+                let expander = self.expander.clone();
+                let result = ValueDeserializer::new(value, expander).deserialize_any(visitor);
+                self.expander.leave();
+                result
+            }
             _ => Err(Self::Error::custom("cannot deserialize items")),
         }
     }
@@ -77,7 +176,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_bool() {
+        match self.value.as_bool() {
             Some(value) => visitor.visit_bool(value),
             None => self.deserialize_any(visitor),
         }
@@ -88,7 +187,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_i8() {
+        match self.value.as_i8() {
             Some(value) => visitor.visit_i8(value),
             None => self.deserialize_any(visitor),
         }
@@ -99,7 +198,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_i16() {
+        match self.value.as_i16() {
             Some(value) => visitor.visit_i16(value),
             None => self.deserialize_any(visitor),
         }
@@ -110,7 +209,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_i32() {
+        match self.value.as_i32() {
             Some(value) => visitor.visit_i32(value),
             None => self.deserialize_any(visitor),
         }
@@ -121,18 +220,29 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_i64() {
+        match self.value.as_i64() {
             Some(value) => visitor.visit_i64(value),
             None => self.deserialize_any(visitor),
         }
     }
 
+    #[inline]
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value.as_i128() {
+            Some(value) => visitor.visit_i128(value),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
     #[inline]
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        match self.as_u8() {
+        match self.value.as_u8() {
             Some(value) => visitor.visit_u8(value),
             None => self.deserialize_any(visitor),
         }
@@ -143,7 +253,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_u16() {
+        match self.value.as_u16() {
             Some(value) => visitor.visit_u16(value),
             None => self.deserialize_any(visitor),
         }
@@ -154,7 +264,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_u32() {
+        match self.value.as_u32() {
             Some(value) => visitor.visit_u32(value),
             None => self.deserialize_any(visitor),
         }
@@ -165,18 +275,29 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_u64() {
+        match self.value.as_u64() {
             Some(value) => visitor.visit_u64(value),
             None => self.deserialize_any(visitor),
         }
     }
 
+    #[inline]
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value.as_u128() {
+            Some(value) => visitor.visit_u128(value),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
     #[inline]
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        match self.as_f32() {
+        match self.value.as_f32() {
             Some(value) => visitor.visit_f32(value),
             None => self.deserialize_any(visitor),
         }
@@ -187,7 +308,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_f64() {
+        match self.value.as_f64() {
             Some(value) => visitor.visit_f64(value),
             None => self.deserialize_any(visitor),
         }
@@ -198,7 +319,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_str() {
+        match self.value.as_str() {
             Some(value) => visitor.visit_borrowed_str(value),
             None => self.deserialize_any(visitor),
         }
@@ -209,7 +330,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_str() {
+        match self.value.as_str() {
             Some(value) => visitor.visit_borrowed_str(value),
             None => self.deserialize_any(visitor),
         }
@@ -220,7 +341,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_str() {
+        match self.value.as_str() {
             Some(value) => visitor.visit_borrowed_str(value),
             None => self.deserialize_any(visitor),
         }
@@ -231,7 +352,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_bstr() {
+        match self.value.as_bstr() {
             Some(value) => visitor.visit_borrowed_bytes(value),
             None => self.deserialize_any(visitor),
         }
@@ -242,7 +363,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_bstr() {
+        match self.value.as_bstr() {
             Some(value) => visitor.visit_borrowed_bytes(value),
             None => self.deserialize_any(visitor),
         }
@@ -253,7 +374,7 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.data.raw(self.id) {
+        match self.value.data.raw(self.value.id) {
             raw::Raw::Null(..) => visitor.visit_none(),
             _ => visitor.visit_some(self),
         }
@@ -296,8 +417,8 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_sequence() {
-            Some(value) => visitor.visit_seq(SequenceIter::new(value.into_iter())),
+        match self.value.as_sequence() {
+            Some(value) => visitor.visit_seq(SequenceIter::new(value.into_iter(), self.expander)),
             None => self.deserialize_any(visitor),
         }
     }
@@ -307,8 +428,8 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_sequence() {
-            Some(value) => visitor.visit_seq(SequenceIter::new(value.into_iter())),
+        match self.value.as_sequence() {
+            Some(value) => visitor.visit_seq(SequenceIter::new(value.into_iter(), self.expander)),
             None => self.deserialize_any(visitor),
         }
     }
@@ -331,8 +452,8 @@ impl<'de> Deserializer<'de> for Value<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.as_mapping() {
-            Some(value) => visitor.visit_map(MappingIter::new(value.into_iter())),
+        match self.value.as_mapping() {
+            Some(value) => visitor.visit_map(MappingIter::new(value.into_iter(), self.expander)),
             None => self.deserialize_any(visitor),
         }
     }
@@ -399,7 +520,11 @@ impl<'de> Deserializer<'de> for BStrDeserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_bytes(self.string)
+        if let Ok(string) = self.string.to_str() {
+            visitor.visit_borrowed_str(string)
+        } else {
+            visitor.visit_borrowed_bytes(self.string)
+        }
     }
 
     #[inline]
@@ -653,12 +778,17 @@ impl<'de> Deserializer<'de> for BStrDeserializer<'de> {
 struct MappingIter<'de> {
     iter: mapping::Iter<'de>,
     value: Option<Value<'de>>,
+    expander: Rc<Expander>,
 }
 
 impl<'de> MappingIter<'de> {
     #[inline]
-    fn new(iter: mapping::Iter<'de>) -> Self {
-        Self { iter, value: None }
+    fn new(iter: mapping::Iter<'de>, expander: Rc<Expander>) -> Self {
+        Self {
+            iter,
+            value: None,
+            expander,
+        }
     }
 }
 
@@ -687,18 +817,19 @@ impl<'de> MapAccess<'de> for MappingIter<'de> {
             return Err(Error::custom("missing value"));
         };
 
-        seed.deserialize(value)
+        seed.deserialize(ValueDeserializer::new(value, self.expander.clone()))
     }
 }
 
 struct SequenceIter<'a> {
     iter: sequence::Iter<'a>,
+    expander: Rc<Expander>,
 }
 
 impl<'de> SequenceIter<'de> {
     #[inline]
-    fn new(iter: sequence::Iter<'de>) -> Self {
-        Self { iter }
+    fn new(iter: sequence::Iter<'de>, expander: Rc<Expander>) -> Self {
+        Self { iter, expander }
     }
 }
 
@@ -714,6 +845,7 @@ impl<'de> SeqAccess<'de> for SequenceIter<'de> {
             return Ok(None);
         };
 
+        let value = ValueDeserializer::new(value, self.expander.clone());
         Ok(Some(seed.deserialize(value)?))
     }
 }
@@ -1,8 +1,8 @@
 use std::fmt;
 
-use serde::de;
+use serde::{de, ser};
 
-/// A error raised during deserialization.
+/// A error raised during serialization or deserialization.
 ///
 /// See [`serde` module][crate::yaml::serde] for documentation.
 #[derive(Debug)]
@@ -22,6 +22,16 @@ impl de::Error for Error {
     }
 }
 
+impl ser::Error for Error {
+    #[inline]
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        <Self as de::Error>::custom(msg)
+    }
+}
+
 impl fmt::Display for Error {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
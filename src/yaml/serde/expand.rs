@@ -0,0 +1,105 @@
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::yaml::data::Id;
+use crate::yaml::raw;
+use crate::yaml::{Error, ErrorKind, Value};
+
+/// The number of values which expanding aliases may produce while serializing
+/// or deserializing a single value.
+///
+/// Every alias produces a copy of the value it refers to, so a small document
+/// with nested aliases can otherwise expand into billions of values.
+const LIMIT: usize = 1_000_000;
+
+/// Keeps track of aliases being expanded while serializing or deserializing a
+/// value.
+#[derive(Default)]
+pub(crate) struct Expander {
+    /// The value every alias in the document refers to, which is resolved the
+    /// first time an alias is expanded.
+    aliases: OnceCell<HashMap<Id, Id>>,
+    /// The number of values in each value which has been expanded into.
+    sizes: RefCell<HashMap<Id, usize>>,
+    /// The values which aliases are currently being expanded into.
+    expanding: RefCell<Vec<Id>>,
+    /// The number of values which have been produced by expanding aliases.
+    expanded: Cell<usize>,
+}
+
+impl Expander {
+    /// Enter the expansion of the given alias, returning the value it refers
+    /// to.
+    pub(crate) fn enter<'a>(&self, alias: &Value<'a>) -> Result<Value<'a>, ExpandError> {
+        let aliases = self.aliases.get_or_init(|| {
+            let mut root = alias.id;
+
+            while let Some(parent) = alias.data.parent(root) {
+                root = parent;
+            }
+
+            raw::resolve_aliases(alias.data, root)
+        });
+
+        let Some(&id) = aliases.get(&alias.id) else {
+            if raw::is_anchor_cycle(alias.data, alias.id) {
+                let error = Error::value(alias.data, alias.id, ErrorKind::AnchorCycle);
+                return Err(ExpandError::Error(error));
+            }
+
+            return Err(ExpandError::Unresolved);
+        };
+
+        let mut expanding = self.expanding.borrow_mut();
+
+        if expanding.contains(&id) {
+            let error = Error::value(alias.data, alias.id, ErrorKind::AnchorCycle);
+            return Err(ExpandError::Error(error));
+        }
+
+        let size = *self
+            .sizes
+            .borrow_mut()
+            .entry(id)
+            .or_insert_with(|| raw::count_values(alias.data, id));
+
+        let expanded = self.expanded.get().saturating_add(size);
+
+        if expanded > LIMIT {
+            return Err(ExpandError::Limit);
+        }
+
+        self.expanded.set(expanded);
+        expanding.push(id);
+        Ok(Value::new(alias.data, id))
+    }
+
+    /// Leave the expansion of the alias which was most recently entered.
+    pub(crate) fn leave(&self) {
+        self.expanding.borrow_mut().pop();
+    }
+}
+
+/// An error raised when an alias can't be expanded.
+pub(crate) enum ExpandError {
+    /// The alias doesn't refer to any value.
+    Unresolved,
+    /// The alias can't be expanded because of an error in the document.
+    Error(Error),
+    /// Expanding the alias would exceed the [`LIMIT`].
+    Limit,
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpandError::Unresolved => write!(f, "cannot expand unresolved alias"),
+            ExpandError::Error(error) => error.fmt(f),
+            ExpandError::Limit => write!(
+                f,
+                "aliases expand into more than {LIMIT} values, which is not supported"
+            ),
+        }
+    }
+}
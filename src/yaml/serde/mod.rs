@@ -8,6 +8,8 @@
 //!
 //! By enabling the `serde` feature [`Value`] implements [`Serialize`] and
 //! [`IntoDeserializer`], allowing it to be used to deserialize into types.
+//! Going the other way, [`to_document`] builds a new [`Document`] out of any
+//! type implementing [`Serialize`].
 //!
 //! [`Serialize`]: serde::Serialize
 //! [`IntoDeserializer`]: serde::de::IntoDeserializer
@@ -87,6 +89,9 @@
 
 mod de;
 mod error;
+mod expand;
 mod ser;
+mod serializer;
 
 pub use self::error::Error;
+pub use self::serializer::to_document;
@@ -4,69 +4,94 @@ use serde::ser::{Error, SerializeMap, SerializeSeq};
 use serde::{Serialize, Serializer};
 
 use crate::yaml::raw;
+use crate::yaml::serde::expand::Expander;
 use crate::yaml::serde_hint::RawNumberHint;
 use crate::yaml::{Mapping, Sequence, Value};
 
 impl Serialize for Value<'_> {
+    #[inline]
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        match self.data.raw(self.id) {
+        Serialized::new(Value::new(self.data, self.id), &Expander::default()).serialize(serializer)
+    }
+}
+
+/// A value being serialized, which keeps track of the values which aliases are
+/// currently being expanded into.
+struct Serialized<'a, 'e> {
+    value: Value<'a>,
+    expanding: &'e Expander,
+}
+
+impl<'a, 'e> Serialized<'a, 'e> {
+    #[inline]
+    fn new(value: Value<'a>, expanding: &'e Expander) -> Self {
+        Self { value, expanding }
+    }
+}
+
+impl Serialize for Serialized<'_, '_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.value.data.raw(self.value.id) {
             raw::Raw::Null(..) => serializer.serialize_none(),
             raw::Raw::Boolean(bool) => serializer.serialize_bool(bool.value),
             raw::Raw::Number(raw) => match raw.hint {
-                RawNumberHint::Float32 => match self.as_f32() {
+                RawNumberHint::Float32 => match self.value.as_f32() {
                     Some(value) => serializer.serialize_f32(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Float64 => match self.as_f64() {
+                RawNumberHint::Float64 => match self.value.as_f64() {
                     Some(value) => serializer.serialize_f64(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Unsigned8 => match self.as_u8() {
+                RawNumberHint::Unsigned8 => match self.value.as_u8() {
                     Some(value) => serializer.serialize_u8(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Unsigned16 => match self.as_u16() {
+                RawNumberHint::Unsigned16 => match self.value.as_u16() {
                     Some(value) => serializer.serialize_u16(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Unsigned32 => match self.as_u32() {
+                RawNumberHint::Unsigned32 => match self.value.as_u32() {
                     Some(value) => serializer.serialize_u32(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Unsigned64 => match self.as_u64() {
+                RawNumberHint::Unsigned64 => match self.value.as_u64() {
                     Some(value) => serializer.serialize_u64(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Unsigned128 => match self.as_u128() {
+                RawNumberHint::Unsigned128 => match self.value.as_u128() {
                     Some(value) => serializer.serialize_u128(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Signed8 => match self.as_i8() {
+                RawNumberHint::Signed8 => match self.value.as_i8() {
                     Some(value) => serializer.serialize_i8(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Signed16 => match self.as_i16() {
+                RawNumberHint::Signed16 => match self.value.as_i16() {
                     Some(value) => serializer.serialize_i16(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Signed32 => match self.as_i32() {
+                RawNumberHint::Signed32 => match self.value.as_i32() {
                     Some(value) => serializer.serialize_i32(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Signed64 => match self.as_i64() {
+                RawNumberHint::Signed64 => match self.value.as_i64() {
                     Some(value) => serializer.serialize_i64(value),
                     None => serializer.serialize_none(),
                 },
-                RawNumberHint::Signed128 => match self.as_i128() {
+                RawNumberHint::Signed128 => match self.value.as_i128() {
                     Some(value) => serializer.serialize_i128(value),
                     None => serializer.serialize_none(),
                 },
             },
             raw::Raw::String(raw) => {
-                let string = self.data.str(raw.id);
+                let string = self.value.data.str(raw.id);
 
                 if let Ok(string) = string.to_str() {
                     serializer.serialize_str(string)
@@ -74,8 +99,23 @@ impl Serialize for Value<'_> {
                     serializer.serialize_bytes(string)
                 }
             }
-            raw::Raw::Mapping(..) => Mapping::new(self.data, self.id).serialize(serializer),
-            raw::Raw::Sequence(..) => Sequence::new(self.data, self.id).serialize(serializer),
+            raw::Raw::Mapping(..) => serialize_mapping(
+                &Mapping::new(self.value.data, self.value.id),
+                self.expanding,
+                serializer,
+            ),
+            raw::Raw::Sequence(..) => serialize_sequence(
+                &Sequence::new(self.value.data, self.value.id),
+                self.expanding,
+                serializer,
+            ),
+            raw::Raw::Alias(..) => {
+                let value = self.expanding.enter(&self.value);
+                let value = value.map_err(S::Error::custom)?;
+                let result = Serialized::new(value, self.expanding).serialize(serializer);
+                self.expanding.leave();
+                result
+            }
             _ => Err(S::Error::custom("cannot serialize items")),
         }
     }
@@ -87,13 +127,7 @@ impl Serialize for Sequence<'_> {
     where
         S: Serializer,
     {
-        let mut seq = serializer.serialize_seq(Some(self.len()))?;
-
-        for item in self {
-            seq.serialize_element(&item)?;
-        }
-
-        seq.end()
+        serialize_sequence(self, &Expander::default(), serializer)
     }
 }
 
@@ -103,16 +137,46 @@ impl Serialize for Mapping<'_> {
     where
         S: Serializer,
     {
-        let mut map = serializer.serialize_map(Some(self.len()))?;
+        serialize_mapping(self, &Expander::default(), serializer)
+    }
+}
 
-        for (key, value) in self {
-            if let Ok(key) = key.to_str() {
-                map.serialize_entry(key, &value)?;
-            } else {
-                map.serialize_entry(key, &value)?;
-            }
-        }
+fn serialize_sequence<S>(
+    sequence: &Sequence<'_>,
+    expanding: &Expander,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut seq = serializer.serialize_seq(Some(sequence.len()))?;
 
-        map.end()
+    for item in sequence {
+        seq.serialize_element(&Serialized::new(item, expanding))?;
     }
+
+    seq.end()
+}
+
+fn serialize_mapping<S>(
+    mapping: &Mapping<'_>,
+    expanding: &Expander,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut map = serializer.serialize_map(Some(mapping.len()))?;
+
+    for (key, value) in mapping {
+        let value = Serialized::new(value, expanding);
+
+        if let Ok(key) = key.to_str() {
+            map.serialize_entry(key, &value)?;
+        } else {
+            map.serialize_entry(key, &value)?;
+        }
+    }
+
+    map.end()
 }
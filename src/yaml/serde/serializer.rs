@@ -0,0 +1,729 @@
+use serde::ser::{self, Error as _, Impossible, Serialize};
+
+use crate::yaml::data::Data;
+use crate::yaml::raw;
+use crate::yaml::serde::Error;
+use crate::yaml::{CollectionStyle, Document, Scalar, Separator, StringKind, ValueMut};
+
+/// Serialize the given value into a new [`Document`].
+///
+/// Maps and structs are emitted as block mappings and sequences as block
+/// sequences, with nested collections indented under their parent. Empty
+/// collections are emitted in flow style as `{}` and `[]`, since an empty
+/// block collection would read back as a `null`.
+///
+/// Enums are represented externally tagged, the same way as they are by
+/// `serde_yaml`.
+///
+/// # Errors
+///
+/// Errors if the value fails to serialize, or if it contains a map with keys
+/// that aren't strings, numbers or booleans.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+/// use serde::{Deserialize, Serialize};
+/// use serde::de::IntoDeserializer;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Service {
+///     image: String,
+///     ports: Vec<u16>,
+///     environment: Vec<(String, String)>,
+/// }
+///
+/// let doc = yaml::from_slice(
+///     r"
+///     image: nginx
+///     ports: [80]
+///     environment: []
+///     "
+/// )?;
+///
+/// let mut service = Service::deserialize(doc.into_deserializer())?;
+/// service.ports.push(443);
+///
+/// let doc = yaml::serde::to_document(&service)?;
+///
+/// assert_eq!(
+///     doc.to_string(),
+///     "image: nginx\nports:\n  - 80\n  - 443\nenvironment: []\n"
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn to_document<T>(value: &T) -> Result<Document, Error>
+where
+    T: ?Sized + Serialize,
+{
+    let node = value.serialize(Serializer)?;
+
+    let mut data = Data::default();
+    let prefix = data.insert_str("");
+    let root = data.insert(raw::Raw::Null(raw::Null::Empty), prefix, None);
+    build(ValueMut::new(&mut data, root), node);
+
+    let suffix = data.insert_str("\n");
+    Ok(Document::new(suffix, root, data))
+}
+
+/// An intermediate serialized value which is built into a document once
+/// serialization has succeeded.
+enum Node {
+    Scalar(Scalar),
+    Sequence(Vec<Node>),
+    Mapping(Vec<(Key, Node)>),
+}
+
+/// A serialized mapping key.
+struct Key {
+    string: String,
+    /// Whether the key was serialized from a string, as opposed to a number or
+    /// a boolean. Strings are quoted if they would otherwise read back as
+    /// something else.
+    is_string: bool,
+}
+
+impl Key {
+    fn string(string: &str) -> Self {
+        Self {
+            string: string.to_owned(),
+            is_string: true,
+        }
+    }
+}
+
+/// Build the given node into the value.
+fn build(value: ValueMut<'_>, node: Node) {
+    match node {
+        Node::Scalar(scalar) => {
+            let raw = scalar.into_raw(value.data);
+            value.data.replace(value.id, raw);
+        }
+        Node::Sequence(items) if items.is_empty() => {
+            _ = value.make_sequence_with(CollectionStyle::Flow);
+        }
+        Node::Sequence(items) => {
+            let mut sequence = value.make_sequence();
+
+            for item in items {
+                build(sequence.push(Separator::Auto), item);
+            }
+        }
+        Node::Mapping(items) if items.is_empty() => {
+            _ = value.make_mapping_with(CollectionStyle::Flow);
+        }
+        Node::Mapping(items) => {
+            let mut mapping = value.make_mapping();
+
+            for (key, item) in items {
+                let inserted = mapping.insert(key.string, Separator::Auto);
+
+                let inserted = if key.is_string {
+                    inserted.with_key_kind(StringKind::Bare)
+                } else {
+                    inserted
+                };
+
+                build(inserted.into_value_mut(), item);
+            }
+        }
+    }
+}
+
+/// Wrap a node in a single-entry mapping keyed by an enum variant.
+fn tagged(variant: &str, node: Node) -> Node {
+    Node::Mapping(vec![(Key::string(variant), node)])
+}
+
+struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Node;
+    type Error = Error;
+    type SerializeSeq = SerializeSeq;
+    type SerializeTuple = SerializeSeq;
+    type SerializeTupleStruct = SerializeSeq;
+    type SerializeTupleVariant = SerializeSeq;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v.to_string())))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(v)))
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        let items = v.iter().map(|&b| Node::Scalar(Scalar::from(b))).collect();
+        Ok(Node::Sequence(items))
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Scalar(Scalar::from(None::<bool>)))
+    }
+
+    #[inline]
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.serialize_none()
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_none()
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(tagged(variant, value.serialize(self)?))
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SerializeSeq {
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SerializeSeq {
+            variant: Some(variant),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    #[inline]
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(SerializeMap {
+            variant: None,
+            key: None,
+            items: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(SerializeMap {
+            variant: Some(variant),
+            key: None,
+            items: Vec::with_capacity(len),
+        })
+    }
+}
+
+struct SerializeSeq {
+    variant: Option<&'static str>,
+    items: Vec<Node>,
+}
+
+impl SerializeSeq {
+    fn push<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Node {
+        let node = Node::Sequence(self.items);
+
+        match self.variant {
+            Some(variant) => tagged(variant, node),
+            None => node,
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeSeq {
+    type Ok = Node;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SerializeSeq {
+    type Ok = Node;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeSeq {
+    type Ok = Node;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeSeq {
+    type Ok = Node;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+struct SerializeMap {
+    variant: Option<&'static str>,
+    key: Option<Key>,
+    items: Vec<(Key, Node)>,
+}
+
+impl SerializeMap {
+    fn field<T>(&mut self, key: &str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.items
+            .push((Key::string(key), value.serialize(Serializer)?));
+        Ok(())
+    }
+
+    fn finish(self) -> Node {
+        let node = Node::Mapping(self.items);
+
+        match self.variant {
+            Some(variant) => tagged(variant, node),
+            None => node,
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Node;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let Some(key) = self.key.take() else {
+            return Err(Error::custom("value serialized before its key"));
+        };
+
+        self.items.push((key, value.serialize(Serializer)?));
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Node;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = Node;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+/// Serializer for mapping keys, which only permits scalars.
+struct KeySerializer;
+
+macro_rules! key {
+    ($($name:ident($ty:ty)),* $(,)?) => {
+        $(
+            #[inline]
+            fn $name(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                Ok(Key {
+                    string: v.to_string(),
+                    is_string: false,
+                })
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = Key;
+    type Error = Error;
+    type SerializeSeq = Impossible<Key, Error>;
+    type SerializeTuple = Impossible<Key, Error>;
+    type SerializeTupleStruct = Impossible<Key, Error>;
+    type SerializeTupleVariant = Impossible<Key, Error>;
+    type SerializeMap = Impossible<Key, Error>;
+    type SerializeStruct = Impossible<Key, Error>;
+    type SerializeStructVariant = Impossible<Key, Error>;
+
+    key! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(Key::string(v.encode_utf8(&mut [0; 4])))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(Key::string(v))
+    }
+
+    #[inline]
+    fn serialize_bytes(self, _: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(Key::string(variant))
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(key_error())
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(key_error())
+    }
+}
+
+fn key_error() -> Error {
+    Error::custom("mapping keys must be strings, numbers or booleans")
+}
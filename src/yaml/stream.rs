@@ -0,0 +1,279 @@
+use std::fmt;
+use std::io;
+use std::slice;
+
+use crate::yaml::Document;
+
+/// A stream of YAML documents, as parsed through [`from_slice_stream`].
+///
+/// Each document holds the markers and whitespace which belong to it, like a
+/// leading `---` and a trailing `...`, so a stream which has not been modified
+/// formats back into exactly the bytes it was parsed from.
+///
+/// [`from_slice_stream`]: crate::yaml::from_slice_stream
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut stream = yaml::from_slice_stream("kind: Service\n---\nkind: Deployment\n...\n")?;
+///
+/// let kinds = stream
+///     .documents()
+///     .flat_map(|doc| doc.as_ref().as_mapping()?.get("kind")?.as_str())
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(kinds, ["Service", "Deployment"]);
+///
+/// let doc = stream.get_mut(1).context("missing document")?;
+/// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+/// root.insert_u32("replicas", 3);
+///
+/// assert_eq!(
+///     stream.to_string(),
+///     "kind: Service\n---\nkind: Deployment\nreplicas: 3\n...\n"
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct Stream {
+    documents: Vec<Document>,
+}
+
+impl Stream {
+    /// Construct a new stream.
+    pub(crate) fn new(documents: Vec<Document>) -> Self {
+        Self { documents }
+    }
+
+    /// Get the number of documents in the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::from_slice_stream("a: 1\n---\nb: 2\n---\n")?;
+    /// assert_eq!(stream.len(), 3);
+    /// assert!(!stream.is_empty());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Test if the stream is empty.
+    ///
+    /// A parsed stream always contains at least one document, even if that
+    /// document is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::from_slice_stream("")?;
+    /// assert_eq!(stream.len(), 1);
+    /// assert!(!stream.is_empty());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Get the document at the given index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::from_slice_stream("a: 1\n---\nb: 2\n")?;
+    ///
+    /// let doc = stream.get(1).context("missing document")?;
+    /// assert_eq!(doc.to_string(), "---\nb: 2\n");
+    /// assert!(stream.get(2).is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&Document> {
+        self.documents.get(index)
+    }
+
+    /// Get the document at the given index mutably.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut stream = yaml::from_slice_stream("a: 1\n---\nb: 2\n")?;
+    ///
+    /// let doc = stream.get_mut(0).context("missing document")?;
+    /// doc.as_mut().into_mapping_mut().context("missing root")?.insert_u32("c", 3);
+    ///
+    /// assert_eq!(stream.to_string(), "a: 1\nc: 3\n---\nb: 2\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Document> {
+        self.documents.get_mut(index)
+    }
+
+    /// Iterate over the documents in the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::from_slice_stream("1\n---\n2\n---\n3\n")?;
+    ///
+    /// let values = stream.documents().flat_map(|doc| doc.as_ref().as_u32()).collect::<Vec<_>>();
+    /// assert_eq!(values, [1, 2, 3]);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn documents(&self) -> slice::Iter<'_, Document> {
+        self.documents.iter()
+    }
+
+    /// Iterate over the documents in the stream.
+    ///
+    /// This is the same as [`Stream::documents`], and is what iterating over
+    /// `&Stream` uses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::from_slice_stream("1\n---\n2\n")?;
+    ///
+    /// let values = stream.iter().flat_map(|doc| doc.as_ref().as_u32()).collect::<Vec<_>>();
+    /// assert_eq!(values, [1, 2]);
+    ///
+    /// let mut count = 0;
+    ///
+    /// for _ in &stream {
+    ///     count += 1;
+    /// }
+    ///
+    /// assert_eq!(count, 2);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn iter(&self) -> slice::Iter<'_, Document> {
+        self.documents.iter()
+    }
+
+    /// Iterate mutably over the documents in the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut stream = yaml::from_slice_stream("1\n---\n2\n")?;
+    ///
+    /// for doc in stream.documents_mut() {
+    ///     doc.as_mut().set_u32(0);
+    /// }
+    ///
+    /// assert_eq!(stream.to_string(), "0\n---\n0\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn documents_mut(&mut self) -> slice::IterMut<'_, Document> {
+        self.documents.iter_mut()
+    }
+
+    /// Convert the stream into its documents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::from_slice_stream("a\n---\nb\n")?;
+    /// let documents = stream.into_documents();
+    ///
+    /// assert_eq!(documents.len(), 2);
+    /// assert_eq!(documents[1].as_ref().as_str(), Some("b"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn into_documents(self) -> Vec<Document> {
+        self.documents
+    }
+
+    /// Write the bytes of every document in the stream to the given `output`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if writing to the output fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::from_slice_stream("a: 1\n---\nb: 2\n")?;
+    ///
+    /// let mut out = Vec::new();
+    /// stream.write_to(&mut out)?;
+    /// assert_eq!(&out[..], b"a: 1\n---\nb: 2\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn write_to<O>(&self, mut output: O) -> io::Result<()>
+    where
+        O: io::Write,
+    {
+        for document in &self.documents {
+            document.write_to(&mut output)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Stream {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for document in &self.documents {
+            document.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Stream {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.documents).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a Stream {
+    type Item = &'a Document;
+    type IntoIter = slice::Iter<'a, Document>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Stream {
+    type Item = Document;
+    type IntoIter = std::vec::IntoIter<Document>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.documents.into_iter()
+    }
+}
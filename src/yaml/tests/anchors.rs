@@ -0,0 +1,202 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn anchors_roundtrip() -> Result<()> {
+    const INPUT: &str = r"
+    base: &base
+      image: nginx
+      ports: &ports [80, 443]
+    web: *base
+    tagged: !!str &tagged 42
+    anchored: &anchored !!str 43
+    empty: &empty
+    seq:
+      - &first one
+      - *first
+    inline: {a: &a 1, b: *a}
+    &key plain: value
+    ";
+
+    let doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    assert_eq!(
+        root.get("tagged").and_then(|v| v.anchor()),
+        Some("tagged".into())
+    );
+    assert_eq!(root.get("tagged").and_then(|v| v.as_str()), Some("42"));
+    assert_eq!(
+        root.get("anchored").and_then(|v| v.anchor()),
+        Some("anchored".into())
+    );
+    assert_eq!(root.get("anchored").and_then(|v| v.as_str()), Some("43"));
+    assert_eq!(
+        root.get("empty").and_then(|v| v.anchor()),
+        Some("empty".into())
+    );
+    assert!(root.get("empty").is_some_and(|v| v.is_null()));
+    assert_eq!(
+        root.get("&key plain").and_then(|v| v.as_str()),
+        Some("value")
+    );
+
+    let web = root.get("web").context("missing web")?;
+    assert_eq!(web.alias(), Some("base".into()));
+    let base = web
+        .resolve_alias()
+        .and_then(|v| v.as_mapping())
+        .context("missing base")?;
+    assert_eq!(base.get("image").and_then(|v| v.as_str()), Some("nginx"));
+
+    let inline = root
+        .get("inline")
+        .and_then(|v| v.as_mapping())
+        .context("missing inline")?;
+    let b = inline.get("b").and_then(|v| v.resolve_alias());
+    assert_eq!(b.and_then(|v| v.as_u32()), Some(1));
+    Ok(())
+}
+
+#[test]
+fn alias_resolution() -> Result<()> {
+    let doc = yaml::from_slice(
+        r"
+        - &x 1
+        - *x
+        - &x 2
+        - *x
+        - &y [*y]
+        - *z
+        ",
+    )?;
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let resolve = |index| root.get(index).and_then(|v| v.resolve_alias());
+
+    assert_eq!(resolve(1).and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(resolve(3).and_then(|v| v.as_u32()), Some(2));
+    assert!(resolve(5).is_none());
+
+    let recursive = root
+        .get(4)
+        .and_then(|v| v.as_sequence())
+        .context("missing recursive")?;
+    assert!(recursive.get(0).and_then(|v| v.resolve_alias()).is_none());
+    Ok(())
+}
+
+#[test]
+fn anchors_kept_on_edit() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        image: &image nginx
+        web: *image
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("image")
+        .context("missing image")?
+        .set_string("httpd");
+    root.get_mut("web")
+        .context("missing web")?
+        .set_string("apache");
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        image: &image httpd
+        web: apache
+        "
+    );
+    Ok(())
+}
+
+#[test]
+fn raw_yaml_anchor() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\nb: 2\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("a")
+        .context("missing a")?
+        .set_raw_yaml("&one 1")?;
+    root.get_mut("b")
+        .context("missing b")?
+        .set_raw_yaml("!!str &two 2")?;
+
+    assert_eq!(doc.to_string(), "a: &one 1\nb: !!str &two 2\n");
+    Ok(())
+}
+
+#[test]
+fn merge_keys() -> Result<()> {
+    let doc = yaml::from_slice(
+        r"
+        defaults: &defaults
+          adapter: postgres
+          host: localhost
+        nested: &nested
+          <<: *defaults
+          host: db
+        first: &first {a: 1, b: 1}
+        second: &second {b: 2, c: 2}
+        single:
+          <<: *defaults
+          database: single
+        chained:
+          <<: *nested
+        list:
+          <<: [*first, *second, {d: 4}]
+        unresolved:
+          <<: *missing
+        ",
+    )?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let single = root
+        .get("single")
+        .and_then(|v| v.as_mapping())
+        .context("missing single")?;
+    assert_eq!(
+        single.get_merged("adapter").and_then(|v| v.as_str()),
+        Some("postgres")
+    );
+    assert_eq!(
+        single.get_merged("database").and_then(|v| v.as_str()),
+        Some("single")
+    );
+    assert!(single.get("adapter").is_none());
+
+    let chained = root
+        .get("chained")
+        .and_then(|v| v.as_mapping())
+        .context("missing chained")?;
+    assert_eq!(
+        chained.get_merged("host").and_then(|v| v.as_str()),
+        Some("db")
+    );
+    assert_eq!(
+        chained.get_merged("adapter").and_then(|v| v.as_str()),
+        Some("postgres")
+    );
+
+    let list = root
+        .get("list")
+        .and_then(|v| v.as_mapping())
+        .context("missing list")?;
+    assert_eq!(list.get_merged("a").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(list.get_merged("b").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(list.get_merged("c").and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(list.get_merged("d").and_then(|v| v.as_u32()), Some(4));
+
+    let unresolved = root
+        .get("unresolved")
+        .and_then(|v| v.as_mapping())
+        .context("missing unresolved")?;
+    assert!(unresolved.get_merged("adapter").is_none());
+    Ok(())
+}
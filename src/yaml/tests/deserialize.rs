@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+
+use crate::yaml;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Config<'a> {
+    name: &'a str,
+    mode: u32,
+    limit: u64,
+    mask: u8,
+    ratio: f64,
+    tags: Vec<&'a str>,
+    #[serde(borrow)]
+    labels: BTreeMap<&'a str, &'a str>,
+    optional: Option<u32>,
+}
+
+#[test]
+fn deserialize_edited_value() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        config:
+          name: web
+          mode: 0o755
+          limit: 1_000_000
+          mask: 0xFF
+          ratio: .inf
+          tags: [a, b]
+          labels: {app: web}
+          optional:
+        ",
+    )?;
+
+    let mut config = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("config"))
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing config")?;
+    config.insert_str("name", "api");
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let value = root.get("config").context("missing config")?;
+    let config = Config::deserialize(value.into_deserializer())?;
+
+    assert_eq!(
+        config,
+        Config {
+            name: "api",
+            mode: 0o755,
+            limit: 1_000_000,
+            mask: 0xff,
+            ratio: f64::INFINITY,
+            tags: vec!["a", "b"],
+            labels: BTreeMap::from([("app", "web")]),
+            optional: None,
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn deserialize_out_of_range() -> Result<()> {
+    let doc = yaml::from_slice("[256, 0x1_0000_0000_0000_0000, -1]")?;
+
+    assert!(Vec::<u8>::deserialize(doc.as_ref().into_deserializer()).is_err());
+
+    let values = Vec::<i128>::deserialize(doc.as_ref().into_deserializer())?;
+    assert_eq!(values, [256, 1 << 64, -1]);
+    Ok(())
+}
+
+#[test]
+fn deserialize_aliases() -> Result<()> {
+    let doc = yaml::from_slice("a: &x [1]\nb: *x\n")?;
+    let values = BTreeMap::<&str, Vec<u32>>::deserialize(doc.as_ref().into_deserializer())?;
+    assert_eq!(values.get("b"), Some(&vec![1]));
+
+    let doc = yaml::from_slice("a: &x [*x]\n")?;
+    let error =
+        BTreeMap::<&str, Vec<Vec<u32>>>::deserialize(doc.as_ref().into_deserializer()).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("alias refers to a value which contains it"),
+        "{error}"
+    );
+    Ok(())
+}
+
+#[test]
+fn deserialize_alias_expansion_limit() -> Result<()> {
+    let mut input = String::from("a0: &a0 [x, x, x, x, x, x, x, x]\n");
+
+    for n in 1..9 {
+        let p = n - 1;
+        input.push_str(&format!(
+            "a{n}: &a{n} [*a{p}, *a{p}, *a{p}, *a{p}, *a{p}, *a{p}, *a{p}, *a{p}]\n"
+        ));
+    }
+
+    let doc = yaml::from_slice(input)?;
+    let error =
+        BTreeMap::<&str, serde::de::IgnoredAny>::deserialize(doc.as_ref().into_deserializer())
+            .unwrap_err();
+    assert!(
+        error.to_string().contains("aliases expand into more than"),
+        "{error}"
+    );
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use crate::yaml;
 
@@ -20,16 +20,207 @@ fn double_quotes() -> Result<()> {
     test!("\"a \\f b\"", "a \x0c b");
     test!("\"a \\r b\"", "a \r b");
     test!("\"a \\e b\"", "a \x1b b");
-    test!("\"a \\\\ b\"", "a \" b");
+    test!("\"a \\\\ b\"", "a \\ b");
+    test!("\"a \\\" b\"", "a \" b");
+    test!("\"a \\/ b\"", "a / b");
+    test!("\"a\\ b\"", "a b");
+    test!("\"a \\\t b\"", "a \t b");
 
     test!("\"a \\x77 b\"", "a \x77 b");
     test!("\"a \\u79c1 b\"", "a 私 b");
     Ok(())
 }
 
+#[test]
+fn double_quotes_unicode() -> Result<()> {
+    test!("\"\\u00e9\"", "\u{e9}");
+    test!("\"\\U0001F600\"", "\u{1f600}");
+    test!("\"\\N\\_\\L\\P\"", "\u{85}\u{a0}\u{2028}\u{2029}");
+
+    let doc = yaml::from_slice("- \"\\u00e9\"\n- \"\\U0001F600 \\N\"")?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.get(0).and_then(|v| v.as_str()), Some("\u{e9}"));
+    assert_eq!(
+        root.get(1).and_then(|v| v.as_str()),
+        Some("\u{1f600} \u{85}")
+    );
+    assert_eq!(doc.to_string(), "- \"\\u00e9\"\n- \"\\U0001F600 \\N\"");
+
+    let error = yaml::from_slice("\"\\U0011FFFF\"").unwrap_err();
+    assert_eq!(*error.kind(), yaml::ErrorKind::BadUnicodeEscape);
+    Ok(())
+}
+
 #[test]
 fn single_quotes() -> Result<()> {
     test!("'It is a party!'", "It is a party!");
     test!("'It''s a party!'", "It's a party!");
     Ok(())
 }
+
+#[test]
+fn single_quoted_control_characters() -> Result<()> {
+    let mut doc = yaml::from_slice("key: 'value'")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("key")
+        .context("missing key")?
+        .set_string_with("a\tb\nc", yaml::StringKind::Single);
+    assert_eq!(doc.to_string(), "key: \"a\\tb\\nc\"");
+
+    let mut out = Vec::new();
+    doc.write_to(&mut out)?;
+    assert_eq!(out, b"key: \"a\\tb\\nc\"");
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("key").and_then(|v| v.as_str()), Some("a\tb\nc"));
+    Ok(())
+}
+
+#[test]
+fn requires_quoting_as_bare() -> Result<()> {
+    let samples = [
+        ("hello", false, false),
+        ("hello world", false, false),
+        ("a,b", false, true),
+        ("[a]", true, true),
+        ("a: b", true, true),
+        ("a:b", false, true),
+        ("a:", true, true),
+        (":a", false, true),
+        ("http://a", false, true),
+        ("a #b", true, true),
+        ("a#b", false, false),
+        ("- a", true, true),
+        ("-a", false, false),
+        ("-1", true, true),
+        (".5", true, true),
+        ("~", true, true),
+        ("True", true, true),
+        ("", true, true),
+        (" padded", true, true),
+        ("*alias", true, true),
+        ("%Y", true, true),
+    ];
+
+    for (string, block, inline) in samples {
+        let mut doc = yaml::from_slice("block: x\ninline: [x]")?;
+        let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+        root.insert_str("block", string);
+        root.get_mut("inline")
+            .and_then(|v| v.into_sequence_mut())
+            .context("missing inline")?
+            .push_string(string);
+
+        let root = doc.as_ref().as_mapping().context("missing root")?;
+        let value = root.get("block").context("missing block")?;
+        assert_eq!(value.requires_quoting_as_bare(), block, "{string:?}");
+
+        let inline_value = root
+            .get("inline")
+            .and_then(|v| v.as_sequence())
+            .and_then(|s| s.get(1).map(|v| v.requires_quoting_as_bare()));
+        assert_eq!(inline_value, Some(inline), "{string:?}");
+
+        // Strings which don't require quoting can be parsed back bare.
+        if !block {
+            let doc = yaml::from_slice(format!("key: {string}"))?;
+            let root = doc.as_ref().as_mapping().context("missing root")?;
+            assert_eq!(root.get("key").and_then(|v| v.as_str()), Some(string));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn error_location() {
+    const INPUT: &str = "first: ok\nsecond: \"ääkkönen \\q\"\n";
+
+    let error = yaml::from_slice(INPUT).unwrap_err();
+    assert_eq!(*error.kind(), yaml::ErrorKind::BadEscape);
+    assert_eq!(&INPUT[error.span()], "\\q");
+    assert_eq!((error.line(), error.column()), (2, 19));
+    assert_eq!(error.to_string(), "error at line 2 column 19: bad escape");
+}
+
+#[test]
+fn single_quoted_unterminated() {
+    for input in ["key: 'x\n", "key: 'it''s\n", "['x]", "'"] {
+        let error = yaml::from_slice(input).unwrap_err();
+        assert_eq!(*error.kind(), yaml::ErrorKind::UnterminatedString);
+        assert_eq!(error.span().end, input.len());
+    }
+
+    let error = yaml::from_slice("first: ok\nsecond: 'x").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "error at line 2 column 9: unterminated string"
+    );
+}
+
+#[test]
+fn escape_unicode() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 'caf\u{e9}'\nb: '\u{1f600} \u{2028}'\nc: 'x\ty'\nd: plain")?;
+    doc.normalize_quotes(yaml::StringKind::Double);
+
+    let expected = "a: \"caf\\xe9\"\nb: \"\\U0001f600 \\u2028\"\nc: \"x\\ty\"\nd: \"plain\"";
+
+    let options = yaml::SerializeOptions::new().escape_unicode(true);
+    assert_eq!(doc.to_string_with(&options), expected);
+    assert_eq!(
+        doc.to_string(),
+        "a: \"caf\u{e9}\"\nb: \"\u{1f600} \u{2028}\"\nc: \"x\\ty\"\nd: \"plain\""
+    );
+
+    let mut output = Vec::new();
+    doc.write_to_with(&mut output, &options)?;
+    assert_eq!(output, expected.as_bytes());
+
+    let reparsed = yaml::from_slice(expected)?;
+    assert!(reparsed.semantic_eq(&doc));
+    Ok(())
+}
+
+#[test]
+fn single_quoted_tab() -> Result<()> {
+    let mut doc = yaml::from_slice("a: \"x\\ty\"\nb: \"x\\ny\"")?;
+    doc.normalize_quotes(yaml::StringKind::Single);
+
+    // Tabs can be written in single-quoted strings, but newlines can't.
+    assert_eq!(doc.to_string(), "a: 'x\ty'\nb: \"x\\ny\"");
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_str()), Some("x\ty"));
+    Ok(())
+}
+
+#[test]
+fn double_quoted_backslash() -> Result<()> {
+    let mut doc = yaml::from_slice("a: \"x\"\nb: C:\\dir\n\"k\": 1")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("a")
+        .context("missing a")?
+        .edit_string(|s| s.push_str("\\y"))?;
+    assert_eq!(doc.to_string(), "a: \"x\\\\y\"\nb: C:\\dir\n\"k\": 1");
+
+    doc.normalize_quotes(yaml::StringKind::Double);
+    assert_eq!(doc.to_string(), "a: \"x\\\\y\"\nb: \"C:\\\\dir\"\n\"k\": 1");
+
+    let conflicts = doc.rename_keys(|key| (key == "k").then(|| String::from("k\\l")));
+    assert!(conflicts.is_empty());
+    assert_eq!(
+        doc.to_string(),
+        "a: \"x\\\\y\"\nb: \"C:\\\\dir\"\n\"k\\\\l\": 1"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_str()), Some("x\\y"));
+    assert_eq!(root.get("b").and_then(|v| v.as_str()), Some("C:\\dir"));
+    assert_eq!(root.get("k\\l").and_then(|v| v.as_u32()), Some(1));
+    Ok(())
+}
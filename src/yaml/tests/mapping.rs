@@ -130,3 +130,958 @@ fn edit_element() -> Result<()> {
     assert_eq!(doc.to_string(), "a:\n  inner:\n    - value\nb:\nc:");
     Ok(())
 }
+
+#[test]
+fn insert_with_comment() -> Result<()> {
+    let mut doc = yaml::from_slice("first: 1\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_with_comment("first", yaml::Separator::Auto, "ignored")
+        .set_u32(2);
+
+    let mut inner = root.insert("inner", yaml::Separator::Auto).make_mapping();
+    inner
+        .insert_with_comment("a", yaml::Separator::Auto, "leading\n\ncomment")
+        .set_u32(1);
+    inner
+        .insert_with_comment("b", yaml::Separator::Auto, "")
+        .set_u32(2);
+
+    root.insert("last", yaml::Separator::Auto).set_string("x");
+
+    assert_eq!(
+        doc.to_string(),
+        "first: 2\ninner:\n  # leading\n  #\n  # comment\n  a: 1\n  b: 2\nlast: x\n"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let inner = root
+        .get("inner")
+        .and_then(|v| v.as_mapping())
+        .context("missing inner")?;
+    assert_eq!(inner.get("a").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(inner.get("b").and_then(|v| v.as_u32()), Some(2));
+    Ok(())
+}
+
+#[test]
+fn inline_mapping_empty_values() -> Result<()> {
+    let mut doc = yaml::from_slice("{a: , b: 2, c:}")?;
+    assert_eq!(doc.to_string(), "{a: , b: 2, c:}");
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.len(), 3);
+    assert!(matches!(
+        root.get("a").map(|v| v.into_any()),
+        Some(yaml::Any::Null)
+    ));
+    assert_eq!(root.get("b").and_then(|v| v.as_u32()), Some(2));
+    assert!(matches!(
+        root.get("c").map(|v| v.into_any()),
+        Some(yaml::Any::Null)
+    ));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("a").context("missing a")?.set_u32(1);
+    assert_eq!(doc.to_string(), "{a: 1, b: 2, c:}");
+    Ok(())
+}
+
+#[test]
+fn inline_mapping_padding() -> Result<()> {
+    let mut doc = yaml::from_slice("{ a: 1, b: 2 }")?;
+    assert_eq!(doc.to_string(), "{ a: 1, b: 2 }");
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.padding(), Some((" ".into(), " ".into())));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.set_padding("", "");
+    assert_eq!(doc.to_string(), "{a: 1, b: 2}");
+    Ok(())
+}
+
+#[test]
+fn inline_mapping_tabs() -> Result<()> {
+    const INPUT: &str = "{a\tb: c\td ,\te: \"f\tg\"\t, h : 'i\tj' }";
+
+    let doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.len(), 3);
+    assert_eq!(root.get("a\tb").and_then(|v| v.as_str()), Some("c\td"));
+    assert_eq!(root.get("e").and_then(|v| v.as_str()), Some("f\tg"));
+    assert_eq!(root.get("h").and_then(|v| v.as_str()), Some("i\tj"));
+    Ok(())
+}
+
+#[test]
+fn present_null_and_absent() -> Result<()> {
+    const INPUT: &str = r"
+    keyword: null
+    tilde: ~
+    empty:
+    nested:
+      empty:
+    last:";
+
+    let doc = yaml::from_slice(INPUT)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    assert_eq!(root.get("keyword").map(|v| v.is_null()), Some(true));
+    assert_eq!(root.get("tilde").map(|v| v.is_null()), Some(true));
+    assert_eq!(root.get("empty").map(|v| v.is_null()), Some(true));
+    assert_eq!(root.get("last").map(|v| v.is_null()), Some(true));
+    assert!(root.get("absent").is_none());
+
+    let nested = root
+        .get("nested")
+        .and_then(|v| v.as_mapping())
+        .context("missing nested")?;
+    assert_eq!(nested.get("empty").map(|v| v.is_null()), Some(true));
+    assert!(nested.get("absent").is_none());
+
+    let doc = yaml::from_slice("{present_null: null, empty: , other: 1}")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("present_null").map(|v| v.is_null()), Some(true));
+    assert_eq!(root.get("empty").map(|v| v.is_null()), Some(true));
+    assert_eq!(root.get("other").map(|v| v.is_null()), Some(false));
+    assert!(root.get("absent").is_none());
+    Ok(())
+}
+
+#[test]
+fn percent_scalars() -> Result<()> {
+    let doc = yaml::from_slice("format: %Y-%m-%d\nformats:\n  - %H:%M\n  - [%s, %%]\n")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(
+        root.get("format").and_then(|v| v.as_str()),
+        Some("%Y-%m-%d")
+    );
+
+    let formats = root
+        .get("formats")
+        .and_then(|v| v.as_sequence())
+        .context("missing formats")?;
+    assert_eq!(formats.get(0).and_then(|v| v.as_str()), Some("%H:%M"));
+
+    let inline = formats
+        .get(1)
+        .and_then(|v| v.as_sequence())
+        .context("missing inline")?;
+    assert_eq!(inline.get(0).and_then(|v| v.as_str()), Some("%s"));
+    assert_eq!(inline.get(1).and_then(|v| v.as_str()), Some("%%"));
+
+    // Without a `---` marker, a leading `%` is not a directive.
+    let doc = yaml::from_slice("%Y-%m-%d")?;
+    assert_eq!(doc.as_ref().as_str(), Some("%Y-%m-%d"));
+
+    let doc = yaml::from_slice("%YAML 1.2\n---\nformat: %Y-%m-%d\n")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(
+        root.get("format").and_then(|v| v.as_str()),
+        Some("%Y-%m-%d")
+    );
+    assert_eq!(doc.to_string(), "%YAML 1.2\n---\nformat: %Y-%m-%d\n");
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "indexmap")]
+fn to_index_map_duplicates() -> Result<()> {
+    let doc = yaml::from_slice("b: 1\na: 2\nb: 3\n")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let map = root.to_index_map();
+    assert!(map.keys().eq(["b", "a"]));
+    assert_eq!(map.get("b").and_then(|v| v.as_u32()), Some(1));
+    Ok(())
+}
+
+#[test]
+fn hash_in_bare_scalars() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        url: http://example.com/#fragment
+        channel: #general
+        issue: C#7 # the issue
+        port: 8080 # the port
+        list: [a#b, c # comment
+          , d]
+        ",
+    )?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(
+        root.get("url").and_then(|v| v.as_str()),
+        Some("http://example.com/#fragment")
+    );
+    assert!(root.get("channel").context("missing channel")?.is_null());
+    assert_eq!(root.get("issue").and_then(|v| v.as_str()), Some("C#7"));
+    assert_eq!(root.get("port").and_then(|v| v.as_u32()), Some(8080));
+
+    let list = root
+        .get("list")
+        .and_then(|v| v.as_sequence())
+        .context("missing list")?;
+    assert_eq!(list.get(0).and_then(|v| v.as_str()), Some("a#b"));
+    assert_eq!(list.get(1).and_then(|v| v.as_str()), Some("c"));
+    assert_eq!(list.get(2).and_then(|v| v.as_str()), Some("d"));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_str("issue", "C#8");
+    root.insert_u32("port", 9090);
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        url: http://example.com/#fragment
+        channel: #general
+        issue: C#8 # the issue
+        port: 9090 # the port
+        list: [a#b, c # comment
+          , d]
+        "
+    );
+    Ok(())
+}
+
+#[test]
+fn quoted_keys() -> Result<()> {
+    let doc = yaml::from_slice("a: 1\n\"b c\": 2\n'd''e': 3\nf: {\"g.h\": 4, 'i': 5}\n")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("b c").and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(root.get("d'e").and_then(|v| v.as_u32()), Some(3));
+
+    let f = root
+        .get("f")
+        .and_then(|v| v.as_mapping())
+        .context("missing f")?;
+    assert_eq!(f.get("g.h").and_then(|v| v.as_u32()), Some(4));
+    assert_eq!(f.get("i").and_then(|v| v.as_u32()), Some(5));
+    Ok(())
+}
+
+#[test]
+fn rename_keys_conflicts() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        "items:\n  - {a: 1, b: 2, c: 3}\n  - {x: 1, 'y': 2}\nsame: {A: 1, B: 2}\n",
+    )?;
+
+    // Both `a` and `b` map to `c`, so neither is renamed. `c` can still be
+    // renamed since it is renamed to something else.
+    let conflicts = doc.rename_keys(|key| match key {
+        "a" | "b" => Some(String::from("c")),
+        "c" => Some(String::from("d")),
+        "y" => Some(String::from("needs: quotes")),
+        "x" => Some(String::from("x")),
+        "A" => Some(String::from("B")),
+        _ => None,
+    });
+
+    assert_eq!(conflicts, ["items.0.a", "items.0.b", "same.A"]);
+    assert_eq!(
+        doc.to_string(),
+        "items:\n  - {a: 1, b: 2, d: 3}\n  - {x: 1, 'needs: quotes': 2}\nsame: {A: 1, B: 2}\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn inline_multiline() -> Result<()> {
+    const INPUT: &str = "env: {\n  a: 1,\n  b: [x,\n     y],\n  # comment\n  c: \"q\"\n}\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let env = root
+        .get("env")
+        .and_then(|v| v.as_mapping())
+        .context("missing env")?;
+    assert_eq!(env.get("a").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(env.get_str("c"), Some("q".into()));
+
+    let b = env
+        .get("b")
+        .and_then(|v| v.as_sequence())
+        .context("missing b")?;
+    assert_eq!(b.get(1).and_then(|v| v.as_str()), Some("y"));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut env = root
+        .get_mut("env")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing env")?;
+    env.get_mut("a").context("missing a")?.set_string("one");
+    assert_eq!(doc.to_string(), INPUT.replace("a: 1", "a: one"));
+    Ok(())
+}
+
+#[test]
+fn insert_null() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\nd:\n  - e\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_null("b", yaml::Null::Empty);
+    root.insert_null("c", yaml::Null::Tilde);
+    root.insert_null("a", yaml::Null::Keyword);
+    root.insert_null("d", yaml::Null::Empty);
+    assert_eq!(doc.to_string(), "a: null\nd:\nb:\nc: ~\n");
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert!(root.iter().all(|(_, value)| value.is_null()));
+    Ok(())
+}
+
+#[test]
+fn empty_strings_and_nulls() -> Result<()> {
+    const INPUT: &str = "a: \"\"\nb: ''\nc:\nd: {e: \"\", f: '', g:}\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let d = root
+        .get("d")
+        .and_then(|v| v.as_mapping())
+        .context("missing d")?;
+
+    for mapping in [&root, &d] {
+        let mut values = mapping.iter().map(|(_, value)| value);
+        let (Some(first), Some(second), Some(third)) =
+            (values.next(), values.next(), values.next())
+        else {
+            panic!("missing values");
+        };
+
+        assert_eq!(first.as_str(), Some(""));
+        assert!(!first.is_null());
+        assert_eq!(second.as_str(), Some(""));
+        assert!(!second.is_null());
+        assert_eq!(third.as_str(), None);
+        assert!(third.is_null());
+    }
+
+    assert!(root.get("missing").is_none());
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("c").context("missing c")?.set_string("");
+    assert_eq!(
+        doc.to_string(),
+        "a: \"\"\nb: ''\nc: ''\nd: {e: \"\", f: '', g:}\n"
+    );
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get_str("c"), Some("".into()));
+    Ok(())
+}
+
+#[test]
+fn dedup_keys() -> Result<()> {
+    let mut doc = yaml::from_slice("{a: 1, b: 2, a: 3, a: 4}")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let values = root
+        .get_all("a")
+        .flat_map(|v| v.as_u32())
+        .collect::<Vec<_>>();
+    assert_eq!(values, [1, 3, 4]);
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert_eq!(root.dedup_keys(yaml::DedupKeys::KeepLast), 2);
+    assert_eq!(root.dedup_keys(yaml::DedupKeys::KeepLast), 0);
+    assert_eq!(doc.to_string(), "{b: 2, a: 4}");
+    Ok(())
+}
+
+#[test]
+fn iter_nth_back() -> Result<()> {
+    let doc = yaml::from_slice("a: 1\nb: 2\nc: 3\n")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let (key, value) = root.iter().rev().nth(1).context("missing item")?;
+    assert_eq!(key, "b");
+    assert_eq!(value.as_u32(), Some(2));
+
+    let (key, _) = root.iter().nth_back(0).context("missing item")?;
+    assert_eq!(key, "c");
+    assert!(root.iter().nth_back(3).is_none());
+    Ok(())
+}
+
+#[test]
+fn trailing_comments() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        jobs: # all jobs
+          build:
+            runs-on: ubuntu # default runner
+          test: {
+            a: 1, # first
+            b: 2 # second
+          }
+        name: ci
+        ",
+    )?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let jobs = root.get("jobs").context("missing jobs")?;
+    assert_eq!(jobs.comment(), Some("all jobs".into()));
+
+    let jobs = jobs.as_mapping().context("missing jobs")?;
+    let build = jobs.get("build").context("missing build")?;
+    assert_eq!(build.comment(), None);
+    let runs_on = build.as_mapping().and_then(|m| m.get("runs-on"));
+    assert_eq!(
+        runs_on.and_then(|v| v.comment()),
+        Some("default runner".into())
+    );
+
+    let test = jobs
+        .get("test")
+        .and_then(|v| v.as_mapping())
+        .context("missing test")?;
+    assert_eq!(
+        test.get("a").and_then(|v| v.comment()),
+        Some("first".into())
+    );
+    assert_eq!(
+        test.get("b").and_then(|v| v.comment()),
+        Some("second".into())
+    );
+    assert_eq!(root.get("name").and_then(|v| v.comment()), None);
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("name")
+        .context("missing name")?
+        .set_string("release");
+    assert!(doc
+        .to_string()
+        .contains("runs-on: ubuntu # default runner\n"));
+    Ok(())
+}
+
+#[test]
+fn remove_first() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        outer:
+          first: 1 # about first
+          second: 2
+        inline: {first: 1, second: 2}
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    let mut outer = root
+        .get_mut("outer")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing outer")?;
+    assert!(outer.remove("first"));
+
+    let mut inline = root
+        .get_mut("inline")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing inline")?;
+    assert!(inline.remove("first"));
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        outer:
+          second: 2
+        inline: {second: 2}
+        "
+    );
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    let mut outer = root
+        .get_mut("outer")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing outer")?;
+    assert!(outer.remove("second"));
+    assert!(outer.as_ref().is_empty());
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        outer:
+        inline: {second: 2}
+        "
+    );
+    Ok(())
+}
+
+#[test]
+fn remove_first_keeps_comments() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\n# about b\nb: 2\n")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert!(root.remove("a"));
+    assert_eq!(doc.to_string(), "# about b\nb: 2\n");
+
+    let mut doc = yaml::from_slice(
+        r"
+        outer:
+          a: 1 # about a
+          # about b
+          b: 2
+        seq:
+          - a: 1
+            # about b
+            b: 2
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    let mut outer = root
+        .get_mut("outer")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing outer")?;
+    assert!(outer.remove("a"));
+
+    let mut inner = root
+        .get_mut("seq")
+        .and_then(|v| v.into_sequence_mut())
+        .and_then(|s| s.get_into_mut(0))
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing inner")?;
+    assert!(inner.remove("a"));
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        outer:
+          # about b
+          b: 2
+        seq:
+          - # about b
+            b: 2
+        "
+    );
+    Ok(())
+}
+
+#[test]
+fn comment_blocks() -> Result<()> {
+    const INPUT: &str = r"
+    ##########
+    # Server #
+    ##########
+
+    host: localhost # the host
+
+    # The port to listen on.
+    port: 80 # default
+
+    # Trailing banner.
+    ";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let port = root.get("port").context("missing port")?;
+    assert_eq!(port.comment(), Some("default".into()));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("workers", 4);
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+    ##########
+    # Server #
+    ##########
+
+    host: localhost # the host
+
+    # The port to listen on.
+    port: 80 # default
+    workers: 4
+
+    # Trailing banner.
+    "
+    );
+
+    let mut doc = yaml::from_slice("items:\n  - one # first\n  - two # second\nafter: 1\n")?;
+
+    let mut items = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("items"))
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing items")?;
+    items.push_string("three");
+
+    assert_eq!(
+        doc.to_string(),
+        "items:\n  - one # first\n  - two # second\n  - three\nafter: 1\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn rename_key() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        spec:
+            # The policy to restart with.
+            restartPolicy: Always # or Never
+            ports: {http: 80, https: 443}
+        ",
+    )?;
+
+    let mut spec = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("spec"))
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing spec")?;
+
+    assert_eq!(
+        spec.rename_key("restartPolicy", "restart_policy"),
+        yaml::RenameKey::Renamed
+    );
+
+    let mut ports = spec
+        .get_into_mut("ports")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing ports")?;
+
+    assert_eq!(
+        ports.rename_key("http", "plain, text"),
+        yaml::RenameKey::Renamed
+    );
+    assert_eq!(
+        ports.rename_key("https", "plain, text"),
+        yaml::RenameKey::Collides
+    );
+    assert_eq!(ports.rename_key("http", "https"), yaml::RenameKey::Missing);
+    assert_eq!(ports.rename_key("https", "https"), yaml::RenameKey::Renamed);
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        spec:
+            # The policy to restart with.
+            restart_policy: Always # or Never
+            ports: {'plain, text': 80, https: 443}
+        "
+    );
+    Ok(())
+}
+
+#[test]
+fn get_or_insert_with_nested() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        services:
+            - name: web
+        ",
+    )?;
+
+    let mut service = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("services"))
+        .and_then(|v| v.into_sequence_mut())
+        .and_then(|s| s.get_into_mut(0))
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing service")?;
+
+    for port in [80, 443] {
+        let mut deploy = service
+            .get_or_insert_with("deploy", || None::<&str>)
+            .make_mapping();
+
+        deploy
+            .get_or_insert_with("ports", || None::<&str>)
+            .make_sequence()
+            .push_u32(port);
+    }
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        services:
+            - name: web
+              deploy:
+                ports:
+                  - 80
+                  - 443
+        "
+    );
+    Ok(())
+}
+
+#[test]
+fn clear_inline_and_block() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        inline_mapping: {x: 1, y: 2}
+        inline_sequence: [1, 2,]
+        padded: [ 1 ]
+        block_mapping:
+          x: 1
+          y: 2
+        block_sequence:
+          - 1
+          - 2
+        last: 1
+        ",
+    )?;
+
+    let keys = [
+        "inline_mapping",
+        "inline_sequence",
+        "padded",
+        "block_mapping",
+        "block_sequence",
+    ];
+
+    for key in keys {
+        let mut root = doc
+            .as_mut()
+            .into_mapping_mut()
+            .context("missing root mapping")?;
+        let mut value = root.get_mut(key).context("missing key")?;
+
+        if let Some(mut mapping) = value.as_mapping_mut() {
+            mapping.clear();
+        } else if let Some(mut sequence) = value.as_sequence_mut() {
+            sequence.clear();
+        }
+    }
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        inline_mapping: {}
+        inline_sequence: []
+        padded: [ ]
+        block_mapping:
+        block_sequence:
+        last: 1
+        "
+    );
+
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+
+    for key in keys {
+        let value = root.get(key).context("missing key")?;
+        let len = value
+            .as_mapping()
+            .map(|m| m.len())
+            .or_else(|| value.as_sequence().map(|s| s.len()));
+        assert_eq!(len, Some(0));
+    }
+
+    let reparsed = yaml::from_slice(doc.to_string())?;
+    let root = reparsed
+        .as_ref()
+        .as_mapping()
+        .context("missing root mapping")?;
+    assert!(root.get("block_mapping").is_some_and(|v| v.is_null()));
+    assert!(root.get("block_sequence").is_some_and(|v| v.is_null()));
+    Ok(())
+}
+
+#[test]
+fn set_null_kinds() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        keyword: 1
+        tilde: 2
+        empty: 3
+        inline: [4, 5, 6]
+        ",
+    )?;
+
+    let kinds = [
+        ("keyword", yaml::Null::Keyword),
+        ("tilde", yaml::Null::Tilde),
+        ("empty", yaml::Null::Empty),
+    ];
+
+    let mut root = doc
+        .as_mut()
+        .into_mapping_mut()
+        .context("missing root mapping")?;
+
+    for (key, kind) in kinds {
+        root.get_mut(key).context("missing key")?.set_null(kind);
+    }
+
+    let mut inline = root
+        .get_into_mut("inline")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing inline")?;
+
+    for (index, (_, kind)) in kinds.into_iter().enumerate() {
+        inline
+            .get_mut(index)
+            .context("missing item")?
+            .set_null(kind);
+    }
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        keyword: null
+        tilde: ~
+        empty:
+        inline: [null, ~, null]
+        "
+    );
+
+    let reparsed = yaml::from_slice(doc.to_string())?;
+    let root = reparsed
+        .as_ref()
+        .as_mapping()
+        .context("missing root mapping")?;
+
+    for (key, _) in kinds {
+        assert!(root.get(key).is_some_and(|v| v.is_null()));
+    }
+
+    let inline = root
+        .get("inline")
+        .and_then(|v| v.as_sequence())
+        .context("missing inline")?;
+    assert_eq!(inline.len(), 3);
+    assert!(inline.iter().all(|v| v.is_null()));
+    Ok(())
+}
+
+#[test]
+fn sort_keys_trailing_comments() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        outer:
+          c: 3 # three
+          # about b
+          b: [1, 2] # two
+          a: |
+            text
+        last: 1 # last
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("outer")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing outer")?
+        .sort_keys();
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        outer:
+          a: |
+            text
+          # about b
+          b: [1, 2] # two
+          c: 3 # three
+        last: 1 # last
+        ",
+    );
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.sort_keys_by(|a, b| a.len().cmp(&b.len()));
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        last: 1 # last
+        outer:
+          a: |
+            text
+          # about b
+          b: [1, 2] # two
+          c: 3 # three
+        ",
+    );
+    Ok(())
+}
+
+#[test]
+fn get_mut_preserves_formatting() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r#"
+        "host":   localhost # the host
+        port:     80 # the port
+        "#,
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("port").context("missing port")?.set_u32(8080);
+    root.get_mut("host")
+        .context("missing host")?
+        .set_string("example.com");
+    assert!(root.get_mut("missing").is_none());
+
+    assert_eq!(
+        doc.to_string(),
+        r#"
+        "host":   example.com # the host
+        port:     8080 # the port
+        "#
+    );
+    Ok(())
+}
+
+#[test]
+fn with_key_kind_bare_requires_quoting() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    for (n, key) in ["plain", "a: b", "#c", "", "true", "a:b"]
+        .into_iter()
+        .enumerate()
+    {
+        root.insert(key, yaml::Separator::Auto)
+            .with_key_kind(yaml::StringKind::Bare)
+            .set_u32(u32::try_from(n)?);
+    }
+
+    assert_eq!(
+        doc.to_string(),
+        "a: 1\nplain: 0\n'a: b': 1\n'#c': 2\n'': 3\n'true': 4\n'a:b': 5"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    for (n, key) in ["plain", "a: b", "#c", "", "true", "a:b"]
+        .into_iter()
+        .enumerate()
+    {
+        assert_eq!(
+            root.get(key).and_then(|v| v.as_u32()),
+            Some(u32::try_from(n)?)
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn to_string_with_order_comments() -> Result<()> {
+    let doc = yaml::from_slice("b: 1 # about b\n# about a\na: 2\n")?;
+
+    // Trailing comments move along with their keys, while full-line comments
+    // stay in place as with `PrettyOptions::sort_keys`.
+    assert_eq!(
+        doc.to_string_with_order(&["a", "b"]),
+        "a: 2\n# about a\nb: 1 # about b\n"
+    );
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn merge_documents() -> Result<()> {
+    let mut base = yaml::from_slice(
+        r"
+        name: web
+        resources: ~
+        items:
+          - a
+        ",
+    )?;
+
+    let other = yaml::from_slice(
+        r"
+        resources:
+            limits:
+                cpu: 1 # one core
+        items:
+            - b
+            - &anchor {c: 1}
+            - *anchor
+        extra:
+            - x
+        ",
+    )?;
+
+    base.merge_with(&other, yaml::MergeStrategy::Concat);
+
+    assert_eq!(
+        base.to_string(),
+        r"
+        name: web
+        resources:
+          limits:
+              cpu: 1 # one core
+        items:
+          - a
+          - b
+          - &anchor {c: 1}
+          - *anchor
+        extra:
+          - x
+        ",
+    );
+
+    let root = base.as_ref().as_mapping().context("missing root")?;
+    let items = root
+        .get("items")
+        .and_then(|v| v.as_sequence())
+        .context("missing items")?;
+    let alias = items
+        .get(3)
+        .and_then(|v| v.resolve_alias())
+        .context("missing alias")?;
+    assert_eq!(
+        alias.as_mapping().and_then(|m| m.get("c")?.as_u32()),
+        Some(1)
+    );
+
+    let mut base = yaml::from_slice("a: 1")?;
+    base.merge(&yaml::from_slice("[1, 2]")?);
+    assert_eq!(base.to_string(), "[1, 2]");
+    Ok(())
+}
+
+#[test]
+fn merge_quoted_keys() -> Result<()> {
+    let mut base = yaml::from_slice("a: 1\ninline: {x: 1}\n")?;
+    let other = yaml::from_slice("'a: b': 1\n\"#k\": 2\ninline: {'y': 2, z: 3}\n")?;
+
+    base.merge(&other);
+
+    assert_eq!(
+        base.to_string(),
+        "a: 1\ninline: {x: 1, 'y': 2, z: 3}\n'a: b': 1\n\"#k\": 2\n"
+    );
+
+    let doc = yaml::from_slice(base.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("a: b").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(root.get("#k").and_then(|v| v.as_u32()), Some(2));
+    Ok(())
+}
+
+#[test]
+fn merge_keeps_anchors() -> Result<()> {
+    let mut base = yaml::from_slice("a: &x 1\nb: &y\n  c: 2\nd: *x\ne: *y\n")?;
+    let other = yaml::from_slice("a: 10\nb: 20\n")?;
+
+    base.merge(&other);
+
+    assert_eq!(base.to_string(), "a: &x 10\nb: &y\n  20\nd: *x\ne: *y\n");
+
+    let root = base.as_ref().as_mapping().context("missing root")?;
+    let d = root.get("d").and_then(|v| v.resolve_alias());
+    let e = root.get("e").and_then(|v| v.resolve_alias());
+    assert_eq!(d.and_then(|v| v.as_u32()), Some(10));
+    assert_eq!(e.and_then(|v| v.as_u32()), Some(20));
+    Ok(())
+}
+
+#[test]
+fn merge_renames_colliding_anchors() -> Result<()> {
+    let mut base = yaml::from_slice("a: &x 1\nb: 2\nc: *x\n")?;
+    let other = yaml::from_slice("b: &x 5\nd: [&x_2 6, *x_2]\n")?;
+
+    base.merge(&other);
+
+    assert_eq!(
+        base.to_string(),
+        "a: &x 1\nb: &x_2 5\nc: *x\nd: [&x_2_2 6, *x_2_2]\n"
+    );
+
+    let root = base.as_ref().as_mapping().context("missing root")?;
+    let c = root.get("c").and_then(|v| v.resolve_alias());
+    assert_eq!(c.and_then(|v| v.as_u32()), Some(1));
+
+    let mut base = yaml::from_slice("a: &x 1\nc: *x\n")?;
+    let other = yaml::from_slice("b: {k: &x 5, l: *x}\n")?;
+
+    base.merge(&other);
+
+    assert_eq!(
+        base.to_string(),
+        "a: &x 1\nc: *x\nb: {k: &x_2 5, l: *x_2}\n"
+    );
+
+    let root = base.as_ref().as_mapping().context("missing root")?;
+    let l = root
+        .get("b")
+        .and_then(|v| v.as_mapping()?.get("l")?.resolve_alias());
+    assert_eq!(l.and_then(|v| v.as_u32()), Some(5));
+    Ok(())
+}
+
+#[test]
+fn merge_block_into_inline() -> Result<()> {
+    let mut base = yaml::from_slice("env: {A: 1}\nargs: [--verbose]\n")?;
+
+    let other = yaml::from_slice(
+        "env:\n  B:\n    - x # first\n    - y\n  C:\n    d: |\n      line\n    e: 2\nargs:\n  - k: v\n    l: [1]\n",
+    )?;
+
+    base.merge_with(&other, yaml::MergeStrategy::Concat);
+
+    assert_eq!(
+        base.to_string(),
+        "env: {A: 1, B: [x, y], C: {d: \"line\\n\", e: 2}}\nargs: [--verbose, {k: v, l: [1]}]\n"
+    );
+
+    let doc = yaml::from_slice(base.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let env = root
+        .get("env")
+        .and_then(|v| v.as_mapping())
+        .context("missing env")?;
+    let c = env
+        .get("C")
+        .and_then(|v| v.as_mapping())
+        .context("missing C")?;
+    assert_eq!(c.get("d").and_then(|v| v.as_str()), Some("line\n"));
+    assert_eq!(c.get("e").and_then(|v| v.as_u32()), Some(2));
+    Ok(())
+}
@@ -1,8 +1,22 @@
 mod actions;
+mod anchors;
+#[cfg(feature = "serde")]
+mod deserialize;
 mod escape;
 mod mapping;
+mod merge;
 mod multiline;
+mod number;
+mod options;
+mod pretty;
+mod raw_yaml;
+mod separator;
 mod sequence;
+#[cfg(feature = "serde")]
+mod serialize;
+mod span;
+mod tags;
+mod timestamp;
 
 use anyhow::{Context, Result};
 
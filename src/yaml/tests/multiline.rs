@@ -181,7 +181,7 @@ fn string_spaces() -> Result<()> {
         doc.as_ref()
             .as_mapping()
             .and_then(|m| m.get("first")?.as_str()),
-        Some("foo bar baz\n")
+        Some("foo\nbar baz\n")
     );
 
     assert_eq!(
@@ -198,3 +198,213 @@ fn string_spaces() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn set_block_nested() -> Result<()> {
+    let mut doc = yaml::from_slice("jobs:\n  build:\n    script: make\n  steps:\n    - make\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut jobs = root
+        .get_mut("jobs")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing jobs")?;
+
+    jobs.get_mut("build")
+        .and_then(|v| v.into_mapping_mut())
+        .and_then(|m| m.get_into_mut("script"))
+        .context("missing script")?
+        .set_block(
+            ["make", "make test"],
+            yaml::Block::Literal(yaml::Chomp::Clip),
+        );
+
+    jobs.get_mut("steps")
+        .and_then(|v| v.into_sequence_mut())
+        .and_then(|s| s.get_into_mut(0))
+        .context("missing step")?
+        .set_block(
+            ["make", "make test"],
+            yaml::Block::Literal(yaml::Chomp::Strip),
+        );
+
+    assert_eq!(
+        doc.to_string(),
+        "jobs:\n  build:\n    script: |\n      make\n      make test\n  steps:\n    - |-\n      make\n      make test\n"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let jobs = doc
+        .as_ref()
+        .as_mapping()
+        .and_then(|m| m.get("jobs")?.as_mapping())
+        .context("missing jobs")?;
+    assert_eq!(
+        jobs.get("build")
+            .and_then(|v| v.as_mapping()?.get("script")?.as_str()),
+        Some("make\nmake test\n")
+    );
+    Ok(())
+}
+
+#[test]
+fn block_scalars() -> Result<()> {
+    const INPUT: &str = r"
+    literal: |
+      one
+        indented
+      two
+    folded: >
+
+      one
+      two
+
+      three
+        indented
+      four
+    explicit: |2
+        indented
+    keep: |+
+      one
+
+    strip: >-
+      one
+      two
+
+
+    clip: |
+      one
+
+
+    last: >+
+      one
+    ";
+
+    let doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    let get = |key| root.get(key).and_then(|v| v.as_str());
+
+    assert_eq!(get("literal"), Some("one\n  indented\ntwo\n"));
+    assert_eq!(get("folded"), Some("\none two\nthree\n  indented\nfour\n"));
+    assert_eq!(get("explicit"), Some("  indented\n"));
+    assert_eq!(get("keep"), Some("one\n\n"));
+    assert_eq!(get("strip"), Some("one two"));
+    assert_eq!(get("clip"), Some("one\n"));
+    // The indentation on the final line of the input is an empty line.
+    assert_eq!(get("last"), Some("one\n\n"));
+
+    let block = |key| root.get(key).and_then(|v| v.block());
+    assert_eq!(
+        block("explicit"),
+        Some(yaml::Block::Literal(yaml::Chomp::Clip))
+    );
+    assert_eq!(block("keep"), Some(yaml::Block::Literal(yaml::Chomp::Keep)));
+    assert_eq!(
+        block("strip"),
+        Some(yaml::Block::Folded(yaml::Chomp::Strip))
+    );
+    Ok(())
+}
+
+#[test]
+fn wrap_folded() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        items:
+          - >-
+            parsed text which is left alone
+        ",
+    )?;
+
+    let mut items = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("items")?.into_sequence_mut())
+        .context("missing items")?;
+
+    items.push_block(
+        ["spaces  are kept", "with an extraordinarily-long-word"],
+        yaml::Block::Folded(yaml::Chomp::Strip),
+    );
+    items.push_block(["short"], yaml::Block::Folded(yaml::Chomp::Keep));
+    items.push_block(
+        ["literal blocks are never wrapped"],
+        yaml::Block::Literal(yaml::Chomp::Clip),
+    );
+
+    let options = yaml::SerializeOptions::new().max_width(Some(28));
+    let output = doc.to_string_with(&options);
+
+    assert_eq!(
+        output,
+        r"
+        items:
+          - >-
+            parsed text which is left alone
+          - >-
+            spaces  are kept
+            with an
+            extraordinarily-long-word
+          - >+
+            short
+          - |
+            literal blocks are never wrapped
+        "
+    );
+
+    let wrapped = yaml::from_slice(&output)?;
+    let items = wrapped
+        .as_ref()
+        .as_mapping()
+        .and_then(|m| m.get("items")?.as_sequence())
+        .context("missing items")?;
+
+    assert_eq!(
+        items.get(1).and_then(|v| v.as_str()),
+        Some("spaces  are kept with an extraordinarily-long-word")
+    );
+    assert_eq!(items.get(2).and_then(|v| v.as_str()), Some("short\n"));
+
+    assert_eq!(
+        doc.to_string_with(&yaml::SerializeOptions::new()),
+        doc.to_string()
+    );
+    Ok(())
+}
+
+#[test]
+fn wrap_folded_copies() -> Result<()> {
+    const TEXT: &str = "  - >-\n    parsed text which is left alone\n";
+
+    let mut doc = yaml::from_slice(format!("items:\n{TEXT}"))?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let items = root
+        .get("items")
+        .and_then(|v| v.as_sequence())
+        .context("missing items")?;
+    let parsed = items.get(0).context("missing parsed")?.id();
+
+    let copy = doc.clone_value(parsed);
+
+    let mut items = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("items")?.into_sequence_mut())
+        .context("missing items")?;
+
+    items.push(yaml::Separator::Auto).copy_from(parsed);
+    items.push(yaml::Separator::Auto).copy_from(copy);
+
+    let other = yaml::from_slice(format!("merged:\n{TEXT}"))?;
+    doc.merge(&other);
+
+    let options = yaml::SerializeOptions::new().max_width(Some(20));
+
+    assert_eq!(
+        doc.to_string_with(&options),
+        format!("items:\n{TEXT}{TEXT}{TEXT}merged:\n{TEXT}")
+    );
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn radix_integers() -> Result<()> {
+    const INPUT: &str = r"
+    hex: 0xFF
+    mixed: 0xaBcD
+    octal: 0o755
+    binary: 0b1010
+    separated: 1_000_000
+    negative: -0x80
+    separated_hex: 0xdead_beef
+    bad_digit: 0o8
+    empty: 0x
+    trailing: 1_
+    trailing_hex: 0x_FF_
+    repeated: 1__0
+    tagged_trailing: !!int 1_
+    tagged_repeated: !!int 0x1__0
+    ";
+
+    let doc = yaml::from_slice(INPUT)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    assert_eq!(root.get("hex").and_then(|v| v.as_u8()), Some(0xff));
+    assert_eq!(root.get("hex").and_then(|v| v.as_i8()), None);
+    assert_eq!(root.get("mixed").and_then(|v| v.as_u32()), Some(0xabcd));
+    assert_eq!(root.get("octal").and_then(|v| v.as_u32()), Some(0o755));
+    assert_eq!(root.get("binary").and_then(|v| v.as_u64()), Some(0b1010));
+    assert_eq!(
+        root.get("separated").and_then(|v| v.as_i64()),
+        Some(1_000_000)
+    );
+    assert_eq!(root.get("negative").and_then(|v| v.as_i8()), Some(-0x80));
+    assert_eq!(root.get("negative").and_then(|v| v.as_u128()), None);
+    assert_eq!(
+        root.get("separated_hex").and_then(|v| v.as_u32()),
+        Some(0xdead_beef)
+    );
+    assert_eq!(root.get("bad_digit").and_then(|v| v.as_str()), Some("0o8"));
+    assert_eq!(root.get("empty").and_then(|v| v.as_str()), Some("0x"));
+    assert_eq!(root.get("trailing").and_then(|v| v.as_i64()), None);
+    assert_eq!(root.get("trailing").and_then(|v| v.as_str()), Some("1_"));
+    assert_eq!(root.get("trailing_hex").and_then(|v| v.as_u32()), None);
+    assert_eq!(
+        root.get("trailing_hex").and_then(|v| v.as_str()),
+        Some("0x_FF_")
+    );
+    assert_eq!(root.get("repeated").and_then(|v| v.as_i64()), None);
+    assert_eq!(root.get("repeated").and_then(|v| v.as_str()), Some("1__0"));
+    assert_eq!(root.get("tagged_trailing").and_then(|v| v.as_i64()), None);
+    assert_eq!(root.get("tagged_repeated").and_then(|v| v.as_i64()), None);
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn integer_ranges() -> Result<()> {
+    let doc = yaml::from_slice(
+        r"
+        max: 340282366920938463463374607431768211455
+        min: -170141183460469231731687303715884105728
+        huge: 340282366920938463463374607431768211456
+        negative_hex: -0x80
+        negative_zero: -0
+        negative_zero_hex: -0x0
+        zero: 0
+        float: 1.0
+        ",
+    )?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let integer = |key| root.get(key).and_then(|v| v.as_integer());
+
+    let max = integer("max").context("missing max")?;
+    assert_eq!(u128::try_from(max)?, u128::MAX);
+    assert!(i128::try_from(max).is_err());
+
+    let min = integer("min").context("missing min")?;
+    assert_eq!(i128::try_from(min)?, i128::MIN);
+    assert!(min < max);
+
+    let negative = integer("negative_hex").context("missing negative_hex")?;
+    assert_eq!(i8::try_from(negative)?, -0x80);
+    assert_eq!(
+        u8::try_from(negative).map_err(|e| e.integer()),
+        Err(negative)
+    );
+    assert_eq!(negative.to_string(), "-128");
+
+    let zero = integer("zero").context("missing zero")?;
+
+    for key in ["negative_zero", "negative_zero_hex"] {
+        let negative_zero = integer(key).context("missing negative zero")?;
+        assert!(!negative_zero.is_negative());
+        assert_eq!(negative_zero, zero);
+        assert_eq!(negative_zero.cmp(&zero), core::cmp::Ordering::Equal);
+        assert_eq!(negative_zero.to_string(), "0");
+    }
+
+    assert!(integer("huge").is_none());
+    assert!(integer("float").is_none());
+    Ok(())
+}
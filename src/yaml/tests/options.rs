@@ -0,0 +1,62 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn yaml_1_1_bools_quoted() -> Result<()> {
+    let options = yaml::ParseOptions::new().yaml_1_1_bools(true);
+
+    let mut doc = yaml::from_slice_with("a: on\nb: [x]\n", &options)?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    for (key, value) in [("c", "yes"), ("d", "Off"), ("e", "y"), ("f", "maybe")] {
+        root.insert_str(key, value);
+    }
+
+    root.get_mut("b")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing b")?
+        .push_string("N");
+
+    assert_eq!(
+        doc.to_string(),
+        "a: on\nb: [x, 'N']\nc: 'yes'\nd: 'Off'\ne: 'y'\nf: maybe\n"
+    );
+
+    let doc = yaml::from_slice_with(doc.to_string(), &options)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_bool()), Some(true));
+    assert_eq!(root.get("c").and_then(|v| v.as_str()), Some("yes"));
+    assert_eq!(root.get("d").and_then(|v| v.as_str()), Some("Off"));
+
+    // Without the option the spellings are plain strings.
+    let mut doc = yaml::from_slice("a: 1")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_str("b", "yes");
+    assert_eq!(doc.to_string(), "a: 1\nb: yes");
+    Ok(())
+}
+
+#[test]
+fn yaml_1_1_bools_raw_yaml() -> Result<()> {
+    let options = yaml::ParseOptions::new().yaml_1_1_bools(true);
+
+    let mut doc = yaml::from_slice_with("a: 1", &options)?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("a")
+        .context("missing a")?
+        .set_raw_yaml("off")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_bool()), Some(false));
+
+    let mut doc = yaml::from_slice("a: 1")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("a")
+        .context("missing a")?
+        .set_raw_yaml("off")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_str()), Some("off"));
+    Ok(())
+}
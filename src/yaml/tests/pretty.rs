@@ -0,0 +1,203 @@
+use std::num::NonZeroUsize;
+
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn reindent_narrower() -> Result<()> {
+    let doc = yaml::from_slice(
+        "spec:\n    # comment\n    script: |\n        echo hello\n\n        echo world\n    steps:\n    -   name: build\n        run: make\n    -   [a, b]\n",
+    )?;
+
+    let indent = NonZeroUsize::new(2).context("zero indent")?;
+    let options = yaml::PrettyOptions::new().indent(indent);
+    let pretty = doc.to_string_pretty(&options);
+
+    assert_eq!(
+        pretty,
+        "spec:\n  # comment\n  script: |\n      echo hello\n\n      echo world\n  steps:\n  - name: build\n    run: make\n  - [a, b]\n"
+    );
+
+    assert!(yaml::from_slice(&pretty)?.semantic_eq_ordered(&doc));
+    Ok(())
+}
+
+#[test]
+fn reindent_narrowest() -> Result<()> {
+    let doc = yaml::from_slice("a:\n  b: 1\n  c:\n    - d\n")?;
+
+    let indent = NonZeroUsize::new(1).context("zero indent")?;
+    let pretty = doc.to_string_pretty(&yaml::PrettyOptions::new().indent(indent));
+
+    assert_eq!(pretty, "a:\n b: 1\n c:\n  - d\n");
+    assert!(yaml::from_slice(&pretty)?.semantic_eq_ordered(&doc));
+    Ok(())
+}
+
+#[test]
+fn default_is_identity() -> Result<()> {
+    let input = "b:   {x: 'y' ,z: 1}\na:\n     - 1\n";
+    let doc = yaml::from_slice(input)?;
+    assert_eq!(doc.to_string_pretty(&yaml::PrettyOptions::new()), input);
+    Ok(())
+}
+
+#[test]
+fn expand_nested() -> Result<()> {
+    let doc = yaml::from_slice("- {name: a very long name, tags: [one, two, three]}\n")?;
+
+    let options = yaml::PrettyOptions::new().max_width(30);
+    let pretty = doc.to_string_pretty(&options);
+
+    assert_eq!(
+        pretty,
+        "- name: a very long name\n  tags: [one, two, three]\n"
+    );
+
+    assert!(yaml::from_slice(&pretty)?.semantic_eq_ordered(&doc));
+    Ok(())
+}
+
+#[test]
+fn normalize_quotes() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        "a: \"line\\nbreak\"\nb: 'it''s'\nc: [x, 'true', \"{y}\"]\nd: |\n  block\n",
+    )?;
+
+    doc.normalize_quotes(yaml::StringKind::Single);
+    assert_eq!(
+        doc.to_string(),
+        "a: \"line\\nbreak\"\nb: 'it''s'\nc: ['x', 'true', '{y}']\nd: |\n  block\n"
+    );
+
+    doc.normalize_quotes(yaml::StringKind::Double);
+    assert_eq!(
+        doc.to_string(),
+        "a: \"line\\nbreak\"\nb: \"it's\"\nc: [\"x\", \"true\", \"{y}\"]\nd: |\n  block\n"
+    );
+
+    doc.normalize_quotes(yaml::StringKind::Bare);
+    assert_eq!(
+        doc.to_string(),
+        "a: \"line\\nbreak\"\nb: \"it's\"\nc: [x, \"true\", \"{y}\"]\nd: |\n  block\n"
+    );
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get_str("a"), Some("line\nbreak".into()));
+    assert_eq!(root.get_str("b"), Some("it's".into()));
+    Ok(())
+}
+
+#[test]
+fn indent_config_new_collections_only() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        spec:
+          containers:
+            - name: web
+        ",
+    )?;
+
+    let step = NonZeroUsize::new(4).context("zero step")?;
+    doc.set_indent(yaml::IndentConfig::new().step(step));
+
+    let mut spec = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("spec"))
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing spec")?;
+
+    let mut volumes = spec
+        .insert("volumes", yaml::Separator::Auto)
+        .make_sequence();
+    let mut volume = volumes.push(yaml::Separator::Auto).make_mapping();
+    volume.insert_str("name", "data");
+    let mut config = volume
+        .insert("config", yaml::Separator::Auto)
+        .make_mapping();
+    config.insert_str("path", "/data");
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        spec:
+          containers:
+            - name: web
+          volumes:
+              - name: data
+                config:
+                    path: /data
+        "
+    );
+    Ok(())
+}
+
+#[test]
+fn normalize_document() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        # Services.
+        services:
+            web:   {image: 'nginx', ports: [80,   443]}
+            db:
+                image:  postgres
+                tagged:  !!str x
+                args:
+                    -    --verbose
+                    - {a: 1}
+                none:
+                script: |
+                    echo
+        ",
+    )?;
+
+    doc.normalize();
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        # Services.
+        services:
+          web:
+            image: 'nginx'
+            ports:
+              - 80
+              - 443
+          db:
+            image: postgres
+            tagged:  !!str x
+            args:
+              - --verbose
+              - a: 1
+            none:
+            script: |
+                echo
+        ",
+    );
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert("new", yaml::Separator::Auto)
+        .make_mapping()
+        .insert_u32("a", 1);
+
+    assert!(doc.to_string().ends_with("new:\n          a: 1\n        "));
+    Ok(())
+}
+
+#[test]
+fn sort_keys_trailing_comments() -> Result<()> {
+    let doc = yaml::from_slice("c: 3 # three\nb:\n  y: 2 # y\n  x: 1 # x\na: 1 # one\n")?;
+
+    let options = yaml::PrettyOptions::new().sort_keys(true);
+    let pretty = doc.to_string_pretty(&options);
+
+    assert_eq!(
+        pretty,
+        "a: 1 # one\nb:\n  x: 1 # x\n  y: 2 # y\nc: 3 # three\n"
+    );
+
+    assert!(yaml::from_slice(&pretty)?.semantic_eq(&doc));
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn set_raw_yaml_in_sequence() -> Result<()> {
+    let mut doc = yaml::from_slice("items:\n  - one\n  - two\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut items = root
+        .get_mut("items")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing items")?;
+
+    items
+        .get_mut(1)
+        .context("missing second")?
+        .set_raw_yaml("name: two\nvalues:\n- a\n- b\n")?;
+
+    assert_eq!(
+        doc.to_string(),
+        "items:\n  - one\n  - name: two\n    values:\n    - a\n    - b\n"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let items = root
+        .get("items")
+        .and_then(|v| v.as_sequence())
+        .context("missing items")?;
+    let second = items
+        .get(1)
+        .and_then(|v| v.as_mapping())
+        .context("missing second")?;
+    assert_eq!(second.get("name").and_then(|v| v.as_str()), Some("two"));
+    assert_eq!(
+        second
+            .get("values")
+            .and_then(|v| v.as_sequence())
+            .map(|s| s.len()),
+        Some(2)
+    );
+    Ok(())
+}
+
+#[test]
+fn set_raw_yaml_scalars() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\nb: 2\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("a")
+        .context("missing a")?
+        .set_raw_yaml("|\n  line one\n  line two\n")?;
+    root.get_mut("b")
+        .context("missing b")?
+        .set_raw_yaml("!!str 42")?;
+
+    assert_eq!(
+        doc.to_string(),
+        "a: |\n    line one\n    line two\nb: !!str 42\n"
+    );
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(
+        root.get("a").and_then(|v| v.as_str()),
+        Some("line one\nline two\n")
+    );
+    assert_eq!(root.get("b").and_then(|v| v.as_str()), Some("42"));
+    Ok(())
+}
+
+#[test]
+fn set_raw_yaml_error() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let error = root
+        .get_mut("a")
+        .context("missing a")?
+        .set_raw_yaml("[1, 2")
+        .unwrap_err();
+
+    assert_eq!(*error.kind(), yaml::ErrorKind::BadSequenceTerminator);
+    assert_eq!(doc.to_string(), "a: 1\n");
+
+    // Nothing which was parsed before the error is kept around, so the next
+    // value is allocated exactly as if the failed edit never happened.
+    let mut expected = yaml::from_slice("a: 1\n")?;
+    let mut root = expected
+        .as_mut()
+        .into_mapping_mut()
+        .context("missing root")?;
+    root.insert_u32("b", 2);
+    let expected = root.as_ref().get("b").context("missing b")?.id();
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("b", 2);
+    assert_eq!(root.as_ref().get("b").context("missing b")?.id(), expected);
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn aligned_separators() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        server:
+          name: app
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut server = root
+        .get_mut("server")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing server")?;
+    server
+        .insert("port", yaml::Separator::Align(8))
+        .set_u32(8080);
+    server
+        .insert("host", yaml::Separator::Align(8))
+        .set_string("localhost");
+    server
+        .insert("timeout", yaml::Separator::Align(8))
+        .set_u32(30);
+    server.insert("ä", yaml::Separator::Align(8)).set_u32(1);
+
+    let mut list = root.insert("list", yaml::Separator::Auto).make_sequence();
+    list.push(yaml::Separator::Align(4)).set_u32(1);
+    list.push(yaml::Separator::Custom("  ")).set_u32(2);
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        server:
+          name: app
+          port:   8080
+          host:   localhost
+          timeout: 30
+          ä:      1
+        list:
+          -   1
+          -  2
+        "
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "must only contain spaces"]
+fn custom_separator_non_space() {
+    let mut doc = yaml::from_slice("a: 1").unwrap();
+    let mut root = doc.as_mut().into_mapping_mut().unwrap();
+    root.insert("b", yaml::Separator::Custom("\t")).set_u32(2);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "must only contain spaces"]
+fn with_separator_non_space() {
+    let mut doc = yaml::from_slice("a: 1").unwrap();
+    let mut root = doc.as_mut().into_mapping_mut().unwrap();
+    root.insert("b", yaml::Separator::Auto)
+        .with_separator("x")
+        .set_u32(2);
+}
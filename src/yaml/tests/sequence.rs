@@ -75,3 +75,913 @@ fn mutable_nested_sequence() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn inline_sequence_empty_elements() -> Result<()> {
+    let mut doc = yaml::from_slice("[a, , c,  ,]")?;
+    assert_eq!(doc.to_string(), "[a, , c,  ,]");
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.len(), 4);
+    assert_eq!(root.get(0).and_then(|v| v.as_str()), Some("a"));
+    assert!(matches!(
+        root.get(1).map(|v| v.into_any()),
+        Some(yaml::Any::Null)
+    ));
+    assert_eq!(root.get(2).and_then(|v| v.as_str()), Some("c"));
+    assert!(matches!(
+        root.get(3).map(|v| v.into_any()),
+        Some(yaml::Any::Null)
+    ));
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(1).context("missing item")?.set_string("b");
+    assert_eq!(doc.to_string(), "[a, b, c,  ,]");
+    Ok(())
+}
+
+#[test]
+fn indentless_sequence() -> Result<()> {
+    const INPUT: &str = r"
+    indentless:
+    - a
+    -
+    - b
+    indented:
+      - c
+      - d
+    nested:
+      inner:
+      - e
+      other: f
+    ";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let indentless = root
+        .get("indentless")
+        .and_then(|v| v.as_sequence())
+        .context("missing indentless")?;
+    assert_eq!(indentless.len(), 3);
+    assert_eq!(indentless.get(0).and_then(|v| v.as_str()), Some("a"));
+    assert!(matches!(
+        indentless.get(1).map(|v| v.into_any()),
+        Some(yaml::Any::Null)
+    ));
+    assert_eq!(indentless.get(2).and_then(|v| v.as_str()), Some("b"));
+
+    let indented = root
+        .get("indented")
+        .and_then(|v| v.as_sequence())
+        .context("missing indented")?;
+    assert!(indented.iter().flat_map(|v| v.as_str()).eq(["c", "d"]));
+
+    let nested = root
+        .get("nested")
+        .and_then(|v| v.as_mapping())
+        .context("missing nested")?;
+    let inner = nested
+        .get("inner")
+        .and_then(|v| v.as_sequence())
+        .context("missing inner")?;
+    assert!(inner.iter().flat_map(|v| v.as_str()).eq(["e"]));
+    assert_eq!(nested.get("other").and_then(|v| v.as_str()), Some("f"));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    root.get_mut("indentless")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing indentless")?
+        .push_string("g");
+
+    root.get_mut("indented")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing indented")?
+        .push_string("h");
+
+    let mut nested = root
+        .get_mut("nested")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing nested")?;
+
+    let mut sequence = nested
+        .insert("created", yaml::Separator::Auto)
+        .make_indentless_sequence();
+    sequence.push_string("i");
+    sequence.push_string("j");
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+    indentless:
+    - a
+    -
+    - b
+    - g
+    indented:
+      - c
+      - d
+      - h
+    nested:
+      inner:
+      - e
+      other: f
+      created:
+      - i
+      - j
+    "
+    );
+    Ok(())
+}
+
+#[test]
+fn inline_sequence_padding() -> Result<()> {
+    let mut doc = yaml::from_slice("[ 1, 2, 3 ]")?;
+    assert_eq!(doc.to_string(), "[ 1, 2, 3 ]");
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.padding(), Some((" ".into(), " ".into())));
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(0).context("missing first")?.set_u32(4);
+    assert_eq!(doc.to_string(), "[ 4, 2, 3 ]");
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.set_padding("  ", "\t");
+    assert_eq!(doc.to_string(), "[  4, 2, 3\t]");
+
+    let doc = yaml::from_slice("- [ ]")?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let inner = root
+        .get(0)
+        .and_then(|v| v.as_sequence())
+        .context("missing inner")?;
+    assert_eq!(inner.padding(), Some(("".into(), " ".into())));
+    assert_eq!(root.padding(), None);
+    Ok(())
+}
+
+#[test]
+fn inline_sequence_tabs() -> Result<()> {
+    const INPUT: &str = "[a\tb, \"c\td\" ,\t'e\tf'\t, g\t]";
+
+    let doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.len(), 4);
+    assert_eq!(root.get(0).and_then(|v| v.as_str()), Some("a\tb"));
+    assert_eq!(root.get(1).and_then(|v| v.as_str()), Some("c\td"));
+    assert_eq!(root.get(2).and_then(|v| v.as_str()), Some("e\tf"));
+    assert_eq!(root.get(3).and_then(|v| v.as_str()), Some("g"));
+    Ok(())
+}
+
+#[test]
+fn inline_sequence_space_before_separator() -> Result<()> {
+    let mut doc = yaml::from_slice("[one , two  ,three ]")?;
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.get(0).and_then(|v| v.as_str()), Some("one"));
+    assert_eq!(root.get(1).and_then(|v| v.as_str()), Some("two"));
+    assert_eq!(root.get(2).and_then(|v| v.as_str()), Some("three"));
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(1).context("missing item")?.set_u32(2);
+    assert_eq!(doc.to_string(), "[one , 2  ,three ]");
+    Ok(())
+}
+
+#[test]
+fn make_sequence_defaults_to_block() -> Result<()> {
+    let mut doc = yaml::from_slice("key: value")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut list = root.insert("list", yaml::Separator::Auto).make_sequence();
+    list.push_string("a fairly long string");
+    list.push_string("another fairly long string");
+
+    assert_eq!(
+        doc.to_string(),
+        "key: value\nlist:\n  - a fairly long string\n  - another fairly long string"
+    );
+    Ok(())
+}
+
+#[test]
+fn force_block() -> Result<()> {
+    let mut doc = yaml::from_slice("[a, b]")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.force_block();
+    assert_eq!(doc.to_string(), "- a\n- b");
+
+    let mut doc = yaml::from_slice("- [a, b]\n- {c: [d]}")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(0)
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing first")?
+        .force_block();
+
+    // Nested inside of an inline collection, so can't be converted.
+    root.get_mut(1)
+        .and_then(|v| v.into_mapping_mut())
+        .and_then(|m| m.get_into_mut("c"))
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing c")?
+        .force_block();
+
+    assert_eq!(doc.to_string(), "- - a\n  - b\n- {c: [d]}");
+    Ok(())
+}
+
+#[test]
+fn force_block_comments() -> Result<()> {
+    let mut doc = yaml::from_slice("m: [ # head\n  1, # one\n  2 # two\n]\nn: 3\n")?;
+    doc.normalize();
+    assert_eq!(
+        doc.to_string(),
+        "m:\n  # head\n  - 1 # one\n  - 2 # two\nn: 3\n"
+    );
+
+    let mut doc = yaml::from_slice("m: {a: 1, # one\n  b: 2} # end\n")?;
+    doc.normalize();
+    assert_eq!(doc.to_string(), "m:\n  a: 1 # one\n  b: 2 # end\n");
+
+    let mut doc = yaml::from_slice("- [1, # one\n  2 # two\n  ]\n")?;
+    doc.normalize();
+    let output = doc.to_string();
+    assert_eq!(output, "- - 1 # one\n  - 2 # two\n");
+    assert!(yaml::from_slice(&output)?.semantic_eq(&doc));
+    Ok(())
+}
+
+#[test]
+fn max_inline_items() -> Result<()> {
+    let doc = yaml::from_slice("- [1, 2, 3]\n- {a: 1, b: 2, c:}\n- [x]\n")?;
+
+    let options = yaml::SerializeOptions::new().max_inline_items(Some(2));
+    assert_eq!(
+        doc.to_string_with(&options),
+        "- - 1\n  - 2\n  - 3\n- a: 1\n  b: 2\n  c:\n- [x]\n"
+    );
+
+    let options = yaml::SerializeOptions::new().max_inline_items(None);
+    assert_eq!(doc.to_string_with(&options), doc.to_string());
+    Ok(())
+}
+
+#[test]
+fn max_inline_items_properties() -> Result<()> {
+    let options = yaml::SerializeOptions::new().max_inline_items(Some(0));
+
+    // Properties of items are separated from the `-` indicator.
+    let doc = yaml::from_slice("m: [&a 1, !t 2, !u &b 3]")?;
+    let output = doc.to_string_with(&options);
+    assert_eq!(output, "m:\n  - &a 1\n  - !t 2\n  - !u &b 3");
+    assert!(yaml::from_slice(&output)?.semantic_eq(&doc));
+
+    // Collections with properties can't start on the line of their `-`.
+    let doc = yaml::from_slice("- &a [1, 2]\n- !t {a: 1}\n")?;
+    let output = doc.to_string_with(&options);
+    assert_eq!(output, "- &a\n  - 1\n  - 2\n- !t\n  a: 1\n");
+    assert!(yaml::from_slice(&output)?.semantic_eq(&doc));
+    Ok(())
+}
+
+#[test]
+fn map_numbers() -> Result<()> {
+    let mut doc = yaml::from_slice("- 4\n- 1.5\n- -3\n- !!int 7\n- text\n- 1e2\n")?;
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.map_numbers(|n| n / 2.0);
+    assert_eq!(
+        doc.to_string(),
+        "- 2\n- 0.75\n- -1.5\n- 3.5\n- text\n- 50.0\n"
+    );
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.map_numbers(|n| n / 0.0);
+    assert_eq!(
+        doc.to_string(),
+        "- 2\n- 0.75\n- -1.5\n- 3.5\n- text\n- 50.0\n"
+    );
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.get(0).and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(root.get(1).and_then(|v| v.as_f64()), Some(0.75));
+    Ok(())
+}
+
+#[test]
+fn map_numbers_integers() -> Result<()> {
+    let mut doc = yaml::from_slice("[0x10, 1_000, 3, 2.5, -0b11, 10000000000000000000]")?;
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.map_numbers(|n| n * 2.0);
+    assert_eq!(doc.to_string(), "[32, 2000, 6, 5.0, -6, 2e19]");
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.map_numbers(|n| n / 4.0);
+    assert_eq!(doc.to_string(), "[8, 500, 1.5, 1.25, -1.5, 5e18]");
+
+    let mut doc = yaml::from_slice("[10000000000000000000]")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.map_numbers(|n| n);
+    assert_eq!(doc.to_string(), "[10000000000000000000]");
+    Ok(())
+}
+
+#[test]
+fn normalize_item_separators() -> Result<()> {
+    let mut doc = yaml::from_slice("-  -   a\n   -  b\n-   [x,  y]\n-    !!str c\n")?;
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.normalize_item_separators("\t");
+    assert_eq!(
+        doc.to_string(),
+        "-  -   a\n   -  b\n-   [x,  y]\n-    !!str c\n"
+    );
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.normalize_item_separators(" ");
+    assert_eq!(doc.to_string(), "- -   a\n  -  b\n- [x,  y]\n- !!str c\n");
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.normalize_item_separators(" ");
+    assert_eq!(doc.to_string(), "- -   a\n  -  b\n- [x,  y]\n- !!str c\n");
+
+    let mut inner = doc
+        .as_mut()
+        .into_sequence_mut()
+        .and_then(|s| s.get_into_mut(0))
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing inner")?;
+    inner.normalize_item_separators("  ");
+    assert_eq!(doc.to_string(), "- -  a\n  -  b\n- [x,  y]\n- !!str c\n");
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let inner = root
+        .get(0)
+        .and_then(|v| v.as_sequence())
+        .context("missing inner")?;
+    assert_eq!(inner.get(1).and_then(|v| v.as_str()), Some("b"));
+
+    let mut doc = yaml::from_slice("-    &x   foo\n-  !t   &y  bar\n-  &z !u  baz\n- *x\n")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.normalize_item_separators(" ");
+    let output = doc.to_string();
+    assert_eq!(output, "- &x   foo\n- !t   &y  bar\n- &z !u  baz\n- *x\n");
+    assert!(yaml::from_slice(&output)?.semantic_eq(&doc));
+    Ok(())
+}
+
+#[test]
+fn to_document_string() -> Result<()> {
+    let doc =
+        yaml::from_slice("- a: 1\n  b: |\n    text\n\n    more\n  c:\n    - d\n- [e,\n  f]\n")?;
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let first = root.get(0).context("missing first")?;
+    let string = first.to_document_string();
+    assert_eq!(string, "a: 1\nb: |\n  text\n\n  more\nc:\n  - d");
+
+    let reparsed = yaml::from_slice(&string)?;
+    let mapping = reparsed.as_ref().as_mapping().context("missing mapping")?;
+    assert_eq!(mapping.get_str("b"), Some("text\n\nmore\n".into()));
+
+    let second = root.get(1).context("missing second")?;
+    assert_eq!(second.to_document_string(), second.to_string());
+    Ok(())
+}
+
+#[test]
+fn inline_multiline() -> Result<()> {
+    const INPUT: &str = "matrix: [\n  1, 2,\n  3, 4,\n]\nnested: [ # comment\n  [a,\n   b],\n  # other\n  {c: d}\n]\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let matrix = root
+        .get("matrix")
+        .and_then(|v| v.as_sequence())
+        .context("missing matrix")?;
+    assert_eq!(
+        matrix.iter().flat_map(|v| v.as_u32()).collect::<Vec<_>>(),
+        [1, 2, 3, 4]
+    );
+
+    let nested = root
+        .get("nested")
+        .and_then(|v| v.as_sequence())
+        .context("missing nested")?;
+    let inner = nested
+        .get(0)
+        .and_then(|v| v.as_sequence())
+        .context("missing inner")?;
+    assert_eq!(inner.get(1).and_then(|v| v.as_str()), Some("b"));
+    assert_eq!(
+        nested
+            .get(1)
+            .and_then(|v| v.as_mapping())
+            .and_then(|m| m.get_str("c")),
+        Some("d".into())
+    );
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut matrix = root
+        .get_mut("matrix")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing matrix")?;
+    matrix.get_mut(2).context("missing item")?.set_u32(30);
+    assert_eq!(doc.to_string(), INPUT.replace("3, 4", "30, 4"));
+    Ok(())
+}
+
+#[test]
+fn replace_empty_null() -> Result<()> {
+    let mut doc = yaml::from_slice("-\n- [,]\n-\n")?;
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(0).context("missing first")?.set_string("");
+    root.get_mut(2).context("missing third")?.set_u32(3);
+
+    root.get_mut(1)
+        .and_then(|v| v.into_sequence_mut())
+        .and_then(|s| s.get_into_mut(0))
+        .context("missing inner")?
+        .set_bool(true);
+
+    assert_eq!(doc.to_string(), "- ''\n- [true,]\n- 3\n");
+    Ok(())
+}
+
+#[test]
+fn iter_nth_back() -> Result<()> {
+    let doc = yaml::from_slice("[1, 2, 3]")?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+
+    assert_eq!(root.iter().rev().nth(1).and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(root.iter().nth_back(0).and_then(|v| v.as_u32()), Some(3));
+    assert!(root.iter().nth_back(3).is_none());
+    Ok(())
+}
+
+#[test]
+fn push_inline() -> Result<()> {
+    let mut doc = yaml::from_slice("- []\n- [a]\n- [ a,  b ]\n- [\n    a,\n    b,\n  ]\n")?;
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+
+    for index in 0..4 {
+        let mut sequence = root
+            .get_mut(index)
+            .and_then(|v| v.into_sequence_mut())
+            .context("missing sequence")?;
+
+        sequence.push_string("x");
+    }
+
+    let mut first = root
+        .get_mut(0)
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing sequence")?;
+
+    let mut nested = first
+        .push(yaml::Separator::Auto)
+        .make_mapping_with(yaml::CollectionStyle::Flow);
+
+    nested.insert_u32("y", 1);
+    nested
+        .insert("z", yaml::Separator::Auto)
+        .make_sequence_with(yaml::CollectionStyle::Flow)
+        .push_bool(true);
+
+    assert_eq!(
+        doc.to_string(),
+        "- [x, {y: 1, z: [true]}]\n- [a, x]\n- [ a,  b,  x ]\n- [\n    a,\n    b,\n    x,\n  ]\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn special_floats() -> Result<()> {
+    let mut doc = yaml::from_slice("[.inf, -.Inf, +.INF, .NaN, .infinite, .]")?;
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+
+    assert_eq!(root.get(0).and_then(|v| v.as_f64()), Some(f64::INFINITY));
+    assert_eq!(
+        root.get(1).and_then(|v| v.as_f64()),
+        Some(f64::NEG_INFINITY)
+    );
+    assert_eq!(root.get(2).and_then(|v| v.as_f32()), Some(f32::INFINITY));
+    assert!(root
+        .get(3)
+        .and_then(|v| v.as_f64())
+        .is_some_and(f64::is_nan));
+    assert_eq!(root.get(4).and_then(|v| v.as_str()), Some(".infinite"));
+    assert_eq!(root.get(5).and_then(|v| v.as_str()), Some("."));
+    assert_eq!(doc.to_string(), "[.inf, -.Inf, +.INF, .NaN, .infinite, .]");
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(0)
+        .context("missing first")?
+        .set_f64(f64::NEG_INFINITY);
+    root.get_mut(1).context("missing second")?.set_f32(f32::NAN);
+    root.push_f64(f64::INFINITY);
+
+    assert_eq!(
+        doc.to_string(),
+        "[-.inf, .nan, +.INF, .NaN, .infinite, ., .inf]"
+    );
+    Ok(())
+}
+
+#[test]
+fn insert_and_remove() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        outer:
+          - b # about b
+          - d
+        inline: [ b, d ]
+        empty: []
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    for key in ["outer", "inline"] {
+        let mut seq = root
+            .get_mut(key)
+            .and_then(|v| v.into_sequence_mut())
+            .context("missing sequence")?;
+        seq.insert(1, yaml::Separator::Auto).set_string("c");
+        seq.insert(0, yaml::Separator::Auto).set_string("a");
+        seq.insert(4, yaml::Separator::Auto).set_string("e");
+    }
+
+    let mut empty = root
+        .get_mut("empty")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing empty")?;
+    empty.insert(0, yaml::Separator::Auto).set_u32(1);
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        outer:
+          - a
+          - b # about b
+          - c
+          - d
+          - e
+        inline: [ a, b, c, d, e ]
+        empty: [1]
+        "
+    );
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    for key in ["outer", "inline"] {
+        let mut seq = root
+            .get_mut(key)
+            .and_then(|v| v.into_sequence_mut())
+            .context("missing sequence")?;
+        assert!(seq.remove(0));
+        assert!(seq.remove(0));
+        assert!(!seq.remove(3));
+    }
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        outer:
+          - c
+          - d
+          - e
+        inline: [ c, d, e ]
+        empty: [1]
+        "
+    );
+    Ok(())
+}
+
+#[test]
+fn remove_first_keeps_comments() -> Result<()> {
+    let mut doc = yaml::from_slice("- a\n# about b\n- b\n")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert!(root.remove(0));
+    assert_eq!(doc.to_string(), "# about b\n- b\n");
+
+    let mut doc = yaml::from_slice(
+        r"
+        outer:
+          - a # about a
+
+          # about b
+          - b
+        ",
+    )?;
+
+    let mut outer = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("outer"))
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing outer")?;
+    assert!(outer.remove(0));
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        outer:
+          # about b
+          - b
+        "
+    );
+    Ok(())
+}
+
+#[test]
+#[should_panic = "insertion index (is 2) should be <= len (is 1)"]
+fn insert_out_of_bounds() {
+    let mut doc = yaml::from_slice("[1]").unwrap();
+    let mut root = doc.as_mut().into_sequence_mut().unwrap();
+    root.insert(2, yaml::Separator::Auto);
+}
+
+#[test]
+fn dedup_numbers() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        "[1, 1.0, 0x10, 16, 1_000, 1e3, .nan, .NaN, -.inf, -.Inf, '1', \"1\", 1, 9007199254740993, 9007199254740992.0]",
+    )?;
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert_eq!(root.dedup(), 6);
+    assert_eq!(
+        doc.to_string(),
+        "[1, 0x10, 1_000, .nan, -.inf, '1', 1, 9007199254740993, 9007199254740992.0]"
+    );
+    Ok(())
+}
+
+#[test]
+fn eq_logical_numbers() -> Result<()> {
+    let a = yaml::from_slice("[1, 0x10, 1_000, .nan, -.inf, 1e3, '1']")?;
+    let b = yaml::from_slice("[1.0, 16, 1000, .NaN, -.Inf, 1000, \"1\"]")?;
+    let c = yaml::from_slice("[1, 0x10, 1_000, .nan, -.inf, 1e3, 1]")?;
+
+    assert!(a.as_ref().eq_logical(&b.as_ref()));
+    assert!(!a.as_ref().eq_logical(&c.as_ref()));
+    assert!(!a.semantic_eq(&b));
+    Ok(())
+}
+
+#[test]
+fn eq_logical_large_integers() -> Result<()> {
+    // 2^53 + 1 can't be represented as a float, so it must not be rounded to
+    // the float next to it.
+    let a = yaml::from_slice("9007199254740993")?;
+    let b = yaml::from_slice("9007199254740992.0")?;
+    let c = yaml::from_slice("9007199254740992")?;
+
+    assert!(!a.as_ref().eq_logical(&b.as_ref()));
+    assert!(!b.as_ref().eq_logical(&a.as_ref()));
+    assert!(c.as_ref().eq_logical(&b.as_ref()));
+    Ok(())
+}
+
+#[test]
+fn copy_from_independent() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        - name: web
+          tags: [a, b]
+          env:
+            - X=1
+        ",
+    )?;
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let web = root.get(0).context("missing web")?.id();
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.push(yaml::Separator::Auto).copy_from(web);
+
+    let mut copy = root
+        .get_mut(1)
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing copy")?;
+    copy.get_mut("name")
+        .context("missing name")?
+        .set_string("db");
+    copy.get_mut("env")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing env")?
+        .push_string("Y=2");
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        - name: web
+          tags: [a, b]
+          env:
+            - X=1
+        - name: db
+          tags: [a, b]
+          env:
+            - X=1
+            - Y=2
+        "
+    );
+
+    let mut doc = yaml::from_slice("[1, {a: [2, 3]}]")?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let inner = root.get(1).context("missing inner")?.id();
+    let copy = doc.clone_value(inner);
+    assert!(!doc.value(copy).is_root());
+    doc.value_mut(copy)
+        .into_mapping_mut()
+        .context("missing mapping")?
+        .insert_u32("b", 4);
+    doc.as_mut()
+        .into_sequence_mut()
+        .context("missing root")?
+        .push(yaml::Separator::Auto)
+        .copy_from(copy);
+
+    assert_eq!(doc.to_string(), "[1, {a: [2, 3]}, {a: [2, 3], b: 4}]");
+
+    // The detached copy was moved into place, so its slot is free again.
+    assert_eq!(doc.clone_value(inner), copy);
+    Ok(())
+}
+
+#[test]
+fn clone_value_block_scalar() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        "items:
+  - |
+    text
+",
+    )?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let items = root
+        .get("items")
+        .and_then(|v| v.as_sequence())
+        .context("missing items")?;
+    let text = items.get(0).context("missing text")?.id();
+
+    let copy = doc.clone_value(text);
+    doc.as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("items")?.into_sequence_mut())
+        .context("missing items")?
+        .push(yaml::Separator::Auto)
+        .copy_from(copy);
+
+    assert_eq!(
+        doc.to_string(),
+        "items:
+  - |
+    text
+  - |
+    text
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn sort_by_and_dedup_block() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        items:
+          - name: b
+            port: 2 # second
+          # comment
+          - name: a
+            port: 1
+          - name: b
+            port: 2.0
+        end: true
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut items = root
+        .get_mut("items")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing items")?;
+
+    items.sort_by(|a, b| {
+        let a = a.as_mapping().and_then(|m| m.get("name")?.as_str());
+        let b = b.as_mapping().and_then(|m| m.get("name")?.as_str());
+        a.cmp(&b)
+    });
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        items:
+          - name: a
+            port: 1
+          # comment
+          - name: b
+            port: 2 # second
+          - name: b
+            port: 2.0
+        end: true
+        ",
+    );
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut items = root
+        .get_mut("items")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing items")?;
+
+    assert_eq!(items.dedup(), 1);
+    assert_eq!(items.dedup(), 0);
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        items:
+          - name: a
+            port: 1
+          # comment
+          - name: b
+            port: 2 # second
+        end: true
+        ",
+    );
+    Ok(())
+}
+
+#[test]
+fn swap_and_swap_remove() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        list:
+          - a # A
+          - b # B
+          - c # C
+        other: 1
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut list = root
+        .get_mut("list")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing list")?;
+
+    list.swap(1, 1);
+    list.swap(2, 1);
+    assert!(list.swap_remove(2));
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        list:
+          - a # A
+          - c # C
+        other: 1
+        "
+    );
+
+    let mut doc = yaml::from_slice("[a, b, c, d]")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert!(root.swap_remove(1));
+    assert_eq!(root.as_ref().to_string(), "[a, d, c]");
+    assert!(root.swap_remove(2));
+    assert_eq!(doc.to_string(), "[a, d]");
+    Ok(())
+}
+
+#[test]
+fn inline_sequence_unset_items() -> Result<()> {
+    let mut doc = yaml::from_slice("[1, 2]")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(1)
+        .context("missing item")?
+        .set_null(yaml::Null::Empty);
+    root.push(yaml::Separator::Auto);
+    root.push(yaml::Separator::Auto).set_u32(4);
+    root.push(yaml::Separator::Auto);
+
+    assert_eq!(doc.to_string(), "[1, null, null, 4, null]");
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.len(), 5);
+    assert_eq!(root.get(3).and_then(|v| v.as_u32()), Some(4));
+    Ok(())
+}
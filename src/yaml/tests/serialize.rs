@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Serialize};
+
+use crate::yaml;
+
+#[derive(Serialize)]
+enum Restart {
+    Always,
+    OnFailure { retries: u32 },
+    Delay(u32),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Service {
+    image: String,
+    replicas: u32,
+    ratio: f64,
+    command: Option<String>,
+    ports: Vec<u16>,
+    volumes: Vec<String>,
+    labels: BTreeMap<String, String>,
+    matrix: Vec<Vec<u32>>,
+}
+
+#[test]
+fn serialize_nested() -> Result<()> {
+    let service = Service {
+        image: String::from("nginx:1.25"),
+        replicas: 3,
+        ratio: 0.5,
+        command: None,
+        ports: vec![80, 443],
+        volumes: Vec::new(),
+        labels: BTreeMap::from([(String::from("app"), String::from("web"))]),
+        matrix: vec![vec![1, 2], vec![3]],
+    };
+
+    let doc = yaml::serde::to_document(&service)?;
+
+    assert_eq!(
+        doc.to_string(),
+        r"image: nginx:1.25
+replicas: 3
+ratio: 0.5
+command: null
+ports:
+  - 80
+  - 443
+volumes: []
+labels:
+  app: web
+matrix:
+  - - 1
+    - 2
+  - - 3
+"
+    );
+
+    let parsed = yaml::from_slice(doc.to_string())?;
+    assert_eq!(Service::deserialize(parsed.into_deserializer())?, service);
+    Ok(())
+}
+
+#[test]
+fn serialize_enums() -> Result<()> {
+    let restart = [
+        Restart::Always,
+        Restart::OnFailure { retries: 5 },
+        Restart::Delay(10),
+    ];
+
+    let doc = yaml::serde::to_document(&restart)?;
+
+    assert_eq!(
+        doc.to_string(),
+        r"- Always
+- OnFailure:
+    retries: 5
+- Delay: 10
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn serialize_and_edit() -> Result<()> {
+    let mut doc = yaml::serde::to_document(&BTreeMap::from([("name", "web")]))?;
+
+    let mut root = doc
+        .as_mut()
+        .into_mapping_mut()
+        .context("missing root mapping")?;
+    root.insert_u32("port", 8080);
+
+    assert_eq!(doc.to_string(), "name: web\nport: 8080\n");
+    Ok(())
+}
+
+#[test]
+fn serialize_invalid_key() {
+    let map = BTreeMap::from([(vec![1u32], 1u32)]);
+    assert!(yaml::serde::to_document(&map).is_err());
+}
+
+#[test]
+fn serialize_value_aliases() -> Result<()> {
+    let doc = yaml::from_slice("a: &x [1]\nb: *x\n")?;
+    assert_eq!(serde_yaml::to_string(&doc.as_ref())?, "a:\n- 1\nb:\n- 1\n");
+
+    let doc = yaml::from_slice("a: &x [*x]\n")?;
+    let error = serde_yaml::to_string(&doc.as_ref()).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("alias refers to a value which contains it"),
+        "{error}"
+    );
+    Ok(())
+}
+
+#[test]
+fn serialize_keys_requiring_quotes() -> Result<()> {
+    let map = BTreeMap::from([
+        ("plain", 0u32),
+        ("a: b", 1),
+        ("#c", 2),
+        ("", 3),
+        ("x\ny", 4),
+        ("1", 5),
+        ("true", 6),
+    ]);
+
+    let doc = yaml::serde::to_document(&map)?;
+
+    assert_eq!(
+        doc.to_string(),
+        "'': 3\n'#c': 2\n'1': 5\n'a: b': 1\nplain: 0\n'true': 6\n\"x\\ny\": 4\n"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let read = BTreeMap::<String, u32>::deserialize(doc.as_ref().into_deserializer())?;
+    let expected = map.iter().map(|(k, v)| ((*k).to_owned(), *v)).collect();
+    assert_eq!(read, expected);
+
+    let doc = yaml::serde::to_document(&BTreeMap::from([(1u32, true)]))?;
+    assert_eq!(doc.to_string(), "1: true\n");
+    Ok(())
+}
+
+#[test]
+fn serialize_values_requiring_quotes() -> Result<()> {
+    let values = vec!["~", "# c", "- x", "[1]", "*a", ".inf", "a #b", "plain"];
+    let doc = yaml::serde::to_document(&values)?;
+
+    assert_eq!(
+        doc.to_string(),
+        "- '~'\n- '# c'\n- '- x'\n- '[1]'\n- '*a'\n- '.inf'\n- 'a #b'\n- plain\n"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+
+    for (value, expected) in root.iter().zip(&values) {
+        assert_eq!(value.as_str(), Some(*expected));
+    }
+
+    assert_eq!(root.len(), values.len());
+    Ok(())
+}
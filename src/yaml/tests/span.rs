@@ -0,0 +1,88 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn spans() -> Result<()> {
+    let input = "\
+# Comment.
+server: &server
+  name: 'app' # trailing
+  ports:
+    - 80
+    - !!str 443
+  script: |
+    echo hello
+  nested: {a: [1, \"two\"]}
+empty:
+";
+
+    let doc = yaml::from_slice(input)?;
+    let text = |value: &yaml::Value<'_>| value.span().map(|span| &input[span]);
+
+    let root = doc.as_ref();
+    assert_eq!(root.location(), Some((2, 1)));
+
+    let root = root.as_mapping().context("missing root")?;
+    let server = root.get("server").context("missing server")?;
+    assert_eq!(server.location(), Some((3, 3)));
+    assert!(text(&server).is_some_and(|t| t.starts_with("name: 'app'")));
+    assert!(text(&server).is_some_and(|t| t.ends_with("[1, \"two\"]}")));
+
+    let server = server.as_mapping().context("missing server")?;
+    let name = server.get("name").context("missing name")?;
+    assert_eq!(text(&name), Some("'app'"));
+    assert_eq!(name.location(), Some((3, 9)));
+
+    let ports = server.get("ports").context("missing ports")?;
+    assert_eq!(text(&ports), Some("- 80\n    - !!str 443"));
+
+    let ports = ports.as_sequence().context("missing ports")?;
+    assert_eq!(ports.get(0).as_ref().and_then(text), Some("80"));
+    assert_eq!(ports.get(1).as_ref().and_then(text), Some("443"));
+    assert_eq!(ports.get(1).and_then(|v| v.location()), Some((6, 13)));
+
+    let script = server.get("script").context("missing script")?;
+    assert_eq!(text(&script), Some("|\n    echo hello"));
+
+    let nested = server.get("nested").context("missing nested")?;
+    assert_eq!(text(&nested), Some("{a: [1, \"two\"]}"));
+
+    let a = nested
+        .as_mapping()
+        .and_then(|m| m.get("a")?.as_sequence())
+        .context("missing a")?;
+    let two = a.get(1).context("missing two")?;
+    assert_eq!(text(&two), Some("\"two\""));
+    assert_eq!(two.location(), Some((9, 19)));
+    Ok(())
+}
+
+#[test]
+fn spans_after_edits() -> Result<()> {
+    let input = "a: 1\nb: [2, 3]\n";
+    let mut doc = yaml::from_slice(input)?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("c", 4);
+    root.get_mut("a")
+        .context("missing a")?
+        .set_raw_yaml("{x: 1}")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("a").and_then(|v| v.span()), None);
+    assert_eq!(root.get("b").and_then(|v| v.span()), Some(8..14));
+    assert_eq!(root.get("b").and_then(|v| v.location()), Some((2, 4)));
+    assert_eq!(root.get("c").and_then(|v| v.span()), None);
+
+    let stream = yaml::from_slice_stream("a: 1\n---\nb: 2\n")?;
+    let second = stream.get(1).context("missing second document")?;
+    let b = second
+        .as_ref()
+        .as_mapping()
+        .and_then(|m| m.get("b"))
+        .context("missing b")?;
+    assert_eq!(b.span(), Some(12..13));
+    assert_eq!(b.location(), Some((3, 4)));
+    Ok(())
+}
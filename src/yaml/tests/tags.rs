@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn tags_roundtrip() -> Result<()> {
+    const INPUT: &str = r"
+    string: !!str 123
+    float: !!float 1
+    bool: !custom true
+    nothing: !!null
+    verbatim: !<tag:yaml.org,2002:str> 42
+    sequence: !!seq
+    - !!str 1
+    - ! 2
+    inline: !!map {a: !!str 1, b: [!!int 2]}
+    ";
+
+    let doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn tags_override_kind() -> Result<()> {
+    let doc = yaml::from_slice(
+        r#"
+        string: !!str 123
+        null_string: !!str null
+        float: !!float 1
+        quoted: !!int "2"
+        bool: !!bool TRUE
+        custom: !custom 3
+        verbatim: !<tag:yaml.org,2002:str> 4
+        "#,
+    )?;
+
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+
+    let string = root.get("string").context("missing string")?;
+    assert_eq!(string.as_str(), Some("123"));
+    assert_eq!(string.as_u32(), None);
+    assert!(matches!(string.into_any(), yaml::Any::String(..)));
+
+    let null_string = root.get("null_string").context("missing null_string")?;
+    assert_eq!(null_string.as_str(), Some("null"));
+
+    let float = root.get("float").context("missing float")?;
+    assert_eq!(float.as_f64(), Some(1.0));
+
+    let quoted = root.get("quoted").context("missing quoted")?;
+    assert_eq!(quoted.as_str(), Some("2"));
+
+    let bool = root.get("bool").context("missing bool")?;
+    assert_eq!(bool.as_bool(), Some(true));
+
+    let custom = root.get("custom").context("missing custom")?;
+    assert_eq!(custom.as_u32(), Some(3));
+
+    let verbatim = root.get("verbatim").context("missing verbatim")?;
+    assert_eq!(verbatim.as_str(), Some("4"));
+    Ok(())
+}
+
+#[test]
+fn tags_property_of_key() -> Result<()> {
+    const INPUT: &str = r"
+    !!merge <<: base
+    other: 1
+    ";
+
+    let doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    assert_eq!(root.get("other").and_then(|v| v.as_u32()), Some(1));
+    Ok(())
+}
+
+#[test]
+fn tags_cleared_on_replace() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        first: !!str 123
+        second: !!str 456
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("first").context("missing first")?.set_u32(10);
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        first: 10
+        second: !!str 456
+        "
+    );
+    Ok(())
+}
+
+#[test]
+fn binary() -> Result<()> {
+    const INPUT: &str = r"
+    a: !<tag:yaml.org,2002:binary> aGk=
+    b: !!binary >
+      aGVs
+      bG8=
+    c: !!binary 'aA=='
+    d: !!binary aGVsbG8
+    e: !!binary not-base64
+    f: !!binary aGk==
+    ";
+
+    let doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    let bytes = |key| root.get(key).and_then(|v| v.as_bytes());
+
+    assert_eq!(bytes("a").as_deref(), Some(&b"hi"[..]));
+    assert_eq!(bytes("b").as_deref(), Some(&b"hello"[..]));
+    assert_eq!(bytes("c").as_deref(), Some(&b"h"[..]));
+    assert_eq!(bytes("d").as_deref(), Some(&b"hello"[..]));
+    assert_eq!(bytes("e"), None);
+    assert_eq!(bytes("f"), None);
+    Ok(())
+}
+
+#[test]
+fn tag_accessor() -> Result<()> {
+    const INPUT: &str = r"
+    - !custom
+      # A comment before the value.
+      a: 1
+    - [!!int 1, !set {a: b}]
+    - key: # A comment before the tag.
+        !!str 2
+    - !!str &anchor value
+    ";
+
+    let doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let tags = root.iter().map(|v| v.tag()).collect::<Vec<_>>();
+    assert_eq!(
+        tags,
+        [Some("!custom".into()), None, None, Some("!!str".into())]
+    );
+
+    let flow = root
+        .get(1)
+        .and_then(|v| v.as_sequence())
+        .context("missing flow")?;
+    assert_eq!(flow.get(0).and_then(|v| v.tag()), Some("!!int".into()));
+    assert_eq!(flow.get(1).and_then(|v| v.tag()), Some("!set".into()));
+
+    let key = root
+        .get(2)
+        .and_then(|v| v.as_mapping())
+        .and_then(|m| m.get("key"))
+        .context("missing key")?;
+    assert_eq!(key.tag(), Some("!!str".into()));
+    Ok(())
+}
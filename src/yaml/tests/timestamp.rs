@@ -0,0 +1,59 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn timestamps() -> Result<()> {
+    let doc = yaml::from_slice(
+        r"
+        canonical: 2001-12-15T02:59:43.1Z
+        iso8601: 2001-12-14t21:59:43.10-05:00
+        spaced: 2001-12-14 21:59:43.10 -5
+        no_zone: 2001-12-15 2:59:43.10
+        date: 2002-12-14
+        leap: 2000-02-29
+        precise: 2001-12-15T02:59:43.1234567891Z
+        short_date: 2002-1-4
+        bad_day: 2001-02-29
+        bad_hour: 2001-12-15T24:00:00Z
+        trailing: 2001-12-15T02:59:43Zx
+        tagged: !!timestamp '2002-12-14'
+        quoted: '2002-12-14'
+        ",
+    )?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let get = |key: &str| root.get(key).and_then(|v| v.as_timestamp());
+
+    let canonical = get("canonical").context("missing canonical")?;
+    assert_eq!(canonical.nanosecond(), 100_000_000);
+    assert_eq!(canonical.offset(), Some(0));
+
+    let iso8601 = get("iso8601").context("missing iso8601")?;
+    assert_eq!(iso8601.offset(), Some(-5 * 3600));
+    assert_eq!(iso8601.to_string(), "2001-12-14T21:59:43.1-05:00");
+    assert_eq!(get("spaced"), Some(iso8601));
+
+    let no_zone = get("no_zone").context("missing no_zone")?;
+    assert_eq!(no_zone.hour(), 2);
+    assert_eq!(no_zone.offset(), None);
+
+    let date = get("date").context("missing date")?;
+    assert!(!date.has_time());
+    assert_eq!(date.to_string(), "2002-12-14");
+
+    assert!(get("leap").is_some());
+    assert_eq!(get("precise").map(|t| t.nanosecond()), Some(123_456_789));
+    assert_eq!(get("short_date"), None);
+    assert_eq!(get("bad_day"), None);
+    assert_eq!(get("bad_hour"), None);
+    assert_eq!(get("trailing"), None);
+    assert_eq!(get("tagged"), Some(date));
+    assert_eq!(get("quoted"), None);
+
+    assert_eq!(
+        root.get("no_zone").and_then(|v| v.as_str()),
+        Some("2001-12-15 2:59:43.10")
+    );
+    Ok(())
+}
@@ -0,0 +1,351 @@
+use core::fmt;
+
+/// A date and time parsed from a YAML timestamp, as returned by
+/// [`Value::as_timestamp`].
+///
+/// Timestamps follow the [YAML timestamp type], which is a subset of ISO-8601
+/// in one of two forms:
+/// * A date like `2001-12-14`, where the time is midnight and there is no
+///   offset.
+/// * A date and time like `2001-12-14t21:59:43.10-05:00` or
+///   `2001-12-14 21:59:43.10 -5`, where the fraction of a second and the
+///   offset from UTC are optional. A `Z` suffix is an offset of zero.
+///
+/// This only holds the components of the timestamp, which are validated to be
+/// in range, and doesn't perform any calendar arithmetic.
+///
+/// [`Value::as_timestamp`]: crate::yaml::Value::as_timestamp
+/// [YAML timestamp type]: https://yaml.org/type/timestamp.html
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice("2001-12-15T02:59:43.1Z")?;
+/// let timestamp = doc.as_ref().as_timestamp().context("expected a timestamp")?;
+///
+/// assert_eq!(timestamp.year(), 2001);
+/// assert_eq!(timestamp.month(), 12);
+/// assert_eq!(timestamp.day(), 15);
+/// assert_eq!(timestamp.hour(), 2);
+/// assert_eq!(timestamp.minute(), 59);
+/// assert_eq!(timestamp.second(), 43);
+/// assert_eq!(timestamp.nanosecond(), 100_000_000);
+/// assert_eq!(timestamp.offset(), Some(0));
+/// assert_eq!(timestamp.to_string(), "2001-12-15T02:59:43.1Z");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timestamp {
+    year: u16,
+    month: u8,
+    day: u8,
+    has_time: bool,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanosecond: u32,
+    offset: Option<i32>,
+}
+
+impl Timestamp {
+    /// Parse a timestamp.
+    pub(crate) fn parse(string: &[u8]) -> Option<Self> {
+        let mut p = Cursor { string, n: 0 };
+
+        let year = p.digits(4, 4)?;
+        p.expect(b'-')?;
+        let month = p.digits(1, 2)?;
+        p.expect(b'-')?;
+        let day = p.digits(1, 2)?;
+
+        let mut timestamp = Self {
+            year: u16::try_from(year).ok()?,
+            month: u8::try_from(month).ok()?,
+            day: u8::try_from(day).ok()?,
+            has_time: false,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            nanosecond: 0,
+            offset: None,
+        };
+
+        if !(1..=12).contains(&timestamp.month)
+            || !(1..=days_in_month(timestamp.year, timestamp.month)).contains(&timestamp.day)
+        {
+            return None;
+        }
+
+        if p.is_eof() {
+            // The date-only form requires two-digit months and days.
+            return (string.len() == 10).then_some(timestamp);
+        }
+
+        if !matches!(p.peek(), Some(b'T' | b't')) && p.ws() == 0 {
+            return None;
+        }
+
+        if matches!(p.peek(), Some(b'T' | b't')) {
+            p.n = p.n.wrapping_add(1);
+        }
+
+        timestamp.has_time = true;
+        timestamp.hour = u8::try_from(p.digits(1, 2)?).ok()?;
+        p.expect(b':')?;
+        timestamp.minute = u8::try_from(p.digits(2, 2)?).ok()?;
+        p.expect(b':')?;
+        timestamp.second = u8::try_from(p.digits(2, 2)?).ok()?;
+
+        if timestamp.hour > 23 || timestamp.minute > 59 || timestamp.second > 59 {
+            return None;
+        }
+
+        if p.peek() == Some(b'.') {
+            p.n = p.n.wrapping_add(1);
+            let mut scale = 100_000_000u32;
+
+            while let Some(b @ b'0'..=b'9') = p.peek() {
+                timestamp.nanosecond = timestamp
+                    .nanosecond
+                    .wrapping_add(u32::from(b.wrapping_sub(b'0')).wrapping_mul(scale));
+                scale /= 10;
+                p.n = p.n.wrapping_add(1);
+            }
+        }
+
+        p.ws();
+
+        match p.peek() {
+            None => {}
+            Some(b'Z') => {
+                p.n = p.n.wrapping_add(1);
+                timestamp.offset = Some(0);
+            }
+            Some(sign @ (b'+' | b'-')) => {
+                p.n = p.n.wrapping_add(1);
+                let hours = p.digits(1, 2)?;
+
+                let minutes = if p.peek() == Some(b':') {
+                    p.n = p.n.wrapping_add(1);
+                    p.digits(2, 2)?
+                } else {
+                    0
+                };
+
+                if hours > 23 || minutes > 59 {
+                    return None;
+                }
+
+                let offset = i32::try_from(
+                    hours
+                        .wrapping_mul(3600)
+                        .wrapping_add(minutes.wrapping_mul(60)),
+                )
+                .ok()?;
+                timestamp.offset = Some(if sign == b'-' {
+                    offset.wrapping_neg()
+                } else {
+                    offset
+                });
+            }
+            Some(..) => return None,
+        }
+
+        p.is_eof().then_some(timestamp)
+    }
+
+    /// Get the year.
+    #[must_use]
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Get the month, from 1 to 12.
+    #[must_use]
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Get the day of the month, starting at 1.
+    #[must_use]
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Test if the timestamp has a time, as opposed to only being a date.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     - 2002-12-14
+    ///     - 2002-12-14 00:00:00
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().expect("expected a sequence");
+    ///
+    /// assert!(root.get(0).and_then(|v| v.as_timestamp()).is_some_and(|t| !t.has_time()));
+    /// assert!(root.get(1).and_then(|v| v.as_timestamp()).is_some_and(|t| t.has_time()));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn has_time(&self) -> bool {
+        self.has_time
+    }
+
+    /// Get the hour, from 0 to 23.
+    #[must_use]
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Get the minute, from 0 to 59.
+    #[must_use]
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Get the second, from 0 to 59.
+    #[must_use]
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Get the fraction of the second in nanoseconds. Digits beyond
+    /// nanosecond precision are ignored.
+    #[must_use]
+    pub fn nanosecond(&self) -> u32 {
+        self.nanosecond
+    }
+
+    /// Get the offset from UTC in seconds, or `None` if the timestamp doesn't
+    /// specify one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     - 2001-12-14t21:59:43.10-05:00
+    ///     - 2001-12-14 21:59:43.10
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().expect("expected a sequence");
+    ///
+    /// assert_eq!(root.get(0).and_then(|v| v.as_timestamp()).and_then(|t| t.offset()), Some(-5 * 3600));
+    /// assert_eq!(root.get(1).and_then(|v| v.as_timestamp()).and_then(|t| t.offset()), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn offset(&self) -> Option<i32> {
+        self.offset
+    }
+}
+
+/// Formats the timestamp in its canonical form, like `2001-12-15T02:59:43.1Z`
+/// or `2001-12-15` for dates.
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)?;
+
+        if !self.has_time {
+            return Ok(());
+        }
+
+        write!(f, "T{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+
+        if self.nanosecond != 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+
+        match self.offset {
+            Some(0) => write!(f, "Z")?,
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", offset / 3600, offset % 3600 / 60)?;
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the number of days in the given month.
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// A simple cursor over the bytes of a timestamp.
+struct Cursor<'a> {
+    string: &'a [u8],
+    n: usize,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.string.get(self.n).copied()
+    }
+
+    fn is_eof(&self) -> bool {
+        self.n >= self.string.len()
+    }
+
+    /// Consume the given byte.
+    fn expect(&mut self, b: u8) -> Option<()> {
+        if self.peek()? != b {
+            return None;
+        }
+
+        self.n = self.n.wrapping_add(1);
+        Some(())
+    }
+
+    /// Consume spaces and tabs, returning how many were consumed.
+    fn ws(&mut self) -> usize {
+        let start = self.n;
+
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.n = self.n.wrapping_add(1);
+        }
+
+        self.n.wrapping_sub(start)
+    }
+
+    /// Parse between `min` and `max` decimal digits.
+    fn digits(&mut self, min: usize, max: usize) -> Option<u32> {
+        let mut value = 0u32;
+        let mut count = 0;
+
+        while count < max {
+            let Some(b @ b'0'..=b'9') = self.peek() else {
+                break;
+            };
+
+            value = value
+                .wrapping_mul(10)
+                .wrapping_add(u32::from(b.wrapping_sub(b'0')));
+            count = count.wrapping_add(1);
+            self.n = self.n.wrapping_add(1);
+        }
+
+        (count >= min).then_some(value)
+    }
+}
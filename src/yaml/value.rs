@@ -1,15 +1,20 @@
+use std::borrow::Cow;
 use std::fmt;
+use std::ops::Range;
 
 use bstr::{BStr, ByteSlice};
 #[cfg(feature = "serde-edits")]
 use serde::{Deserialize, Serialize};
 
-use crate::yaml::data::{Data, Id};
-use crate::yaml::raw::Raw;
-use crate::yaml::{Any, Mapping, Number, Sequence, String};
+use crate::yaml::data::{Data, Id, StringId};
+use crate::yaml::eq;
+use crate::yaml::raw::{self, Raw, RawStringKind};
+use crate::yaml::{
+    Any, Error, ErrorKind, Integer, Mapping, Number, NumberStyle, Sequence, String, Timestamp,
+};
 
 /// The kind of a multiline string.
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Chomp {
     /// This is the `-` chomping indicator, which strips the final line break.
@@ -54,7 +59,7 @@ pub enum StringKind {
 }
 
 /// The kind of a block.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Block {
     /// The literal `|` block, with a chomping mode as indicated by [`Chomp`].
@@ -97,8 +102,57 @@ pub enum Separator<'a> {
     /// The only legal separator in YAML is spaces, but this can technically
     /// contain anything and will be literally embedded in the generated YAML.
     /// It is up to the caller to ensure nothing but spaces is used or suffer
-    /// the consequences.
+    /// the consequences. In debug builds, this is checked with an assertion.
     Custom(&'a str),
+    /// Pad with spaces so that the value starts at the given column, counted
+    /// from the start of the key or sequence marker. This can be used to align
+    /// values into columns.
+    ///
+    /// At least one space is always used, so keys which are too long to fit
+    /// are separated from their value with a single space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("name: app")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.insert("port", yaml::Separator::Align(10)).set_u32(8080);
+    /// root.insert("listen_address", yaml::Separator::Align(10)).set_string("localhost");
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "name: app\nport:     8080\nlisten_address: localhost"
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    Align(usize),
+}
+
+impl Separator<'_> {
+    /// Insert the separator to use for a value following a key and indicator
+    /// which is `width` characters wide, or return `None` if it should be
+    /// automatically determined.
+    pub(crate) fn insert(self, data: &mut Data, width: usize) -> Option<StringId> {
+        match self {
+            Separator::Auto => None,
+            Separator::Custom(separator) => {
+                debug_assert!(
+                    separator.bytes().all(|b| b == b' '),
+                    "separator {separator:?} must only contain spaces"
+                );
+
+                Some(data.insert_str(separator))
+            }
+            Separator::Align(column) => {
+                let padding = column.saturating_sub(width).max(1);
+                Some(data.insert_str(" ".repeat(padding)))
+            }
+        }
+    }
 }
 
 /// The kind of a null value.
@@ -114,6 +168,16 @@ pub enum Null {
     Empty,
 }
 
+/// The style of a collection, as returned by [`Value::collection_style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CollectionStyle {
+    /// A block collection, where each item is on its own line.
+    Block,
+    /// A flow collection, like `[1, 2]` or `{a: 1}`.
+    Flow,
+}
+
 /// A value inside of the document.
 ///
 /// # Examples
@@ -144,6 +208,70 @@ macro_rules! as_number {
     ($name:ident, $ty:ty, $doc:literal, $lit:literal) => {
         #[doc = concat!("Try and get the value as a ", $doc, ".")]
         ///
+        /// Hexadecimal, octal, and binary integers prefixed with `0x`, `0o`,
+        /// and `0b` are supported, as are `_` digit separators.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use anyhow::Context;
+        /// use nondestructive::yaml;
+        ///
+        #[doc = concat!("let doc = yaml::from_slice(\"", stringify!($lit), "\")?;")]
+        #[doc = concat!("let value = doc.as_ref().", stringify!($name), "();")]
+        #[doc = concat!("assert_eq!(value, Some(", stringify!($lit), "));")]
+        ///
+        /// let doc = yaml::from_slice("0x2A")?;
+        #[doc = concat!("assert_eq!(doc.as_ref().", stringify!($name), "(), Some(42));")]
+        /// # Ok::<_, anyhow::Error>(())
+        /// ```
+        #[must_use]
+        pub fn $name(&self) -> Option<$ty> {
+            match self.data.raw(self.id) {
+                Raw::Number(raw) => {
+                    let string = self.data.str(raw.string);
+                    raw::parse_integer(string)
+                }
+                _ => None,
+            }
+        }
+    };
+}
+
+macro_rules! as_nonzero {
+    ($name:ident, $ty:ident, $as:ident, $doc:literal) => {
+        #[doc = concat!("Try and get the value as a non-zero ", $doc, ".")]
+        ///
+        #[doc = concat!("This is the same as [`Value::", stringify!($as), "`], except that a zero is")]
+        /// treated as missing.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        #[doc = concat!("use std::num::", stringify!($ty), ";")]
+        ///
+        /// use nondestructive::yaml;
+        ///
+        /// let doc = yaml::from_slice("42")?;
+        #[doc = concat!("assert_eq!(doc.as_ref().", stringify!($name), "(), ", stringify!($ty), "::new(42));")]
+        ///
+        /// let doc = yaml::from_slice("0")?;
+        #[doc = concat!("assert_eq!(doc.as_ref().", stringify!($name), "(), None);")]
+        /// # Ok::<_, anyhow::Error>(())
+        /// ```
+        #[must_use]
+        pub fn $name(&self) -> Option<core::num::$ty> {
+            core::num::$ty::new(self.$as()?)
+        }
+    };
+}
+
+macro_rules! as_float {
+    ($name:ident, $ty:ty, $doc:literal, $lit:literal) => {
+        #[doc = concat!("Try and get the value as a ", $doc, ".")]
+        ///
+        /// The special values `.inf`, `-.inf`, and `.nan` are supported.
+        ///
         /// # Examples
         ///
         /// ```
@@ -153,6 +281,13 @@ macro_rules! as_number {
         #[doc = concat!("let doc = yaml::from_slice(\"", stringify!($lit), "\")?;")]
         #[doc = concat!("let value = doc.as_ref().", stringify!($name), "();")]
         #[doc = concat!("assert_eq!(value, Some(", stringify!($lit), "));")]
+        ///
+        /// let doc = yaml::from_slice("[.inf, -.Inf, .NaN]")?;
+        /// let values = doc.as_ref().as_sequence().context("missing sequence")?;
+        ///
+        #[doc = concat!("assert_eq!(values.get(0).and_then(|v| v.", stringify!($name), "()), Some(", stringify!($ty), "::INFINITY));")]
+        #[doc = concat!("assert_eq!(values.get(1).and_then(|v| v.", stringify!($name), "()), Some(", stringify!($ty), "::NEG_INFINITY));")]
+        #[doc = concat!("assert!(values.get(2).and_then(|v| v.", stringify!($name), "()).is_some_and(", stringify!($ty), "::is_nan));")]
         /// # Ok::<_, anyhow::Error>(())
         /// ```
         #[must_use]
@@ -160,7 +295,13 @@ macro_rules! as_number {
             match self.data.raw(self.id) {
                 Raw::Number(raw) => {
                     let string = self.data.str(raw.string);
-                    lexical_core::parse(string).ok()
+
+                    match raw::SpecialFloat::detect(string) {
+                        Some(raw::SpecialFloat::Infinity) => Some(<$ty>::INFINITY),
+                        Some(raw::SpecialFloat::NegInfinity) => Some(<$ty>::NEG_INFINITY),
+                        Some(raw::SpecialFloat::NaN) => Some(<$ty>::NAN),
+                        None => lexical_core::parse(string).ok(),
+                    }
                 }
                 _ => None,
             }
@@ -325,116 +466,232 @@ impl<'a> Value<'a> {
         self.id
     }
 
-    /// Get the value as a [`BStr`].
+    /// Get the byte span of the value in the input the document was parsed
+    /// from.
+    ///
+    /// The span covers the value itself, but not any tag or anchor preceding
+    /// it or any whitespace and comments surrounding it. Since it refers to the
+    /// original input, it isn't affected by edits to other parts of the
+    /// document.
+    ///
+    /// Returns `None` if the value wasn't parsed from the input, such as if it
+    /// has been created or replaced through an edit, or if it's an empty value
+    /// which is implied by the layout.
     ///
     /// # Examples
     ///
     /// ```
     /// use anyhow::Context;
     /// use nondestructive::yaml;
-    /// use bstr::BStr;
     ///
-    /// let doc = yaml::from_slice("string")?;
-    /// assert_eq!(doc.as_ref().as_str(), Some("string"));
+    /// let input = "name: app\nports: [80, 443] # public\n";
+    /// let mut doc = yaml::from_slice(input)?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let ports = root.get("ports").context("missing ports")?;
+    /// let span = ports.span().context("missing span")?;
+    /// assert_eq!(&input[span], "[80, 443]");
+    ///
+    /// let ports = ports.as_sequence().context("missing sequence")?;
+    /// let port = ports.get(1).context("missing port")?;
+    /// assert_eq!(port.span(), Some(22..25));
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// root.get_mut("name").context("missing name")?.set_string("other");
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// assert_eq!(root.get("name").and_then(|v| v.span()), None);
+    /// assert_eq!(root.get("ports").and_then(|v| v.span()), Some(17..26));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.data.span(self.id)
+    }
+
+    /// Get the 1-based line and column at which the value starts in the input
+    /// the document was parsed from. The column is counted in bytes.
+    ///
+    /// This is based on [`Value::span`], and returns `None` under the same
+    /// conditions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
     ///
     /// let doc = yaml::from_slice(
-    ///     r#"
-    ///     - It's the same string!
-    ///     - "It's the same string!"
-    ///     - 'It''s the same string!'
-    ///     "#
+    ///     r"
+    ///     server:
+    ///       port: 8080
+    ///     "
     /// )?;
     ///
-    /// let array = doc.as_ref().as_sequence().context("expected sequence")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let server = root.get("server").and_then(|v| v.as_mapping()).context("missing server")?;
+    /// let port = server.get("port").context("missing port")?;
     ///
-    /// for item in array {
-    ///     assert_eq!(item.as_bstr(), Some(BStr::new("It's the same string!")));
-    /// }
+    /// assert_eq!(doc.as_ref().location(), Some((2, 5)));
+    /// assert_eq!(port.location(), Some((3, 13)));
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[must_use]
-    pub fn as_bstr(&self) -> Option<&'a BStr> {
-        match self.data.raw(self.id) {
-            Raw::String(raw) => Some(self.data.str(raw.id)),
-            _ => None,
-        }
+    pub fn location(&self) -> Option<(usize, usize)> {
+        self.data.location(self.span()?.start)
     }
 
-    /// Get the value as a [`str`]. This might fail if the underlying string is
-    /// not valid UTF-8.
+    /// Test if two values are logically equal, ignoring how they are
+    /// formatted.
     ///
-    /// See [`Value::as_bstr`] for an alternative.
+    /// Strings are compared by their content regardless of how they are
+    /// quoted, numbers by their value so that `1` equals `1.0` and `0x10`
+    /// equals `16`, mappings by their keys and values regardless of the order
+    /// of the keys, and sequences element-wise. Values with different custom
+    /// tags are never equal.
     ///
-    /// # Escape sequences and unicode
+    /// Unlike [`Document::semantic_eq`], which compares numbers by how they are
+    /// spelled, this compares numbers by value. The values might belong to
+    /// different documents.
     ///
-    /// YAML supports a variety of escape sequences which will be handled by
-    /// this parser.
+    /// [`Document::semantic_eq`]: crate::yaml::Document::semantic_eq
+    ///
+    /// # Examples
     ///
     /// ```
-    /// use anyhow::Context;
     /// use nondestructive::yaml;
     ///
-    /// let doc = yaml::from_slice("フェリスと言います！")?;
-    /// assert_eq!(doc.as_ref().as_str(), Some("フェリスと言います！"));
+    /// let a = yaml::from_slice("{name: 'x', replicas: 1, ports: [80, 443]}")?;
+    /// let b = yaml::from_slice("ports: [80, 443]\nreplicas: 1.0\nname: \"x\"\n")?;
+    /// let c = yaml::from_slice("{name: x, replicas: 2, ports: [80, 443]}")?;
+    ///
+    /// assert!(a.as_ref().eq_logical(&b.as_ref()));
+    /// assert!(!a.as_ref().eq_logical(&c.as_ref()));
+    ///
+    /// // Documents compare numbers by their representation.
+    /// assert!(!a.semantic_eq(&b));
     /// # Ok::<_, anyhow::Error>(())
     /// ```
+    #[must_use]
+    pub fn eq_logical(&self, other: &Value<'_>) -> bool {
+        eq::semantic_eq(self.data, self.id, other.data, other.id, eq::Mode::LOGICAL)
+    }
+
+    /// Get the collection which contains this value, or `None` if this is the
+    /// root value of the document.
+    ///
+    /// # Examples
     ///
     /// ```
     /// use anyhow::Context;
     /// use nondestructive::yaml;
     ///
-    /// let doc = yaml::from_slice("\"hello \\x20 world\"")?;
-    /// assert_eq!(doc.as_ref().as_str(), Some("hello \x20 world"));
-    /// assert_eq!(doc.to_string(), "\"hello \\x20 world\"");
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     services:
+    ///       web:
+    ///         ports: [80, 443]
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let web = root.get("services").and_then(|v| v.as_mapping()).and_then(|m| m.get("web")).context("missing web")?;
+    /// let ports = web.as_mapping().and_then(|m| m.get("ports")).context("missing ports")?;
+    /// let sequence = ports.as_sequence().context("missing sequence")?;
+    /// let port = sequence.get(0).context("missing port")?;
+    ///
+    /// assert_eq!(port.parent().map(|v| v.id()), Some(ports.id()));
+    /// assert_eq!(ports.parent().map(|v| v.id()), Some(web.id()));
+    /// assert!(doc.as_ref().parent().is_none());
     /// # Ok::<_, anyhow::Error>(())
     /// ```
+    #[must_use]
+    #[inline]
+    pub fn parent(&self) -> Option<Value<'a>> {
+        let parent = self.data.parent(self.id)?;
+        Some(Value::new(self.data, parent))
+    }
+
+    /// Get the depth of this value in the document, where the root value has
+    /// a depth of `0` and every collection it is nested in adds one to it.
     ///
-    /// # Examples
+    /// Note that this walks the parents of the value, so it has a cost
+    /// proportional to the depth.
     ///
-    /// Some simple primitive strings:
+    /// # Examples
     ///
     /// ```
     /// use anyhow::Context;
     /// use nondestructive::yaml;
     ///
-    /// let doc = yaml::from_slice("string")?;
-    /// assert_eq!(doc.as_ref().as_str(), Some("string"));
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     services:
+    ///       web:
+    ///         ports: [80, 443]
+    ///     "
+    /// )?;
     ///
-    /// let doc = yaml::from_slice("key::value")?;
-    /// assert_eq!(doc.as_ref().as_str(), Some("key::value"));
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let services = root.get("services").context("missing services")?;
+    /// let web = services.as_mapping().and_then(|m| m.get("web")).context("missing web")?;
+    /// let ports = web.as_mapping().and_then(|m| m.get("ports")).context("missing ports")?;
+    /// let sequence = ports.as_sequence().context("missing sequence")?;
+    /// let port = sequence.get(1).context("missing port")?;
+    ///
+    /// assert_eq!(doc.as_ref().depth(), 0);
+    /// assert_eq!(services.depth(), 1);
+    /// assert_eq!(web.depth(), 2);
+    /// assert_eq!(port.depth(), 4);
     /// # Ok::<_, anyhow::Error>(())
     /// ```
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        let mut depth = 0usize;
+        let mut current = self.id;
+
+        while let Some(parent) = self.data.parent(current) {
+            depth = depth.wrapping_add(1);
+            current = parent;
+        }
+
+        depth
+    }
+
+    /// Test if this is the root value of the document.
     ///
-    /// An array exemplifying different string escapes:
+    /// # Examples
     ///
     /// ```
     /// use anyhow::Context;
     /// use nondestructive::yaml;
     ///
     /// let doc = yaml::from_slice(
-    ///     r#"
-    ///     - It's the same string!
-    ///     - "It's the same string!"
-    ///     - 'It''s the same string!'
-    ///     "#
+    ///     r"
+    ///     services:
+    ///       web: nginx
+    ///     "
     /// )?;
     ///
-    /// let array = doc.as_ref().as_sequence().context("expected sequence")?;
+    /// let root = doc.as_ref();
+    /// let services = root.as_mapping().and_then(|m| m.get("services")).context("missing services")?;
     ///
-    /// for item in array {
-    ///     assert_eq!(item.as_str(), Some("It's the same string!"));
-    /// }
+    /// assert!(root.is_root());
+    /// assert!(!services.is_root());
+    /// assert!(services.parent().context("missing parent")?.is_root());
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[must_use]
-    pub fn as_str(&self) -> Option<&'a str> {
-        match self.data.raw(self.id) {
-            Raw::String(raw) => self.data.str(raw.id).to_str().ok(),
-            _ => None,
-        }
+    pub fn is_root(&self) -> bool {
+        self.data.parent(self.id).is_none() && !self.data.layout(self.id).detached
     }
 
-    /// Get the value as a boolean.
+    /// Get the comment which follows the value on the same line, without the
+    /// leading `#` and surrounding whitespace.
+    ///
+    /// For block collections this is the comment on the line which opens the
+    /// collection, such as the one following `key:`. Comments following the
+    /// very last value of a document are not associated with any value.
     ///
     /// # Examples
     ///
@@ -442,22 +699,39 @@ impl<'a> Value<'a> {
     /// use anyhow::Context;
     /// use nondestructive::yaml;
     ///
-    /// let doc = yaml::from_slice("true")?;
-    /// assert_eq!(doc.as_ref().as_bool(), Some(true));
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     image: nginx # pinned below
+    ///     tag: '1.25'
+    ///     ports: # exposed
+    ///       - 80 # http
+    ///       - 443
+    ///     user: www
+    ///     "
+    /// )?;
     ///
-    /// let doc = yaml::from_slice("string")?;
-    /// assert_eq!(doc.as_ref().as_bool(), None);
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let ports = root.get("ports").context("missing ports")?;
+    /// let sequence = ports.as_sequence().context("missing sequence")?;
+    ///
+    /// assert_eq!(root.get("image").and_then(|v| v.comment()), Some("pinned below".into()));
+    /// assert_eq!(root.get("tag").and_then(|v| v.comment()), None);
+    /// assert_eq!(ports.comment(), Some("exposed".into()));
+    /// assert_eq!(sequence.get(0).and_then(|v| v.comment()), Some("http".into()));
+    /// assert_eq!(sequence.get(1).and_then(|v| v.comment()), None);
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[must_use]
-    pub fn as_bool(&self) -> Option<bool> {
-        match self.data.raw(self.id) {
-            Raw::Boolean(bool) => Some(bool.value),
-            _ => None,
-        }
+    pub fn comment(&self) -> Option<&'a BStr> {
+        raw::trailing_comment(self.data, self.id)
     }
 
-    /// Get the value as a [`Mapping`].
+    /// Get the explicit tag associated with the value, like `!!str` or
+    /// `!custom`.
+    ///
+    /// The tag is returned as it is written, so verbatim tags keep their
+    /// delimiters like in `!<tag:yaml.org,2002:str>`. Tags are preserved as
+    /// written when the document is displayed.
     ///
     /// # Examples
     ///
@@ -466,35 +740,38 @@ impl<'a> Value<'a> {
     /// use nondestructive::yaml;
     ///
     /// let doc = yaml::from_slice(
-    ///     r#"
-    ///     number1: 10
-    ///     number2: 20
-    ///     mapping:
-    ///         inner: 400
-    ///     string3: "I am a quoted string!"
-    ///     "#
+    ///     r"
+    ///     version: !!str 123
+    ///     secret: !vault |
+    ///       encrypted
+    ///     date: !<tag:yaml.org,2002:timestamp> 2001-12-14
+    ///     plain: 123
+    ///     "
     /// )?;
     ///
-    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
-    ///
-    /// assert_eq!(root.get("number1").and_then(|v| v.as_u32()), Some(10));
-    /// assert_eq!(root.get("number2").and_then(|v| v.as_u32()), Some(20));
-    ///
-    /// let mapping = root.get("mapping").and_then(|v| v.as_mapping()).context("missing inner mapping")?;
-    /// assert_eq!(mapping.get("inner").and_then(|v| v.as_u32()), Some(400));
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
     ///
-    /// assert_eq!(root.get("string3").and_then(|v| v.as_str()), Some("I am a quoted string!"));
+    /// assert_eq!(root.get("version").and_then(|v| v.tag()), Some("!!str".into()));
+    /// assert_eq!(root.get("secret").and_then(|v| v.tag()), Some("!vault".into()));
+    /// assert_eq!(
+    ///     root.get("date").and_then(|v| v.tag()),
+    ///     Some("!<tag:yaml.org,2002:timestamp>".into())
+    /// );
+    /// assert_eq!(root.get("plain").and_then(|v| v.tag()), None);
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[must_use]
-    pub fn as_mapping(&self) -> Option<Mapping<'a>> {
-        match self.data.raw(self.id) {
-            Raw::Mapping(..) => Some(Mapping::new(self.data, self.id)),
-            _ => None,
-        }
+    pub fn tag(&self) -> Option<&'a BStr> {
+        let tag = self.data.tag(self.id)?;
+        // The stored tag includes whatever precedes it, like indentation.
+        let start = tag
+            .rfind_byteset(b" \t\r\n")
+            .map_or(0, |n| n.saturating_add(1));
+        Some(tag[start..].as_bstr())
     }
 
-    /// Get the value as a [`Sequence`].
+    /// Get the name of the anchor associated with the value, like `name` in
+    /// `&name`.
     ///
     /// # Examples
     ///
@@ -504,28 +781,25 @@ impl<'a> Value<'a> {
     ///
     /// let doc = yaml::from_slice(
     ///     r"
-    ///     - one
-    ///     - two
-    ///     - three
-    ///     ",
+    ///     defaults: &defaults
+    ///       image: nginx
+    ///     web: *defaults
+    ///     "
     /// )?;
     ///
-    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
     ///
-    /// assert_eq!(root.get(0).and_then(|v| v.as_str()), Some("one"));
-    /// assert_eq!(root.get(1).and_then(|v| v.as_str()), Some("two"));
-    /// assert_eq!(root.get(2).and_then(|v| v.as_str()), Some("three"));
+    /// assert_eq!(root.get("defaults").and_then(|v| v.anchor()), Some("defaults".into()));
+    /// assert_eq!(root.get("web").and_then(|v| v.anchor()), None);
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[must_use]
-    pub fn as_sequence(&self) -> Option<Sequence<'a>> {
-        match self.data.raw(self.id) {
-            Raw::Sequence(..) => Some(Sequence::new(self.data, self.id)),
-            _ => None,
-        }
+    pub fn anchor(&self) -> Option<&'a BStr> {
+        self.data.anchor(self.id)
     }
 
-    /// Coerce a number to help discriminate the value type borrowing from self.
+    /// Get the name of the anchor this value refers to if it's an alias, like
+    /// `name` in `*name`.
     ///
     /// # Examples
     ///
@@ -533,21 +807,27 @@ impl<'a> Value<'a> {
     /// use anyhow::Context;
     /// use nondestructive::yaml;
     ///
-    /// let doc = yaml::from_slice("42")?;
-    /// let doc = doc.as_ref();
+    /// let doc = yaml::from_slice("[&first 1, *first, '*first']")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root")?;
     ///
-    /// assert!(matches!(doc.as_number(), Some(..)));
+    /// assert_eq!(root.get(0).and_then(|v| v.alias()), None);
+    /// assert_eq!(root.get(1).and_then(|v| v.alias()), Some("first".into()));
+    /// assert_eq!(root.get(2).and_then(|v| v.alias()), None);
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[must_use]
-    pub fn as_number(&self) -> Option<Number<'_>> {
+    pub fn alias(&self) -> Option<&'a BStr> {
         match self.data.raw(self.id) {
-            Raw::Number(raw) => Some(Number::new(self.data, raw)),
+            Raw::Alias(raw) => Some(self.data.str(raw.name)),
             _ => None,
         }
     }
 
-    /// Coerce a number to help discriminate the value type borrowing from self.
+    /// Resolve the value an alias refers to.
+    ///
+    /// This is the closest value preceeding the alias in the document which
+    /// has a matching anchor. Returns `None` if this isn't an alias, if no
+    /// such anchor exists, or if the alias refers to a value which contains it.
     ///
     /// # Examples
     ///
@@ -555,21 +835,969 @@ impl<'a> Value<'a> {
     /// use anyhow::Context;
     /// use nondestructive::yaml;
     ///
-    /// let doc = yaml::from_slice("42")?;
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     base: &image nginx
+    ///     web:
+    ///       image: *image
+    ///     worker:
+    ///       image: *missing
+    ///     "
+    /// )?;
     ///
-    /// assert!(matches!(doc.as_ref().into_number(), Some(..)));
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let web = root.get("web").and_then(|v| v.as_mapping()).context("missing web")?;
+    /// let worker = root.get("worker").and_then(|v| v.as_mapping()).context("missing worker")?;
+    ///
+    /// let image = web.get("image").and_then(|v| v.resolve_alias()).context("missing image")?;
+    /// assert_eq!(image.as_str(), Some("nginx"));
+    /// assert_eq!(image.id(), root.get("base").context("missing base")?.id());
+    ///
+    /// assert!(worker.get("image").and_then(|v| v.resolve_alias()).is_none());
+    /// assert!(root.get("base").and_then(|v| v.resolve_alias()).is_none());
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[must_use]
-    pub fn into_number(self) -> Option<Number<'a>> {
-        match self.data.raw(self.id) {
-            Raw::Number(raw) => Some(Number::new(self.data, raw)),
-            _ => None,
-        }
+    pub fn resolve_alias(&self) -> Option<Value<'a>> {
+        let id = raw::resolve_alias(self.data, self.id)?;
+        Some(Value::new(self.data, id))
+    }
+
+    /// Resolve the value an alias refers to like [`Value::resolve_alias`], but
+    /// error if the alias refers to a value which contains it.
+    ///
+    /// Returns `Ok(None)` if this isn't an alias or if no matching anchor
+    /// exists.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::AnchorCycle`] if the alias refers to an anchor
+    /// on a value which contains it, like `a: &x [*x]`. Expanding such an
+    /// alias would never terminate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     base: &base [1]
+    ///     copy: *base
+    ///     cycle: &cycle [*cycle]
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    ///
+    /// let copy = root.get("copy").context("missing copy")?.try_resolve_alias()?;
+    /// assert_eq!(copy.map(|v| v.id()), root.get("base").map(|v| v.id()));
+    ///
+    /// let cycle = root.get("cycle").and_then(|v| v.as_sequence()).context("missing cycle")?;
+    /// let error = cycle.get(0).context("missing alias")?.try_resolve_alias().unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::AnchorCycle);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn try_resolve_alias(&self) -> Result<Option<Value<'a>>, Error> {
+        if let Some(value) = self.resolve_alias() {
+            return Ok(Some(value));
+        }
+
+        if raw::is_anchor_cycle(self.data, self.id) {
+            return Err(Error::value(self.data, self.id, ErrorKind::AnchorCycle));
+        }
+
+        Ok(None)
+    }
+
+    /// Get the style of the value if it's a mapping or a sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     block:
+    ///       - 1
+    ///     flow: {a: 1}
+    ///     scalar: 1
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    ///
+    /// assert_eq!(doc.as_ref().collection_style(), Some(yaml::CollectionStyle::Block));
+    /// assert_eq!(root.get("block").and_then(|v| v.collection_style()), Some(yaml::CollectionStyle::Block));
+    /// assert_eq!(root.get("flow").and_then(|v| v.collection_style()), Some(yaml::CollectionStyle::Flow));
+    /// assert_eq!(root.get("scalar").and_then(|v| v.collection_style()), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn collection_style(&self) -> Option<CollectionStyle> {
+        match self.data.raw(self.id) {
+            Raw::Mapping(..) | Raw::Sequence(..) if raw::is_inline(self.data, self.id) => {
+                Some(CollectionStyle::Flow)
+            }
+            Raw::Mapping(..) | Raw::Sequence(..) => Some(CollectionStyle::Block),
+            _ => None,
+        }
+    }
+
+    /// Get the kind of block if the value is a literal `|` or folded `>`
+    /// block scalar, including its chomping indicator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     literal: |
+    ///       one
+    ///        two
+    ///     folded: >-
+    ///       one
+    ///       two
+    ///
+    ///       three
+    ///     plain: one
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let literal = root.get("literal").context("missing literal")?;
+    /// let folded = root.get("folded").context("missing folded")?;
+    ///
+    /// assert_eq!(literal.block(), Some(yaml::Block::Literal(yaml::Chomp::Clip)));
+    /// assert_eq!(literal.as_str(), Some("one\n two\n"));
+    /// assert_eq!(folded.block(), Some(yaml::Block::Folded(yaml::Chomp::Strip)));
+    /// assert_eq!(folded.as_str(), Some("one two\nthree"));
+    /// assert_eq!(root.get("plain").and_then(|v| v.block()), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn block(&self) -> Option<Block> {
+        let Raw::String(raw) = self.data.raw(self.id) else {
+            return None;
+        };
+
+        if !matches!(raw.kind, RawStringKind::Multiline) {
+            return None;
+        }
+
+        raw::block_of(self.data.str(raw.original))
+    }
+
+    /// Serialize the value as if it was a standalone document.
+    ///
+    /// Unlike the [`Display`] implementation, which keeps the indentation the
+    /// value has inside of its document, block collections are re-indented to
+    /// start at the left margin.
+    ///
+    /// [`Display`]: fmt::Display
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     spec:
+    ///       template:
+    ///         image: nginx
+    ///         ports: [80, 443]
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let spec = root.get("spec").and_then(|v| v.as_mapping()).context("missing spec")?;
+    /// let template = spec.get("template").context("missing template")?;
+    ///
+    /// assert_eq!(
+    ///     template.to_string(),
+    ///     "image: nginx\n        ports: [80, 443]"
+    /// );
+    ///
+    /// assert_eq!(
+    ///     template.to_document_string(),
+    ///     "image: nginx\nports: [80, 443]"
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_document_string(&self) -> std::string::String {
+        let string = self.to_string();
+
+        let indent = match self.data.raw(self.id) {
+            Raw::Mapping(raw) if !raw::is_inline(self.data, self.id) => raw.indent,
+            Raw::Sequence(raw) if !raw::is_inline(self.data, self.id) => raw.indent,
+            _ => return string,
+        };
+
+        let Ok(indent) = isize::try_from(indent) else {
+            return string;
+        };
+
+        match raw::indent_lines(string.as_bytes(), -indent) {
+            Some(out) => out.to_str_lossy().into_owned(),
+            None => string,
+        }
+    }
+
+    /// Get the value as a [`BStr`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    /// use bstr::BStr;
+    ///
+    /// let doc = yaml::from_slice("string")?;
+    /// assert_eq!(doc.as_ref().as_str(), Some("string"));
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     - It's the same string!
+    ///     - "It's the same string!"
+    ///     - 'It''s the same string!'
+    ///     "#
+    /// )?;
+    ///
+    /// let array = doc.as_ref().as_sequence().context("expected sequence")?;
+    ///
+    /// for item in array {
+    ///     assert_eq!(item.as_bstr(), Some(BStr::new("It's the same string!")));
+    /// }
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_bstr(&self) -> Option<&'a BStr> {
+        match self.data.raw(self.id) {
+            Raw::String(raw) => Some(self.data.str(raw.id)),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a [`str`]. This might fail if the underlying string is
+    /// not valid UTF-8.
+    ///
+    /// See [`Value::as_bstr`] for an alternative.
+    ///
+    /// # Escape sequences and unicode
+    ///
+    /// YAML supports a variety of escape sequences which will be handled by
+    /// this parser.
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("フェリスと言います！")?;
+    /// assert_eq!(doc.as_ref().as_str(), Some("フェリスと言います！"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("\"hello \\x20 world\"")?;
+    /// assert_eq!(doc.as_ref().as_str(), Some("hello \x20 world"));
+    /// assert_eq!(doc.to_string(), "\"hello \\x20 world\"");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    ///
+    /// Every escape sequence of double-quoted strings in YAML 1.2 is
+    /// supported, including `\uXXXX`, `\UXXXXXXXX` and the named escapes
+    /// `\N`, `\_`, `\L` and `\P`. The original text is kept when the document
+    /// is written back out.
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(r#""caf\u00e9 \U0001F600\_\\""#)?;
+    /// assert_eq!(doc.as_ref().as_str(), Some("caf\u{e9} \u{1f600}\u{a0}\\"));
+    /// assert_eq!(doc.to_string(), r#""caf\u00e9 \U0001F600\_\\""#);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// Some simple primitive strings:
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("string")?;
+    /// assert_eq!(doc.as_ref().as_str(), Some("string"));
+    ///
+    /// let doc = yaml::from_slice("key::value")?;
+    /// assert_eq!(doc.as_ref().as_str(), Some("key::value"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    ///
+    /// An array exemplifying different string escapes:
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     - It's the same string!
+    ///     - "It's the same string!"
+    ///     - 'It''s the same string!'
+    ///     "#
+    /// )?;
+    ///
+    /// let array = doc.as_ref().as_sequence().context("expected sequence")?;
+    ///
+    /// for item in array {
+    ///     assert_eq!(item.as_str(), Some("It's the same string!"));
+    /// }
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self.data.raw(self.id) {
+            Raw::String(raw) => self.data.str(raw.id).to_str().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the exact source text of a scalar value as it appeared in the
+    /// parsed document, including any quotes or block indicators.
+    ///
+    /// Only the text of the value itself is returned. Any tag or anchor, and
+    /// the whitespace and comments which precede or follow the value are not
+    /// included. Block strings span from their indicator to the end of their
+    /// last line of content, with every line indented as it was written. An
+    /// empty value such as the one in `key:` produces an empty string.
+    ///
+    /// This differs from [`Value::as_str`] which decodes the string, and from
+    /// formatting the value which serializes it again. Scalars which have been
+    /// set programmatically produce the text they are written as, except for
+    /// strings set with an explicit quoting style such as through
+    /// [`ValueMut::set_string_with`]. For those, as well as for collections,
+    /// aliases, and text which is not valid UTF-8, `None` is returned.
+    ///
+    /// [`ValueMut::set_string_with`]: crate::yaml::ValueMut::set_string_with
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r#"
+    ///     - "hello\tworld"
+    ///     - 'It''s'
+    ///     - bare
+    ///     - 0x10
+    ///     - ~
+    ///     - |
+    ///       block
+    ///     - [1, 2]
+    ///     "#
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// let sources = root.iter().map(|v| v.source_str()).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     sources,
+    ///     [
+    ///         Some("\"hello\\tworld\""),
+    ///         Some("'It''s'"),
+    ///         Some("bare"),
+    ///         Some("0x10"),
+    ///         Some("~"),
+    ///         Some("|\n      block"),
+    ///         None,
+    ///     ]
+    /// );
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.get_mut(0).context("missing first")?.set_string_with("hello", yaml::StringKind::Double);
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// assert_eq!(root.get(0).and_then(|v| v.source_str()), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn source_str(&self) -> Option<&'a str> {
+        let string = match self.data.raw(self.id) {
+            Raw::Null(raw::Null::Keyword(string)) => *string,
+            Raw::Null(raw::Null::Tilde) => return Some("~"),
+            Raw::Null(raw::Null::Empty) => return Some(""),
+            Raw::Boolean(raw) => raw.string,
+            Raw::Number(raw) => raw.string,
+            Raw::String(raw) => match raw.kind {
+                RawStringKind::Bare => raw.id,
+                RawStringKind::Original | RawStringKind::Multiline => raw.original,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        self.data.str(string).to_str().ok()
+    }
+
+    /// Get the string form of any scalar value, regardless of its type.
+    ///
+    /// Strings are decoded like with [`Value::as_str`], while numbers, booleans
+    /// and nulls produce the text they were written as. Only returns `None`
+    /// for collections.
+    ///
+    /// Any invalid UTF-8 is replaced with the unicode replacement character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     - "hello\tworld"
+    ///     - 1.50
+    ///     - TRUE
+    ///     - ~
+    ///     - Null
+    ///     - {a: 1}
+    ///     "#
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// let strings = root.iter().map(|v| v.coerce_str()).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     strings,
+    ///     [
+    ///         Some("hello\tworld".into()),
+    ///         Some("1.50".into()),
+    ///         Some("TRUE".into()),
+    ///         Some("~".into()),
+    ///         Some("Null".into()),
+    ///         None,
+    ///     ]
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn coerce_str(&self) -> Option<Cow<'a, str>> {
+        let string = match self.data.raw(self.id) {
+            Raw::Null(raw::Null::Keyword(string)) => *string,
+            Raw::Null(raw::Null::Tilde) => return Some(Cow::Borrowed("~")),
+            Raw::Null(raw::Null::Empty) => return Some(Cow::Borrowed("")),
+            Raw::Boolean(raw) => raw.string,
+            Raw::Number(raw) => raw.string,
+            Raw::String(raw) => raw.id,
+            _ => return None,
+        };
+
+        Some(self.data.str(string).to_str_lossy())
+    }
+
+    /// Test if the value is null.
+    ///
+    /// This includes the `null` and `~` keywords, as well as values which are
+    /// empty, like the value of `key:`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     keyword: null
+    ///     tilde: ~
+    ///     empty:
+    ///     string: 'null'
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.get("keyword").map(|v| v.is_null()), Some(true));
+    /// assert_eq!(root.get("tilde").map(|v| v.is_null()), Some(true));
+    /// assert_eq!(root.get("empty").map(|v| v.is_null()), Some(true));
+    /// assert_eq!(root.get("string").map(|v| v.is_null()), Some(false));
+    /// assert_eq!(root.get("missing").map(|v| v.is_null()), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        matches!(self.data.raw(self.id), Raw::Null(..))
+    }
+
+    /// Test if the value is a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[hello, 'quoted', 42]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// assert!(root.iter().map(|v| v.is_string()).eq([true, true, false]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_string(&self) -> bool {
+        matches!(self.data.raw(self.id), Raw::String(..))
+    }
+
+    /// Test if the value is a number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[42, -1.5, .inf, '42']")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// assert!(root.iter().map(|v| v.is_number()).eq([true, true, true, false]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_number(&self) -> bool {
+        matches!(self.data.raw(self.id), Raw::Number(..))
+    }
+
+    /// Test if the value is a boolean.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[true, false, 'true', 1]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// assert!(root.iter().map(|v| v.is_bool()).eq([true, true, false, false]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_bool(&self) -> bool {
+        matches!(self.data.raw(self.id), Raw::Boolean(..))
+    }
+
+    /// Test if the value is a mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[{a: 1}, {}, [a], a]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// assert!(root.iter().map(|v| v.is_mapping()).eq([true, true, false, false]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_mapping(&self) -> bool {
+        matches!(self.data.raw(self.id), Raw::Mapping(..))
+    }
+
+    /// Test if the value is a sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[[1, 2], [], {a: 1}, a]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// assert!(root.iter().map(|v| v.is_sequence()).eq([true, true, false, false]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_sequence(&self) -> bool {
+        matches!(self.data.raw(self.id), Raw::Sequence(..))
+    }
+
+    /// Test if the value is a string which would have to be quoted if it were
+    /// written without quotes.
+    ///
+    /// This tests the decoded content of the string, regardless of how it is
+    /// currently written, so it can be used to tell if writing the same
+    /// content as a bare string would introduce quotes. Strings inside of
+    /// inline collections are held to stricter rules, since `,[]{}` are
+    /// significant there.
+    ///
+    /// Values which are not strings return `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     bare: hello world
+    ///     quoted: "hello world"
+    ///     number: '42'
+    ///     colon: 'key: value'
+    ///     comma: a,b
+    ///     inline: ['a,b', c]
+    ///     "#
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.get("bare").map(|v| v.requires_quoting_as_bare()), Some(false));
+    /// assert_eq!(root.get("quoted").map(|v| v.requires_quoting_as_bare()), Some(false));
+    /// assert_eq!(root.get("number").map(|v| v.requires_quoting_as_bare()), Some(true));
+    /// assert_eq!(root.get("colon").map(|v| v.requires_quoting_as_bare()), Some(true));
+    /// assert_eq!(root.get("comma").map(|v| v.requires_quoting_as_bare()), Some(false));
+    ///
+    /// let inline = root.get("inline").and_then(|v| v.as_sequence()).context("missing inline")?;
+    /// assert_eq!(inline.get(0).map(|v| v.requires_quoting_as_bare()), Some(true));
+    /// assert_eq!(inline.get(1).map(|v| v.requires_quoting_as_bare()), Some(false));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn requires_quoting_as_bare(&self) -> bool {
+        let Raw::String(raw) = self.data.raw(self.id) else {
+            return false;
+        };
+
+        let Ok(string) = self.data.str(raw.id).to_str() else {
+            return true;
+        };
+
+        raw::requires_quoting(self.data, string, raw::is_inline_item(self.data, self.id))
+    }
+
+    /// Get the decoded contents of a string tagged with `!!binary`, which
+    /// holds base64-encoded data.
+    ///
+    /// Whitespace inside of the encoded data is ignored, so that payloads which
+    /// are folded over multiple lines using block scalars can be decoded. The
+    /// original encoded string is left as-is in the document.
+    ///
+    /// Returns `None` if the value isn't tagged with `!!binary` or if it isn't
+    /// valid base64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     inline: !!binary aGVsbG8=
+    ///     block: !!binary |
+    ///       aGVs
+    ///       bG8=
+    ///     plain: aGVsbG8=
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    ///
+    /// assert_eq!(root.get("inline").and_then(|v| v.as_bytes()).as_deref(), Some(&b"hello"[..]));
+    /// assert_eq!(root.get("block").and_then(|v| v.as_bytes()).as_deref(), Some(&b"hello"[..]));
+    /// assert_eq!(root.get("plain").and_then(|v| v.as_bytes()), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_bytes(&self) -> Option<Vec<u8>> {
+        if !self.tag().is_some_and(|tag| raw::is_binary_tag(tag)) {
+            return None;
+        }
+
+        let Raw::String(raw) = self.data.raw(self.id) else {
+            return None;
+        };
+
+        raw::decode_base64(self.data.str(raw.id))
+    }
+
+    /// Get the value as a [`Timestamp`].
+    ///
+    /// Plain scalars such as `2001-12-15` or `2001-12-14 21:59:43.10 -5` are
+    /// recognized, as are strings explicitly tagged with `!!timestamp`. The
+    /// original text of the scalar is left as-is in the document.
+    ///
+    /// Returns `None` if the value isn't a valid timestamp, or if it's a
+    /// quoted string without a `!!timestamp` tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     date: 2002-12-14
+    ///     canonical: 2001-12-15T02:59:43.1Z
+    ///     spaced: 2001-12-14 21:59:43.10 -5
+    ///     tagged: !!timestamp "2001-12-14"
+    ///     quoted: "2001-12-14"
+    ///     invalid: 2001-02-29
+    ///     "#,
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    ///
+    /// let date = root.get("date").and_then(|v| v.as_timestamp()).context("missing date")?;
+    /// assert_eq!((date.year(), date.month(), date.day()), (2002, 12, 14));
+    /// assert!(!date.has_time());
+    ///
+    /// let canonical = root.get("canonical").and_then(|v| v.as_timestamp()).context("missing canonical")?;
+    /// assert_eq!(canonical.nanosecond(), 100_000_000);
+    ///
+    /// let spaced = root.get("spaced").and_then(|v| v.as_timestamp()).context("missing spaced")?;
+    /// assert_eq!(spaced.to_string(), "2001-12-14T21:59:43.1-05:00");
+    ///
+    /// assert!(root.get("tagged").and_then(|v| v.as_timestamp()).is_some());
+    /// assert!(root.get("quoted").and_then(|v| v.as_timestamp()).is_none());
+    /// assert!(root.get("invalid").and_then(|v| v.as_timestamp()).is_none());
+    ///
+    /// assert_eq!(root.get("spaced").and_then(|v| v.as_str()), Some("2001-12-14 21:59:43.10 -5"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_timestamp(&self) -> Option<Timestamp> {
+        let Raw::String(raw) = self.data.raw(self.id) else {
+            return None;
+        };
+
+        let tagged = self.tag().is_some_and(|tag| raw::is_timestamp_tag(tag));
+
+        if !tagged
+            && (matches!(raw.kind, RawStringKind::Multiline) || raw.quote(self.data).is_some())
+        {
+            return None;
+        }
+
+        Timestamp::parse(self.data.str(raw.id))
+    }
+
+    /// Get the value as a boolean.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("true")?;
+    /// assert_eq!(doc.as_ref().as_bool(), Some(true));
+    ///
+    /// let doc = yaml::from_slice("string")?;
+    /// assert_eq!(doc.as_ref().as_bool(), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.data.raw(self.id) {
+            Raw::Boolean(bool) => Some(bool.value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a [`Mapping`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     number1: 10
+    ///     number2: 20
+    ///     mapping:
+    ///         inner: 400
+    ///     string3: "I am a quoted string!"
+    ///     "#
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// assert_eq!(root.get("number1").and_then(|v| v.as_u32()), Some(10));
+    /// assert_eq!(root.get("number2").and_then(|v| v.as_u32()), Some(20));
+    ///
+    /// let mapping = root.get("mapping").and_then(|v| v.as_mapping()).context("missing inner mapping")?;
+    /// assert_eq!(mapping.get("inner").and_then(|v| v.as_u32()), Some(400));
+    ///
+    /// assert_eq!(root.get("string3").and_then(|v| v.as_str()), Some("I am a quoted string!"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_mapping(&self) -> Option<Mapping<'a>> {
+        match self.data.raw(self.id) {
+            Raw::Mapping(..) => Some(Mapping::new(self.data, self.id)),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a [`Sequence`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     - one
+    ///     - two
+    ///     - three
+    ///     ",
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// assert_eq!(root.get(0).and_then(|v| v.as_str()), Some("one"));
+    /// assert_eq!(root.get(1).and_then(|v| v.as_str()), Some("two"));
+    /// assert_eq!(root.get(2).and_then(|v| v.as_str()), Some("three"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_sequence(&self) -> Option<Sequence<'a>> {
+        match self.data.raw(self.id) {
+            Raw::Sequence(..) => Some(Sequence::new(self.data, self.id)),
+            _ => None,
+        }
+    }
+
+    /// Coerce a number to help discriminate the value type borrowing from self.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("42")?;
+    /// let doc = doc.as_ref();
+    ///
+    /// assert!(matches!(doc.as_number(), Some(..)));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_number(&self) -> Option<Number<'_>> {
+        match self.data.raw(self.id) {
+            Raw::Number(raw) => Some(Number::new(self.data, raw)),
+            _ => None,
+        }
+    }
+
+    /// Coerce a number to help discriminate the value type borrowing from self.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("42")?;
+    ///
+    /// assert!(matches!(doc.as_ref().into_number(), Some(..)));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn into_number(self) -> Option<Number<'a>> {
+        match self.data.raw(self.id) {
+            Raw::Number(raw) => Some(Number::new(self.data, raw)),
+            _ => None,
+        }
+    }
+
+    /// Get the notation the value is written in if it is a number.
+    ///
+    /// This allows for writing back an updated number in the same notation
+    /// through [`ValueMut::set_number_formatted`].
+    ///
+    /// [`ValueMut::set_number_formatted`]: crate::yaml::ValueMut::set_number_formatted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("0o755")?;
+    /// assert_eq!(doc.as_ref().number_style(), Some(yaml::NumberStyle::Octal));
+    ///
+    /// let doc = yaml::from_slice("'0o755'")?;
+    /// assert_eq!(doc.as_ref().number_style(), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn number_style(&self) -> Option<NumberStyle> {
+        Some(self.as_number()?.style())
+    }
+
+    /// Try and get the value as an [`Integer`], which can hold any value of
+    /// the primitive integer types.
+    ///
+    /// Methods like [`Value::as_u8`] return `None` both for values which
+    /// aren't integers and for integers which don't fit in the requested type.
+    /// This can be used to tell the two apart, since the returned [`Integer`]
+    /// is converted into a primitive integer with [`TryFrom`].
+    ///
+    /// Returns `None` if the value isn't an integer, or if it doesn't fit in
+    /// 128 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[300, '300', 3.5, -1]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// let value = root.get(0).context("missing value")?;
+    /// assert_eq!(value.as_u8(), None);
+    /// let integer = value.as_integer().context("expected an integer")?;
+    /// assert!(u8::try_from(integer).is_err());
+    /// assert_eq!(u16::try_from(integer)?, 300);
+    ///
+    /// assert!(root.get(1).and_then(|v| v.as_integer()).is_none());
+    /// assert!(root.get(2).and_then(|v| v.as_integer()).is_none());
+    ///
+    /// let integer = root.get(3).and_then(|v| v.as_integer()).context("expected an integer")?;
+    /// assert_eq!(i8::try_from(integer)?, -1);
+    /// assert!(u64::try_from(integer).is_err());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_integer(&self) -> Option<Integer> {
+        self.as_number()?.as_integer()
     }
 
-    as_number!(as_f32, f32, "32-bit float", 10.42);
-    as_number!(as_f64, f64, "64-bit float", 10.42);
+    as_float!(as_f32, f32, "32-bit float", 10.42);
+    as_float!(as_f64, f64, "64-bit float", 10.42);
     as_number!(as_u8, u8, "8-bit unsigned integer", 42);
     as_number!(as_i8, i8, "8-bit signed integer", -42);
     as_number!(as_u16, u16, "16-bit unsigned integer", 42);
@@ -580,12 +1808,39 @@ impl<'a> Value<'a> {
     as_number!(as_i64, i64, "64-bit signed integer", -42);
     as_number!(as_u128, u128, "16-bit unsigned integer", 42);
     as_number!(as_i128, i128, "128-bit signed integer", -42);
+    as_nonzero!(as_nonzero_u8, NonZeroU8, as_u8, "8-bit unsigned integer");
+    as_nonzero!(
+        as_nonzero_u16,
+        NonZeroU16,
+        as_u16,
+        "16-bit unsigned integer"
+    );
+    as_nonzero!(
+        as_nonzero_u32,
+        NonZeroU32,
+        as_u32,
+        "32-bit unsigned integer"
+    );
+    as_nonzero!(
+        as_nonzero_u64,
+        NonZeroU64,
+        as_u64,
+        "64-bit unsigned integer"
+    );
+    as_nonzero!(
+        as_nonzero_u128,
+        NonZeroU128,
+        as_u128,
+        "128-bit unsigned integer"
+    );
 }
 
 impl fmt::Display for Value<'_> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.data.raw(self.id).display(self.data, f, None)
+        self.data
+            .raw(self.id)
+            .write_to(self.data, &mut raw::Fmt(f), None)
     }
 }
 
@@ -599,7 +1854,7 @@ impl fmt::Debug for Value<'_> {
                 self.0
                     .data
                     .raw(self.0.id)
-                    .display(self.0.data, f, Some(self.0.id))
+                    .write_to(self.0.data, &mut raw::Fmt(f), Some(self.0.id))
             }
         }
 
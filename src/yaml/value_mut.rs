@@ -1,10 +1,15 @@
-use crate::yaml::data::{Data, Id};
+use bstr::ByteSlice;
+
+use crate::yaml::data::{Data, Id, StringId};
 use crate::yaml::raw::{self, Raw};
-use crate::yaml::{AnyMut, Block, MappingMut, Null, SequenceMut, StringKind, Value};
+use crate::yaml::{
+    AnyMut, Block, Chomp, CollectionStyle, Error, ErrorKind, MappingMut, Null, Parser, SequenceMut,
+    StringKind, Value,
+};
 
 /// A mutable value inside of a document.
 pub struct ValueMut<'a> {
-    data: &'a mut Data,
+    pub(crate) data: &'a mut Data,
     pub(crate) id: Id,
 }
 
@@ -333,7 +338,7 @@ macro_rules! set_float {
         /// ```
         pub fn $name(&mut self, value: $ty) {
             let mut buffer = ryu::Buffer::new();
-            let string = self.data.insert_str(buffer.format(value));
+            let string = self.data.insert_str(raw::format_float(&mut buffer, value));
             self.data.replace(self.id, Raw::Number(raw::Number::new(string, crate::yaml::serde_hint::$hint)));
         }
     };
@@ -366,6 +371,9 @@ macro_rules! set_number {
 impl<'a> ValueMut<'a> {
     /// Replace the current value with the specified null value.
     ///
+    /// An empty null inside of an inline sequence is written as `null`, since
+    /// an empty slot there isn't valid YAML.
+    ///
     /// # Examples
     ///
     /// ```
@@ -383,6 +391,11 @@ impl<'a> ValueMut<'a> {
     /// doc.as_mut().set_null(yaml::Null::Empty);
     /// assert_eq!(doc.to_string(), "  ");
     ///
+    /// let mut doc = yaml::from_slice("[1, 2]")?;
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.get_mut(0).context("missing first")?.set_null(yaml::Null::Empty);
+    /// root.get_mut(1).context("missing second")?.set_null(yaml::Null::Empty);
+    /// assert_eq!(doc.to_string(), "[null, null]");
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[inline]
@@ -402,7 +415,8 @@ impl<'a> ValueMut<'a> {
     /// used.
     ///
     /// The heuristic used is:
-    /// * [`StringKind::Single`] will be used if the leading digit is numeric.
+    /// * [`StringKind::Single`] will be used if the leading digit is numeric,
+    ///   or if the string is empty since an empty bare value is a null.
     /// * [`StringKind::Double`] will be used if either a single `'` is
     ///   encounted, or a non-graphical component that requires escaping.
     /// * Otherwise, [`StringKind::Bare`] is used.
@@ -472,6 +486,102 @@ impl<'a> ValueMut<'a> {
         self.data.replace(self.id, value);
     }
 
+    /// Edit the string stored in this value in place.
+    ///
+    /// The current string is decoded and passed to the closure, after which it
+    /// is written back. The existing quoting is preserved where possible,
+    /// strings which weren't quoted are only quoted if they have to be, and
+    /// literal blocks remain literal blocks as long as the edited string can
+    /// be represented as one.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::ExpectedString`] if the value is not a string,
+    /// and with [`ErrorKind::BadUtf8`] if the string is not valid UTF-8. The
+    /// value is left unmodified in both cases.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r#"
+    ///     name: web
+    ///     image: "nginx:1.24"
+    ///     port: 80
+    ///     script: |
+    ///       make
+    ///     "#
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    ///
+    /// let mut name = root.get_mut("name").context("missing name")?;
+    /// name.edit_string(|s| s.push_str(": frontend"))?;
+    ///
+    /// let mut image = root.get_mut("image").context("missing image")?;
+    /// image.edit_string(|s| *s = s.replace("1.24", "1.25"))?;
+    ///
+    /// let mut script = root.get_mut("script").context("missing script")?;
+    /// script.edit_string(|s| s.push_str("make install\n"))?;
+    ///
+    /// let mut port = root.get_mut("port").context("missing port")?;
+    /// assert!(port.edit_string(|s| s.push('0')).is_err());
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     name: 'web: frontend'
+    ///     image: "nginx:1.25"
+    ///     port: 80
+    ///     script: |
+    ///       make
+    ///       make install
+    ///     "#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn edit_string<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut std::string::String),
+    {
+        let Raw::String(raw) = self.data.raw(self.id) else {
+            return Err(Error::value(self.data, self.id, ErrorKind::ExpectedString));
+        };
+
+        let literal = matches!(raw.kind, raw::RawStringKind::Multiline)
+            && self.data.str(raw.original).starts_with(b"|");
+
+        let Ok(string) = self.data.str(raw.id).to_str() else {
+            return Err(Error::value(self.data, self.id, ErrorKind::BadUtf8));
+        };
+
+        let mut string = string.to_owned();
+        f(&mut string);
+
+        if literal && !string.is_empty() {
+            let (body, chomp) = match string.strip_suffix('\n') {
+                Some(body) => (body, Chomp::Clip),
+                None => (string.as_str(), Chomp::Strip),
+            };
+
+            let representable = !body.ends_with('\n')
+                && !body.starts_with(' ')
+                && !body.chars().any(|c| c.is_control() && c != '\n');
+
+            if representable {
+                self.set_block(body.split('\n'), Block::Literal(chomp));
+                return Ok(());
+            }
+        }
+
+        let value = raw::new_string_like(self.data, self.id, string);
+        self.data.replace(self.id, value);
+        Ok(())
+    }
+
     /// Set the value as a literal block.
     ///
     /// This takes an iterator, which will be used to construct the block. The
@@ -578,6 +688,279 @@ impl<'a> ValueMut<'a> {
         self.data.replace(self.id, value);
     }
 
+    /// Replace the value with a fragment of already formatted YAML.
+    ///
+    /// The fragment should be written as if it's at the start of a line,
+    /// and is reindented to fit the location of the value. Block collections
+    /// are placed on their own lines, like with [`ValueMut::make_mapping`].
+    /// Comments and formatting inside of the fragment are preserved.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the fragment can't be parsed, in which case the value is left
+    /// unmodified. The span of the error refers to the fragment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     name: web
+    ///     resources: ~
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    ///
+    /// root.get_mut("resources").context("missing resources")?.set_raw_yaml(
+    ///     "# Tuned for production.\nlimits:\n  cpu: 500m\n  memory: 128Mi\nports: [80, 443]\n",
+    /// )?;
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     name: web
+    ///     resources:
+    ///       ## Tuned for production.
+    ///       limits:
+    ///         cpu: 500m
+    ///         memory: 128Mi
+    ///       ports: [80, 443]
+    ///     "
+    /// );
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut name = root.get_mut("name").context("missing name")?;
+    /// assert!(name.set_raw_yaml("{a: 1").is_err());
+    /// name.set_raw_yaml("'quoted'")?;
+    /// assert_eq!(root.as_ref().get("name").and_then(|v| v.as_str()), Some("quoted"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_raw_yaml(&mut self, yaml: &str) -> Result<(), Error> {
+        let value = Parser::parse_fragment(self.data, yaml.as_bytes())?;
+        let (indent, prefix) = raw::make_indent(self.data, self.id, 0);
+        let indent = isize::try_from(indent).unwrap_or(isize::MAX);
+        self.data.indent_by(value, indent);
+
+        let mut prefix = if self.data.raw(value).is_tabular() {
+            self.data.str(prefix).to_vec()
+        } else {
+            self.data.prefix(self.id).to_vec()
+        };
+
+        let layout = *self.data.layout(value);
+
+        // Any comments leading up to the fragment are retained after the
+        // prefix of the value being replaced.
+        let prefix = if layout.tag.is_some() || layout.anchor.is_some() {
+            // The prefix goes in front of whichever property comes first.
+            let mut properties = [layout.anchor.map(|anchor| anchor.string), layout.tag];
+
+            if layout.anchor.is_some_and(|anchor| anchor.after_tag) {
+                properties.reverse();
+            }
+
+            let mut leading = Some(prefix);
+
+            for property in properties.iter_mut().flatten() {
+                let string = self.data.str(*property);
+                let string = raw::indent_lines(string, indent).unwrap_or_else(|| string.to_vec());
+
+                let string = match leading.take() {
+                    Some(mut prefix) => {
+                        prefix.extend(string.trim_start());
+                        prefix
+                    }
+                    None => string,
+                };
+
+                *property = self.data.insert_str(string);
+            }
+
+            if layout.anchor.is_some_and(|anchor| anchor.after_tag) {
+                properties.reverse();
+            }
+
+            let [anchor, tag] = properties;
+            self.data.set_tag(value, tag);
+            self.data.set_anchor(
+                value,
+                layout
+                    .anchor
+                    .zip(anchor)
+                    .map(|(anchor, string)| raw::Anchor { string, ..anchor }),
+            );
+
+            let own = self.data.str(layout.prefix);
+            let own = raw::indent_lines(own, indent).unwrap_or_else(|| own.to_vec());
+            self.data.insert_str(own)
+        } else {
+            let own = self.data.str(layout.prefix);
+
+            if let Some(n) = own.find_byte(b'#') {
+                let own = &own[n..];
+                prefix.extend(raw::indent_lines(own, indent).as_deref().unwrap_or(own));
+            }
+
+            self.data.insert_str(prefix)
+        };
+
+        self.data.relocate(value, self.id, prefix);
+        Ok(())
+    }
+
+    /// Set the value as a number written exactly as `text`.
+    ///
+    /// This allows for controlling the notation of the number, like its
+    /// radix, zero-padding or `_` digit separators, for example to keep the
+    /// [`NumberStyle`] of the number being replaced.
+    ///
+    /// Returns `false` and leaves the value untouched if `text` isn't a number.
+    ///
+    /// [`NumberStyle`]: crate::yaml::NumberStyle
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("mode: 0o644")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut mode = root.get_mut("mode").context("missing mode")?;
+    ///
+    /// if mode.as_ref().number_style() == Some(yaml::NumberStyle::Octal) {
+    ///     assert!(mode.set_number_formatted(&format!("0o{:o}", 0o755)));
+    /// }
+    ///
+    /// assert!(!mode.set_number_formatted("0o9"));
+    /// assert!(!mode.set_number_formatted("seven"));
+    /// assert_eq!(doc.to_string(), "mode: 0o755");
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut mode = root.get_mut("mode").context("missing mode")?;
+    /// assert!(mode.set_number_formatted("1_000_000"));
+    /// assert_eq!(mode.as_ref().as_u32(), Some(1_000_000));
+    /// assert_eq!(doc.to_string(), "mode: 1_000_000");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_number_formatted(&mut self, text: &str) -> bool {
+        let Ok(value) = Parser::parse_fragment(self.data, text.as_bytes()) else {
+            return false;
+        };
+
+        let number = match self.data.raw(value) {
+            Raw::Number(number) if self.data.str(number.string) == text => Some(number.clone()),
+            _ => None,
+        };
+
+        self.data.drop(value);
+
+        let Some(number) = number else {
+            return false;
+        };
+
+        self.data.replace(self.id, Raw::Number(number));
+        true
+    }
+
+    /// Replace the value with a deep copy of the value with the given
+    /// identifier from the same document.
+    ///
+    /// The copy is re-indented to fit its new position, and editing it
+    /// doesn't affect the original. Anchors are not copied.
+    ///
+    /// A detached copy made with [`Document::clone_value`] is moved into
+    /// place instead of being copied again, so its identifier can't be used
+    /// afterwards.
+    ///
+    /// [`Document::clone_value`]: crate::yaml::Document::clone_value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     defaults:
+    ///       image: nginx
+    ///       ports: [80]
+    ///     services:
+    ///       web:
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let defaults = root.get("defaults").context("missing defaults")?.id();
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut web = root
+    ///     .get_into_mut("services")
+    ///     .and_then(|v| v.into_mapping_mut())
+    ///     .and_then(|m| m.get_into_mut("web"))
+    ///     .context("missing web")?;
+    ///
+    /// web.copy_from(defaults);
+    /// web.as_mapping_mut().context("missing mapping")?.insert_str("image", "httpd");
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     defaults:
+    ///       image: nginx
+    ///       ports: [80]
+    ///     services:
+    ///       web:
+    ///         image: httpd
+    ///         ports: [80]
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn copy_from(&mut self, id: Id) {
+        let from = raw::indent_of(self.data, id);
+
+        let mut top = self.id;
+
+        while let Some(parent) = self.data.layout(top).parent {
+            top = parent;
+        }
+
+        // A detached copy can be moved as long as this value isn't inside of it.
+        let value = if self.data.layout(id).detached && top != id {
+            id
+        } else {
+            self.data.clone_tree(id, None)
+        };
+
+        self.place(value, from);
+        self.data.set_anchor(self.id, None);
+    }
+
+    /// Move the detached value with the given identifier into the place of
+    /// this value, re-indenting it from the indentation it was copied from as
+    /// determined by [`raw::indent_of`].
+    pub(crate) fn place(&mut self, value: Id, from: usize) {
+        let (to, prefix) = raw::make_indent(self.data, self.id, 0);
+        let from = isize::try_from(from).unwrap_or(isize::MAX);
+        let to = isize::try_from(to).unwrap_or(isize::MAX);
+        self.data.indent_by(value, to.saturating_sub(from));
+
+        let prefix = if self.data.raw(value).is_tabular() {
+            prefix
+        } else {
+            self.data.layout(self.id).prefix
+        };
+
+        self.data.relocate(value, self.id, prefix);
+    }
+
     /// Set the value as a boolean.
     ///
     /// # Examples
@@ -772,4 +1155,194 @@ impl<'a> ValueMut<'a> {
 
         SequenceMut::new(self.data, self.id)
     }
+
+    /// Make the value into a mapping with the given style, unless it already
+    /// is a mapping.
+    ///
+    /// With [`CollectionStyle::Block`] this behaves like
+    /// [`ValueMut::make_mapping`]. Block collections can't be nested inside of
+    /// flow collections, so [`CollectionStyle::Flow`] should be used for values
+    /// which are inside of one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("name: web")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// let mut env = root.insert("env", yaml::Separator::Auto).make_mapping_with(yaml::CollectionStyle::Flow);
+    /// env.insert_str("RUST_LOG", "debug");
+    /// env.insert_u32("PORT", 80);
+    ///
+    /// assert_eq!(doc.to_string(), "name: web\nenv: {RUST_LOG: debug, PORT: 80}");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn make_mapping_with(mut self, style: CollectionStyle) -> MappingMut<'a> {
+        if matches!(style, CollectionStyle::Block)
+            || matches!(self.data.raw(self.id), Raw::Mapping(..))
+        {
+            return self.make_mapping();
+        }
+
+        let prefix = self.flow_prefix();
+        let suffix = self.data.insert_str("");
+
+        self.data.replace_with(
+            self.id,
+            prefix,
+            Raw::Mapping(raw::Mapping {
+                indent: 0,
+                kind: raw::MappingKind::Inline {
+                    trailing: false,
+                    suffix,
+                },
+                items: Vec::new(),
+            }),
+        );
+
+        MappingMut::new(self.data, self.id)
+    }
+
+    /// Make the value into a sequence with the given style, unless it already
+    /// is a sequence.
+    ///
+    /// With [`CollectionStyle::Block`] this behaves like
+    /// [`ValueMut::make_sequence`]. Block collections can't be nested inside of
+    /// flow collections, so [`CollectionStyle::Flow`] should be used for values
+    /// which are inside of one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("name: web")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    ///
+    /// let mut block = root.insert("block", yaml::Separator::Auto).make_sequence_with(yaml::CollectionStyle::Block);
+    /// block.push_string("a");
+    /// block.push_string("b");
+    ///
+    /// let mut flow = root.insert("flow", yaml::Separator::Auto).make_sequence_with(yaml::CollectionStyle::Flow);
+    /// flow.push_string("a");
+    /// flow.push_string("b");
+    /// flow.push_string("c");
+    ///
+    /// assert_eq!(doc.to_string(), "name: web\nblock:\n  - a\n  - b\nflow: [a, b, c]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn make_sequence_with(mut self, style: CollectionStyle) -> SequenceMut<'a> {
+        if matches!(style, CollectionStyle::Block)
+            || matches!(self.data.raw(self.id), Raw::Sequence(..))
+        {
+            return self.make_sequence();
+        }
+
+        let prefix = self.flow_prefix();
+        let suffix = self.data.insert_str("");
+
+        self.data.replace_with(
+            self.id,
+            prefix,
+            Raw::Sequence(raw::Sequence {
+                indent: 0,
+                kind: raw::SequenceKind::Inline {
+                    trailing: false,
+                    suffix,
+                },
+                items: Vec::new(),
+            }),
+        );
+
+        SequenceMut::new(self.data, self.id)
+    }
+
+    /// Construct the prefix used by a flow collection replacing this value,
+    /// which starts on the same line as whatever precedes it.
+    fn flow_prefix(&mut self) -> StringId {
+        let prefix = self.data.layout(self.id).prefix;
+
+        if self.data.layout(self.id).parent.is_none() || raw::is_inline_item(self.data, self.id) {
+            return prefix;
+        }
+
+        let string = self.data.str(prefix);
+
+        if string.is_empty() || string.contains(&raw::NEWLINE) {
+            return self.data.insert_str(" ");
+        }
+
+        prefix
+    }
+
+    /// Make the value into a sequence, unless it already is one.
+    ///
+    /// If the value belongs to a tabular mapping the sequence is indented at
+    /// the same level as the key it belongs to. Otherwise this behaves like
+    /// [`ValueMut::make_sequence`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     first: second
+    ///     third: fourth
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    ///
+    /// let mut sequence = root.get_mut("first").context("missing first")?.make_indentless_sequence();
+    /// sequence.push_u32(1);
+    /// sequence.push_u32(2);
+    ///
+    /// let mut sequence = root.get_mut("third").context("missing third")?.make_sequence();
+    /// sequence.push_u32(3);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     first:
+    ///     - 1
+    ///     - 2
+    ///     third:
+    ///       - 3
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn make_indentless_sequence(self) -> SequenceMut<'a> {
+        if matches!(self.data.raw(self.id), Raw::Sequence(..)) {
+            return SequenceMut::new(self.data, self.id);
+        }
+
+        let Some((indent, prefix)) = raw::make_indentless(self.data, self.id) else {
+            return self.make_sequence();
+        };
+
+        self.data.replace_with(
+            self.id,
+            prefix,
+            Raw::Sequence(raw::Sequence {
+                indent,
+                kind: raw::SequenceKind::Mapping,
+                items: Vec::new(),
+            }),
+        );
+
+        SequenceMut::new(self.data, self.id)
+    }
 }
@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use nondestructive::yaml;
+
+#[test]
+fn pointer_escapes() -> Result<()> {
+    let doc = yaml::from_slice(
+        r#"
+        "~1": escaped tilde
+        "/": slash
+        "": empty
+        "0": [zero]
+        "#,
+    )?;
+
+    // `~01` is an escaped `~` followed by `1`, not an escaped `/`.
+    let value = doc.pointer("/~01").and_then(|v| v.as_str());
+    assert_eq!(value, Some("escaped tilde"));
+
+    assert_eq!(doc.pointer("/~1").and_then(|v| v.as_str()), Some("slash"));
+    assert_eq!(doc.pointer("/").and_then(|v| v.as_str()), Some("empty"));
+    assert_eq!(doc.pointer("/0/0").and_then(|v| v.as_str()), Some("zero"));
+
+    assert!(doc.pointer("/~2").is_none());
+    assert!(doc.pointer("/~").is_none());
+    assert!(doc.pointer("/0/-").is_none());
+    assert!(doc.pointer("/0/0/0").is_none());
+    Ok(())
+}
+
+#[test]
+fn pointer_mut() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        items:
+          - name: first
+          - name: second
+        ",
+    )?;
+
+    let mut item = doc
+        .pointer_mut("/items/1")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing item")?;
+    item.insert_u32("port", 80);
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        items:
+          - name: first
+          - name: second
+            port: 80
+        "
+    );
+    Ok(())
+}
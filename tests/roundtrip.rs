@@ -0,0 +1,247 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, ensure, Context, Result};
+use bstr::BStr;
+use nondestructive::yaml;
+
+/// Every document in the corpus must be reproduced exactly when it hasn't been
+/// modified.
+#[test]
+fn identity_roundtrip() -> Result<()> {
+    for path in corpus()? {
+        let input = fs::read(&path)?;
+        roundtrip(&input).with_context(|| anyhow!("{}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Fuzz the corpus by parsing variations of each document with lines removed.
+///
+/// Not every variation is a valid document, but those which can be parsed
+/// must round-trip exactly.
+#[test]
+fn identity_roundtrip_fuzz() -> Result<()> {
+    for path in corpus()? {
+        let input = fs::read(&path)?;
+        let lines = input.split_inclusive(|&b| b == b'\n').collect::<Vec<_>>();
+
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..256 {
+            let variation = lines
+                .iter()
+                .filter(|_| rng.next() % 4 != 0)
+                .flat_map(|line| line.iter().copied())
+                .collect::<Vec<_>>();
+
+            check(&variation).with_context(|| anyhow!("{}", path.display()))?;
+        }
+
+        for n in 0..=input.len() {
+            check(&input[..n]).with_context(|| anyhow!("{}: truncated at {n}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Trimming trailing whitespace doesn't change the meaning of a document, and
+/// trimming it again does nothing.
+#[test]
+fn trim_trailing_whitespace() -> Result<()> {
+    let mut inputs =
+        vec![b"a: b  \nc:   \n  - d \t\n  # comment  \n\ne: [1 ,  \n  2 ]  \n".to_vec()];
+
+    for path in corpus()? {
+        inputs.push(fs::read(&path)?);
+    }
+
+    for input in inputs {
+        let original = yaml::from_slice(&input)?;
+
+        let mut doc = original.clone();
+        doc.trim_trailing_whitespace();
+        let once = doc.to_string();
+
+        let reparsed = yaml::from_slice(&once)?;
+        ensure!(
+            reparsed.semantic_eq_ordered(&original),
+            "trimming changed the document: {:?}",
+            BStr::new(&once)
+        );
+
+        doc.trim_trailing_whitespace();
+        ensure_eq(once.as_bytes(), doc.to_string().as_bytes())?;
+
+        let mut doc = reparsed;
+        doc.trim_trailing_whitespace();
+        ensure_eq(once.as_bytes(), doc.to_string().as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Formatting a document for version control doesn't change its meaning, and
+/// formatting the output again does nothing.
+#[test]
+fn vcs_friendly() -> Result<()> {
+    let mut inputs = vec![b"- [a, {b: c, d: [e]}]\n- {f: \"g\"}\n".to_vec()];
+
+    for path in corpus()? {
+        inputs.push(fs::read(&path)?);
+    }
+
+    for input in inputs {
+        let original = yaml::from_slice(&input)?;
+        let once = original.to_string_vcs_friendly();
+
+        let reparsed = yaml::from_slice(&once)?;
+        ensure!(
+            reparsed.semantic_eq_ordered(&original),
+            "formatting changed the document: {:?}",
+            BStr::new(&once)
+        );
+
+        ensure_eq(
+            once.as_bytes(),
+            reparsed.to_string_vcs_friendly().as_bytes(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writing a document to an output produces the same bytes as formatting it,
+/// including after it has been modified.
+#[test]
+fn write_to_matches_display() -> Result<()> {
+    for path in corpus()? {
+        let input = fs::read(&path)?;
+        let mut doc = yaml::from_slice(&input)?;
+
+        doc.trim_trailing_whitespace();
+        doc.normalize_quotes(yaml::StringKind::Double);
+
+        if let Some(mut mapping) = doc.as_mut().into_mapping_mut() {
+            mapping.insert_str("added", "a value: which needs quoting");
+            mapping.insert_u32("number", 42);
+        } else if let Some(mut sequence) = doc.as_mut().into_sequence_mut() {
+            sequence
+                .insert(0, yaml::Separator::Auto)
+                .set_string("first");
+            sequence.push_u32(42);
+        }
+
+        let mut out = Vec::new();
+        doc.write_to(&mut out)?;
+        ensure_eq(doc.to_string().as_bytes(), &out)
+            .with_context(|| anyhow!("{}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Aliases which refer to a value they are contained in are not resolved, so
+/// documents which refer to themselves can be safely processed.
+#[test]
+fn self_referential_anchors() -> Result<()> {
+    for input in [
+        &b"a: &x [*x]\n"[..],
+        b"&x [*x]",
+        b"- &x [a, *x]\n- *x\n",
+        b"b: &y\nc: {d: *y}\n",
+    ] {
+        let doc = yaml::from_slice(input)?;
+        ensure_eq(input, doc.to_string().as_bytes())?;
+        ensure!(
+            doc.semantic_eq(&doc.clone()),
+            "document is not equal to itself"
+        );
+
+        let mut trimmed = doc.clone();
+        trimmed.trim_trailing_whitespace();
+        ensure!(trimmed.semantic_eq(&doc), "trimming changed the document");
+    }
+
+    let doc = yaml::from_slice("a: &x [*x]")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let a = root.get("a").context("missing a")?;
+    ensure!(a.anchor() == Some("x".into()));
+
+    let sequence = a.as_sequence().context("missing sequence")?;
+    let alias = sequence.get(0).context("missing alias")?;
+    ensure!(alias.alias() == Some("x".into()));
+    ensure!(alias.resolve_alias().is_none());
+
+    let error = alias
+        .try_resolve_alias()
+        .err()
+        .context("expected an error")?;
+    ensure!(*error.kind() == yaml::ErrorKind::AnchorCycle);
+    Ok(())
+}
+
+fn corpus() -> Result<Vec<PathBuf>> {
+    let root =
+        PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").context("missing CARGO_MANIFEST_DIR")?)
+            .join("tests");
+
+    let mut paths = Vec::new();
+
+    for dir in ["roundtrip", "yaml"] {
+        for e in fs::read_dir(root.join(dir))? {
+            let path = e?.path();
+
+            if path.extension().and_then(|e| e.to_str()) == Some("yaml") {
+                paths.push(path);
+            }
+        }
+    }
+
+    paths.sort();
+    Ok(paths)
+}
+
+fn roundtrip(input: &[u8]) -> Result<()> {
+    let doc = yaml::from_slice(input)?;
+    ensure_eq(input, doc.to_string().as_bytes())?;
+
+    let mut out = Vec::new();
+    doc.write_to(&mut out)?;
+    ensure_eq(input, &out)?;
+    Ok(())
+}
+
+fn check(input: &[u8]) -> Result<()> {
+    let Ok(doc) = yaml::from_slice(input) else {
+        return Ok(());
+    };
+
+    ensure_eq(input, doc.to_string().as_bytes())
+}
+
+fn ensure_eq(expected: &[u8], actual: &[u8]) -> Result<()> {
+    ensure!(
+        expected == actual,
+        "document did not round-trip\nexpected: {:?}\n  actual: {:?}",
+        BStr::new(expected),
+        BStr::new(actual)
+    );
+
+    Ok(())
+}
+
+/// Simple xorshift generator, so that fuzzing is deterministic.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
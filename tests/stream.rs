@@ -0,0 +1,48 @@
+use anyhow::{Context, Result};
+use nondestructive::yaml;
+
+#[test]
+fn stream_roundtrip() -> Result<()> {
+    for input in [
+        "",
+        "a: 1\n",
+        "---\n",
+        "---\n---\n",
+        "a: 1\n---\nb: 2\n",
+        "--- # first\na: 1\n...\n--- # second\nb: 2\n...\n",
+        "a: 1\n...\nb: 2\n",
+        "%YAML 1.2\n---\na: 1\n...\n%YAML 1.2\n---\nb: 2\n",
+        "- 1\n# between\n---\n- 2\n",
+        "--- |\n  text\n--- >\n  folded\n",
+    ] {
+        let stream = yaml::from_slice_stream(input)?;
+        assert_eq!(stream.to_string(), input);
+
+        let mut out = Vec::new();
+        stream.write_to(&mut out)?;
+        assert_eq!(out, input.as_bytes());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn stream_documents() -> Result<()> {
+    let stream = yaml::from_slice_stream("---\n---\na: 1\n...\nb\n--- |\n  text\n")?;
+    assert_eq!(stream.len(), 4);
+
+    let docs = stream.documents().collect::<Vec<_>>();
+    assert!(docs[0].as_ref().is_null());
+
+    let root = docs[1].as_ref().as_mapping().context("missing mapping")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(docs[2].as_ref().as_str(), Some("b"));
+    assert_eq!(docs[3].as_ref().as_str(), Some("text\n"));
+    Ok(())
+}
+
+#[test]
+fn stream_errors() {
+    assert!(yaml::from_slice_stream("a: 1\nb\n").is_err());
+    assert!(yaml::from_slice_stream("a: 1\n---\n{a: 1\n").is_err());
+}